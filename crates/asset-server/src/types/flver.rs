@@ -2,6 +2,7 @@ use std::error::Error;
 
 use bevy::{
     asset::{Asset, Handle, LoadContext},
+    log::warn,
     prelude::Mesh,
     reflect::Reflect,
     render::{
@@ -10,8 +11,9 @@ use bevy::{
     },
 };
 use fstools_formats::flver::{
-    face_set::FaceSetIndices, mesh::Mesh as FlverMesh, reader::VertexAttributeSemantic,
-    vertex_buffer::accessor::VertexAttributeAccessor, Flver,
+    mesh::Mesh as FlverMesh,
+    vertex_buffer::{accessor::VertexAttributeAccessor, VertexAttributeSemantic},
+    Flver,
 };
 
 use crate::asset_source::fast_path::FastPathAssetLoader;
@@ -19,12 +21,54 @@ use crate::asset_source::fast_path::FastPathAssetLoader;
 #[derive(Asset, Debug, Reflect)]
 pub struct FlverAsset {
     meshes: Vec<Handle<Mesh>>,
+    bones: Vec<FlverBone>,
+    dummies: Vec<FlverDummy>,
+    materials: Vec<FlverMaterial>,
+}
+
+#[derive(Debug, Reflect)]
+pub struct FlverBone {
+    pub name: String,
+    pub parent_index: Option<usize>,
+    pub translation: [f32; 3],
+    pub rotation: [f32; 3],
+    pub scale: [f32; 3],
+}
+
+#[derive(Debug, Reflect)]
+pub struct FlverDummy {
+    pub reference_id: u16,
+    pub parent_bone_index: Option<usize>,
+    pub attached_bone_index: Option<usize>,
+    pub position: [f32; 3],
+    pub forward: [f32; 3],
+    pub up_vector: [f32; 3],
+}
+
+#[derive(Debug, Reflect)]
+pub struct FlverMaterial {
+    pub name: String,
+    pub mtd: String,
+    /// Pairs of texture type and texture path.
+    pub textures: Vec<(String, String)>,
 }
 
 impl FlverAsset {
     pub fn meshes(&self) -> impl Iterator<Item = &Handle<Mesh>> {
         self.meshes.iter()
     }
+
+    pub fn bones(&self) -> &[FlverBone] {
+        &self.bones
+    }
+
+    pub fn dummies(&self) -> &[FlverDummy] {
+        &self.dummies
+    }
+
+    pub fn materials(&self) -> &[FlverMaterial] {
+        &self.materials
+    }
 }
 
 pub struct FlverAssetLoader;
@@ -45,39 +89,101 @@ impl FastPathAssetLoader for FlverAssetLoader {
         let mut meshes = Vec::with_capacity(flver.mesh_count());
 
         for (index, flver_mesh) in flver.meshes.iter().enumerate() {
-            let mesh_handle = load_context
-                .labeled_asset_scope(format!("mesh{}", index), |_| load_mesh(&flver, flver_mesh));
-
-            meshes.push(mesh_handle);
+            let mesh = match load_mesh(&flver, flver_mesh) {
+                Ok(mesh) => mesh,
+                Err(error) => {
+                    warn!(
+                        "Skipping mesh {index} of {}: {error}",
+                        load_context.path().display()
+                    );
+                    continue;
+                }
+            };
+
+            meshes.push(load_context.add_labeled_asset(format!("mesh{}", index), mesh));
         }
 
-        Ok(FlverAsset { meshes })
+        let bones = flver
+            .bones
+            .iter()
+            .map(|bone| FlverBone {
+                name: flver.bone_name(bone).unwrap_or_default(),
+                parent_index: bone.parent_index(),
+                translation: bone.translation(),
+                rotation: bone.rotation(),
+                scale: bone.scale(),
+            })
+            .collect();
+
+        let dummies = flver
+            .dummies
+            .iter()
+            .map(|dummy| FlverDummy {
+                reference_id: dummy.reference_id(),
+                parent_bone_index: dummy.parent_bone_index(),
+                attached_bone_index: dummy.attached_bone_index(),
+                position: dummy.position(),
+                forward: dummy.forward(),
+                up_vector: dummy.up_vector(),
+            })
+            .collect();
+
+        let materials = flver
+            .materials
+            .iter()
+            .map(|material| FlverMaterial {
+                name: flver.material_name(material).unwrap_or_default(),
+                mtd: flver.material_mtd(material).unwrap_or_default(),
+                textures: flver
+                    .material_textures(material)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|texture| {
+                        (
+                            flver.texture_type(texture).unwrap_or_default(),
+                            flver.texture_path(texture).unwrap_or_default(),
+                        )
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Ok(FlverAsset {
+            meshes,
+            bones,
+            dummies,
+            materials,
+        })
     }
 }
 
-fn load_mesh(flver: &Flver, flver_mesh: &FlverMesh) -> Mesh {
+/// Builds a mesh from the vertices and most detailed face set of a FLVER mesh, failing if they
+/// can't be read, e.g. because the face set is edge-compressed.
+fn load_mesh(flver: &Flver, flver_mesh: &FlverMesh) -> Result<Mesh, &'static str> {
     let mut mesh = Mesh::new(
         PrimitiveTopology::TriangleList,
         RenderAssetUsages::RENDER_WORLD,
     );
 
     let face_set = flver
-        .mesh_face_sets(flver_mesh)
-        .find(|set| set.is_lod0())
-        .expect("couldn't find main face set");
+        .mesh_lod_face_set(flver_mesh, 0)
+        .ok_or("couldn't find main face set")?;
 
     let buffer = flver
         .mesh_buffers(flver_mesh)
         .next()
-        .expect("no vertex buffers for FLVER");
+        .ok_or("no vertex buffers for mesh")?;
 
-    let layout = &flver.vertex_buffer_layouts[buffer.layout_index.get() as usize];
+    let layout = flver
+        .vertex_buffer_layouts
+        .get(buffer.layout_index.get() as usize)
+        .ok_or("vertex buffer layout out of bounds")?;
     let layout_members = flver.vertex_attributes(layout);
 
     for member in layout_members {
-        use fstools_formats::flver::reader::VertexAttributeSemantic::*;
+        use VertexAttributeSemantic::*;
 
-        let semantic = VertexAttributeSemantic::from(member.semantic_id.get());
+        let semantic = member.semantic();
         let Some(accessor) = flver.vertex_attribute_accessor(buffer, member) else {
             continue;
         };
@@ -91,14 +197,22 @@ fn load_mesh(flver: &Flver, flver_mesh: &FlverMesh) -> Mesh {
                 Mesh::ATTRIBUTE_NORMAL,
                 VertexAttributeValues::Float32x3(it.collect()),
             ),
-            (Normal, VertexAttributeAccessor::SNorm8x4(it)) => (
+            (Normal, VertexAttributeAccessor::BiasedNorm8x4(it)) => (
                 Mesh::ATTRIBUTE_NORMAL,
                 VertexAttributeValues::Float32x3(it.map(|f| [f[0], f[1], f[2]]).collect()),
             ),
-            (UV, VertexAttributeAccessor::UV(it)) => (
+            (Normal, VertexAttributeAccessor::SNorm16x4(it)) => (
+                Mesh::ATTRIBUTE_NORMAL,
+                VertexAttributeValues::Float32x3(it.map(|f| [f[0], f[1], f[2]]).collect()),
+            ),
+            (UV, VertexAttributeAccessor::UV(it)) if member.index.get() == 0 => (
                 Mesh::ATTRIBUTE_UV_0,
                 VertexAttributeValues::Float32x2(it.collect()),
             ),
+            (UV, VertexAttributeAccessor::UVPair(it)) if member.index.get() == 0 => (
+                Mesh::ATTRIBUTE_UV_0,
+                VertexAttributeValues::Float32x2(it.map(|[u, v, _, _]| [u, v]).collect()),
+            ),
             _ => {
                 continue;
             }
@@ -107,15 +221,10 @@ fn load_mesh(flver: &Flver, flver_mesh: &FlverMesh) -> Mesh {
         mesh.insert_attribute(attribute, values);
     }
 
-    let indices = match flver.face_set_indices(face_set) {
-        Some(FaceSetIndices::U8(data)) => {
-            Indices::U16(data.iter().map(|index| *index as u16).collect())
-        }
-        Some(FaceSetIndices::U16(data)) => Indices::U16(data.iter().map(|val| val.get()).collect()),
-        Some(FaceSetIndices::U32(data)) => Indices::U32(data.iter().map(|val| val.get()).collect()),
-        _ => unimplemented!(),
-    };
+    let indices = flver
+        .face_set_triangles(face_set)
+        .ok_or("couldn't read face set indices")?;
 
-    mesh.insert_indices(indices);
-    mesh
+    mesh.insert_indices(Indices::U32(indices));
+    Ok(mesh)
 }
@@ -1,10 +1,11 @@
 use std::{error::Error, io::Cursor};
 
+use byteorder::ByteOrder;
 use fstools_dvdbnd::DvdBnd;
 use fstools_formats::{
     bnd4::BND4,
     entryfilelist::EntryFileList,
-    flver::reader::FLVER,
+    flver::{AnyFlver, FlverInner},
     msb,
     msb::{
         event,
@@ -67,40 +68,98 @@ pub fn describe_flver(
     nested_bnd_names: &[String],
 ) -> Result<(), Box<dyn Error>> {
     let (dcx, data) = dvd_bnd.read_file(nested_bnd_names, name)?;
-    let flver = FLVER::from_reader(&mut Cursor::new(data))?;
-
     println!("Compression type: {}", dcx);
-    println!("Version: 0x{:X}", flver.version);
-    println!("Bounding Box Min: {}", flver.bounding_box_min);
-    println!("Bounding Box Max: {}", flver.bounding_box_max);
-    println!("Faces: {}", flver.face_count);
+
+    match AnyFlver::parse(&data)? {
+        AnyFlver::LittleEndian(flver) => print_flver(&flver),
+        AnyFlver::BigEndian(flver) => {
+            println!("Big endian");
+            print_flver(&flver);
+        }
+    }
+
+    Ok(())
+}
+
+fn print_flver<O: ByteOrder + 'static>(flver: &FlverInner<O>) {
+    println!("Version: 0x{:X}", flver.version());
+    println!("Bounding Box Min: {:?}", flver.bounding_box_min());
+    println!("Bounding Box Max: {:?}", flver.bounding_box_max());
+    println!("Faces: {}", flver.face_count());
     println!("Index Buffers: {}", flver.face_sets.len());
     println!("Vertex Buffers: {}", flver.vertex_buffers.len());
+
     println!("Bones: {}", flver.bones.len());
+    for (idx, bone) in flver.bones.iter().enumerate() {
+        print!("Bone[{idx}] {}", flver.bone_name(bone).unwrap_or_default());
+        print!(" parent: {:?},", bone.parent_index());
+        print!(" translation: {:?},", bone.translation());
+        print!(" rotation: {:?},", bone.rotation());
+        println!(" scale: {:?}", bone.scale());
+    }
+
     println!("Dummies: {}", flver.dummies.len());
+    for (idx, dummy) in flver.dummies.iter().enumerate() {
+        print!("Dummy[{idx}] ref: {},", dummy.reference_id());
+        print!(" parent bone: {:?},", dummy.parent_bone_index());
+        print!(" attach bone: {:?},", dummy.attached_bone_index());
+        println!(" position: {:?}", dummy.position());
+    }
 
     println!("Materials: {}", flver.materials.len());
-    for idx in 0..flver.materials.len() {
-        println!("Material[{idx}] {}", flver.materials[idx].mtd);
+    for (idx, material) in flver.materials.iter().enumerate() {
+        println!(
+            "Material[{idx}] {} {}",
+            flver.material_name(material).unwrap_or_default(),
+            flver.material_mtd(material).unwrap_or_default()
+        );
+        for texture in flver.material_textures(material).unwrap_or_default() {
+            println!(
+                "  {}: {}",
+                flver.texture_type(texture).unwrap_or_default(),
+                flver.texture_path(texture).unwrap_or_default()
+            );
+        }
+        for item in flver.material_gx_items(material).unwrap_or_default() {
+            println!(
+                "  GX {} ({} bytes)",
+                item.id_str().unwrap_or("?"),
+                item.data.len()
+            );
+        }
     }
 
     println!("Meshes: {}", flver.meshes.len());
-    for idx in 0..flver.meshes.len() {
+    for (idx, mesh) in flver.meshes.iter().enumerate() {
+        let face_set_indices: Vec<_> =
+            mesh_part_indices(flver.face_sets, flver.mesh_face_sets(mesh));
+        let buffer_indices: Vec<_> =
+            mesh_part_indices(flver.vertex_buffers, flver.mesh_buffers(mesh));
+
         print!("Mesh[{idx}]");
-        print!(" bone: {},", flver.meshes[idx].default_bone_index);
-        print!(" material: {},", flver.meshes[idx].material_index);
-        print!(" dynamic: {},", flver.meshes[idx].dynamic);
-        print!(
-            " Index Buffers: {:?},",
-            flver.meshes[idx].face_set_indices.as_slice()
-        );
-        println!(
-            " Vertex Buffers: {:?}",
-            flver.meshes[idx].vertex_buffer_indices.as_slice()
-        );
+        print!(" bone: {:?},", mesh.default_bone_index());
+        print!(" material: {},", mesh.material_index());
+        print!(" dynamic: {},", mesh.is_dynamic());
+        print!(" Index Buffers: {:?},", face_set_indices);
+        println!(" Vertex Buffers: {:?}", buffer_indices);
     }
 
-    Ok(())
+    let report = flver.validate();
+    println!("Validation Issues: {}", report.issues.len());
+    for issue in report.issues {
+        println!("  {issue}");
+    }
+}
+
+/// Finds the indices of the parts referenced by a mesh in the list of all parts of that kind.
+fn mesh_part_indices<'a, T>(parts: &'a [T], referenced: impl Iterator<Item = &'a T>) -> Vec<usize> {
+    referenced
+        .filter_map(|part| {
+            parts
+                .iter()
+                .position(|candidate| std::ptr::eq(candidate, part))
+        })
+        .collect()
 }
 
 pub fn describe_matbin(
@@ -11,11 +11,13 @@ use crate::{
         describe_bnd, describe_entryfilelist, describe_flver, describe_matbin, describe_msb,
     },
     extract::extract,
+    textures::export_textures,
 };
 
 mod describe;
 mod extract;
 mod repl;
+mod textures;
 
 #[derive(Debug, Parser)]
 #[command(version, about, long_about = None)]
@@ -87,6 +89,30 @@ pub enum Action {
         output_path: PathBuf,
     },
 
+    /// Export the textures of TPFs as DDS files, or as PNG previews of their top mip.
+    ExportTextures {
+        #[arg(
+            short,
+            long,
+            required = false,
+            value_delimiter = ',',
+            help = "Chain of nested bnd names. Required to export TPFs therein."
+        )]
+        nested_bnd_names: Vec<String>,
+
+        /// Names of the TPFs to export.
+        #[arg(required = true)]
+        names: Vec<String>,
+
+        /// Path to a folder that textures will be exported to, with a subfolder for every TPF.
+        #[arg(short, long, default_value("./textures"))]
+        output_path: PathBuf,
+
+        /// Decode the textures and write PNG files instead of DDS files?
+        #[arg(long)]
+        png: bool,
+    },
+
     Repl,
 }
 
@@ -135,6 +161,14 @@ impl Action {
             } => {
                 extract(dvd_bnd, recursive, filter, output_path, *game_type)?;
             }
+            Action::ExportTextures {
+                nested_bnd_names,
+                names,
+                output_path,
+                png,
+            } => {
+                export_textures(dvd_bnd, &names, &nested_bnd_names, &output_path, png)?;
+            }
             Action::Repl => {
                 repl::begin(dvd_bnd, game_type)?;
             }
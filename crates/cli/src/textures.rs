@@ -0,0 +1,48 @@
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{BufWriter, Cursor},
+    path::Path,
+};
+
+use fstools_dvdbnd::DvdBnd;
+use fstools_formats::tpf::TPF;
+
+pub fn export_textures(
+    dvd_bnd: &DvdBnd,
+    names: &[String],
+    nested_bnd_names: &[String],
+    output_path: &Path,
+    png: bool,
+) -> Result<(), Box<dyn Error>> {
+    let mut count = 0;
+
+    for name in names {
+        let (_, data) = dvd_bnd.read_file(nested_bnd_names, name)?;
+        let mut cursor = Cursor::new(&data);
+        let tpf = TPF::from_reader(&mut cursor)?;
+
+        let file_name = name.rsplit(['\\', '/']).next().unwrap_or(name);
+        let tpf_name = file_name.split('.').next().unwrap_or(file_name);
+        let parent_path = output_path.join(tpf_name);
+        fs::create_dir_all(&parent_path)?;
+
+        for texture in &tpf.textures {
+            let bytes = texture.bytes(&mut cursor)?;
+            let bytes = tpf.linear_data(texture, &bytes)?;
+
+            if png {
+                let image = texture.to_rgba(&bytes, None)?;
+                let file = File::create(parent_path.join(format!("{}.png", texture.name)))?;
+                image.write_png(BufWriter::new(file))?;
+            } else {
+                let dds = texture.to_dds(&bytes, None)?;
+                fs::write(parent_path.join(format!("{}.dds", texture.name)), dds)?;
+            }
+            count += 1;
+        }
+    }
+
+    println!("Exported {count} textures");
+    Ok(())
+}
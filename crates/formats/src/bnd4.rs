@@ -1,6 +1,7 @@
-use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
 
-use byteorder::{ReadBytesExt, LE};
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use encoding_rs::SHIFT_JIS;
 
 use crate::io_ext::ReadFormatsExt;
 
@@ -52,7 +53,7 @@ impl BND4 {
 
         let mut files = vec![];
         for _ in 0..file_count {
-            files.push(BND4Entry::from_reader(&mut r)?);
+            files.push(BND4Entry::from_reader(&mut r, unicode)?);
         }
 
         let mut data = vec![];
@@ -97,6 +98,181 @@ impl BND4 {
     pub fn normalize_path(path: &str) -> String {
         path.replace("N:\\", "").to_lowercase().replace('\\', "/")
     }
+
+    /// Copies the files of this archive so they can be modified and written with
+    /// [`BND4::write`].
+    pub fn owned_files(&self) -> Vec<BND4File> {
+        self.files
+            .iter()
+            .map(|entry| BND4File {
+                flags: entry.flags,
+                unk4: entry.unk4,
+                id: entry.id,
+                path: entry.path.clone(),
+                data: self.file_bytes(entry).to_vec(),
+            })
+            .collect()
+    }
+
+    /// Writes an archive containing `files`, using the header fields of this archive.
+    ///
+    /// Files are written uncompressed with the same file header layout as the one read by
+    /// [`BND4::from_reader`]. Paths are written as UTF-16 if the archive is unicode and as
+    /// Shift-JIS otherwise. If the archive is extended, a path hash table is written.
+    pub fn write<W: Write>(&self, mut writer: W, files: &[BND4File]) -> io::Result<()> {
+        let mut w = Cursor::new(Vec::new());
+        let file_header_size = self.file_header_size.max(0x24);
+
+        w.write_all(b"BND4")?;
+        w.write_u8(self.unk04)?;
+        w.write_u8(self.unk05)?;
+        w.write_all(&[0; 3])?;
+        w.write_u8(0)?;
+        w.write_u8(self.unk0a)?;
+        w.write_u8(0)?;
+        w.write_u32::<LE>(files.len() as u32)?;
+        w.write_u64::<LE>(0x40)?;
+        w.write_u64::<LE>(self.version)?;
+        w.write_u64::<LE>(file_header_size)?;
+        let headers_end_pos = w.position();
+        w.write_u64::<LE>(0)?;
+        w.write_u8(self.unicode as u8)?;
+        w.write_u8(self.raw_format)?;
+        w.write_u8(self.extended)?;
+        w.write_all(&[0; 5])?;
+        let buckets_offset_pos = w.position();
+        w.write_u64::<LE>(0)?;
+
+        for file in files {
+            w.write_u8(file.flags)?;
+            w.write_all(&[0; 3])?;
+            w.write_i32::<LE>(file.unk4)?;
+            w.write_u64::<LE>(file.data.len() as u64)?;
+            w.write_u64::<LE>(file.data.len() as u64)?;
+            // Data and name offsets are filled in below
+            w.write_u32::<LE>(0)?;
+            w.write_u32::<LE>(file.id)?;
+            w.write_u32::<LE>(0)?;
+            w.write_all(&vec![0; file_header_size as usize - 0x24])?;
+        }
+
+        let mut name_offsets = Vec::with_capacity(files.len());
+        for file in files {
+            name_offsets.push(w.position() as u32);
+            if self.unicode {
+                for unit in file.path.encode_utf16().chain([0]) {
+                    w.write_u16::<LE>(unit)?;
+                }
+            } else {
+                let (bytes, _, _) = SHIFT_JIS.encode(&file.path);
+                w.write_all(&bytes)?;
+                w.write_u8(0)?;
+            }
+        }
+
+        if self.extended == 4 {
+            pad(&mut w, 8)?;
+            let buckets_offset = w.position();
+            write_hash_table(&mut w, files)?;
+            patch_u64(&mut w, buckets_offset_pos, buckets_offset)?;
+        }
+
+        let headers_end = w.position();
+        patch_u64(&mut w, headers_end_pos, headers_end)?;
+
+        for (index, file) in files.iter().enumerate() {
+            pad(&mut w, 0x10)?;
+            let data_offset = w.position() as u32;
+            w.write_all(&file.data)?;
+
+            let header_pos = 0x40 + index as u64 * file_header_size;
+            let end = w.position();
+            w.seek(SeekFrom::Start(header_pos + 0x18))?;
+            w.write_u32::<LE>(data_offset)?;
+            w.seek(SeekFrom::Start(header_pos + 0x20))?;
+            w.write_u32::<LE>(name_offsets[index])?;
+            w.seek(SeekFrom::Start(end))?;
+        }
+
+        writer.write_all(&w.into_inner())
+    }
+}
+
+/// An owned file to be written to a BND4 archive with [`BND4::write`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct BND4File {
+    pub flags: u8,
+    /// Usually -1.
+    pub unk4: i32,
+    pub id: u32,
+    pub path: String,
+    pub data: Vec<u8>,
+}
+
+fn pad(w: &mut Cursor<Vec<u8>>, alignment: u64) -> io::Result<()> {
+    let padding = (alignment - w.position() % alignment) % alignment;
+    w.write_all(&vec![0; padding as usize])
+}
+
+fn patch_u64(w: &mut Cursor<Vec<u8>>, position: u64, value: u64) -> io::Result<()> {
+    let end = w.position();
+    w.seek(SeekFrom::Start(position))?;
+    w.write_u64::<LE>(value)?;
+    w.seek(SeekFrom::Start(end))?;
+    Ok(())
+}
+
+/// Hash of a file path as used by the hash table of extended BND4 archives.
+fn path_hash(path: &str) -> u32 {
+    let path = path.to_lowercase().replace('\\', "/");
+    let prefix = if path.starts_with('/') { "" } else { "/" };
+
+    prefix
+        .chars()
+        .chain(path.chars())
+        .fold(0u32, |hash, c| hash.wrapping_mul(37).wrapping_add(c as u32))
+}
+
+// Follows SoulsFormats' BinderHashTable
+fn write_hash_table(w: &mut Cursor<Vec<u8>>, files: &[BND4File]) -> io::Result<()> {
+    let is_prime = |p: u32| {
+        p >= 2
+            && (2..)
+                .take_while(|d| d * d <= p)
+                .all(|d| !p.is_multiple_of(d))
+    };
+    let bucket_count = (files.len() as u32 / 7..)
+        .find(|p| is_prime(*p))
+        .unwrap_or(2);
+
+    let mut buckets: Vec<Vec<(u32, u32)>> = vec![Vec::new(); bucket_count as usize];
+    for (index, file) in files.iter().enumerate() {
+        let hash = path_hash(&file.path);
+        buckets[(hash % bucket_count) as usize].push((hash, index as u32));
+    }
+
+    // The offset of the path hashes is filled in once the buckets are written
+    let hashes_offset_pos = w.position();
+    w.write_u64::<LE>(0)?;
+    w.write_u32::<LE>(bucket_count)?;
+    w.write_all(&[0x10, 8, 8, 0])?;
+
+    let mut start = 0u32;
+    for bucket in &mut buckets {
+        bucket.sort_by_key(|(hash, _)| *hash);
+        w.write_u32::<LE>(bucket.len() as u32)?;
+        w.write_u32::<LE>(start)?;
+        start += bucket.len() as u32;
+    }
+
+    let hashes_offset = w.position();
+    patch_u64(w, hashes_offset_pos, hashes_offset)?;
+    for (hash, index) in buckets.iter().flatten() {
+        w.write_u32::<LE>(*hash)?;
+        w.write_u32::<LE>(*index)?;
+    }
+
+    Ok(())
 }
 
 #[derive(Debug, PartialEq)]
@@ -111,7 +287,8 @@ pub struct BND4Entry {
 }
 
 impl BND4Entry {
-    pub fn from_reader<R: Read + Seek>(mut r: R) -> Result<Self, io::Error> {
+    /// Reads a file header. Its path is UTF-16 if `unicode` is set and Shift-JIS otherwise.
+    pub fn from_reader<R: Read + Seek>(mut r: R, unicode: bool) -> Result<Self, io::Error> {
         let flags = r.read_u8()?;
         r.read_padding(3)?;
 
@@ -124,7 +301,11 @@ impl BND4Entry {
 
         let current = r.stream_position()?;
         r.seek(SeekFrom::Start(name_offset as u64))?;
-        let path = r.read_utf16::<LE>()?;
+        let path = if unicode {
+            r.read_utf16::<LE>()?
+        } else {
+            r.read_shift_jis()?
+        };
         r.seek(SeekFrom::Start(current))?;
 
         assert!(
@@ -151,3 +332,114 @@ impl BND4Entry {
         Ok(buffer)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use encoding_rs::SHIFT_JIS;
+
+    use super::{path_hash, BND4File, BND4};
+
+    #[test]
+    pub fn round_trips_file_headers() {
+        let archive = BND4 {
+            unk04: 0,
+            unk05: 1,
+            unk0a: 0,
+            file_count: 0,
+            file_headers_offset: 0x40,
+            version: 0x3637_5237_4437_3030,
+            file_header_size: 0x24,
+            file_headers_end: 0,
+            unicode: true,
+            raw_format: 0x74,
+            extended: 4,
+            buckets_offset: 0,
+            files: Vec::new(),
+            data: Vec::new(),
+        };
+        let files = [-1, 0x1234].map(|unk4| BND4File {
+            flags: 0x40,
+            unk4,
+            id: unk4 as u32,
+            path: format!("N:\\test\\{unk4}.bin"),
+            data: vec![unk4 as u8; 3],
+        });
+
+        let mut bytes = Vec::new();
+        archive.write(&mut bytes, &files).expect("written");
+        let parsed = BND4::from_reader(Cursor::new(&bytes)).expect("valid BND4");
+        assert_eq!(parsed.owned_files(), files);
+
+        let mut rewritten = Vec::new();
+        parsed
+            .write(&mut rewritten, &parsed.owned_files())
+            .expect("written");
+        assert_eq!(rewritten, bytes);
+    }
+
+    fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+        u64::from_le_bytes(bytes[offset..offset + 8].try_into().expect("8 bytes"))
+    }
+
+    fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().expect("4 bytes"))
+    }
+
+    #[test]
+    pub fn writes_path_hashes_offset_and_shift_jis_names() {
+        let mut archive = BND4 {
+            unk04: 0,
+            unk05: 1,
+            unk0a: 0,
+            file_count: 0,
+            file_headers_offset: 0x40,
+            version: 0,
+            file_header_size: 0x24,
+            file_headers_end: 0,
+            unicode: true,
+            raw_format: 0x74,
+            extended: 4,
+            buckets_offset: 0,
+            files: Vec::new(),
+            data: Vec::new(),
+        };
+        let files: Vec<BND4File> = (0..10)
+            .map(|index| BND4File {
+                flags: 0x40,
+                unk4: -1,
+                id: index,
+                path: format!("N:\\テスト\\{index}.bin"),
+                data: vec![index as u8],
+            })
+            .collect();
+
+        let mut bytes = Vec::new();
+        archive.write(&mut bytes, &files).expect("written");
+
+        // The hash table starts with the offset of the path hashes, which follow the buckets
+        let buckets_offset = read_u64(&bytes, 0x38) as usize;
+        let bucket_count = read_u32(&bytes, buckets_offset + 8) as usize;
+        let hashes_offset = read_u64(&bytes, buckets_offset) as usize;
+        assert_eq!(hashes_offset, buckets_offset + 0x10 + bucket_count * 8);
+        let mut hashes: Vec<(u32, u32)> = (0..files.len())
+            .map(|index| {
+                let offset = hashes_offset + index * 8;
+                (read_u32(&bytes, offset), read_u32(&bytes, offset + 4))
+            })
+            .collect();
+        hashes.sort_by_key(|(_, index)| *index);
+        assert_eq!(hashes[3], (path_hash(&files[3].path), 3));
+
+        archive.unicode = false;
+        let mut bytes = Vec::new();
+        archive.write(&mut bytes, &files).expect("written");
+        let (name, _, _) = SHIFT_JIS.encode(&files[0].path);
+        assert!(bytes.windows(name.len()).any(|window| window == &name[..]));
+
+        let parsed = BND4::from_reader(Cursor::new(&bytes)).expect("valid BND4");
+        assert!(!parsed.unicode);
+        assert_eq!(parsed.owned_files(), files);
+    }
+}
@@ -1,6 +1,6 @@
-use std::io::{self, Read};
+use std::io::{self, Read, Write};
 
-use flate2::read::ZlibDecoder;
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
 
 pub struct DeflateDecoder<R: Read>(ZlibDecoder<R>);
 
@@ -15,3 +15,10 @@ impl<R: Read> Read for DeflateDecoder<R> {
         self.0.read(buf)
     }
 }
+
+/// Compresses `data` into a zlib stream using the compression level used by the games.
+pub fn compress(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
@@ -6,7 +6,7 @@ use std::{
 
 use byteorder::BE;
 use thiserror::Error;
-use zerocopy::{FromBytes, FromZeroes, U32};
+use zerocopy::{AsBytes, FromBytes, FromZeroes, U32};
 use zstd::ZstdDecoder;
 
 use self::{deflate::DeflateDecoder, oodle::OodleReader};
@@ -33,6 +33,9 @@ pub enum DcxError {
 
     #[error("Unable to create compression codec for DCX contents")]
     DecoderError,
+
+    #[error("Compressing DCX contents with {0} is not supported, only DFLT and ZSTD are")]
+    UnsupportedCompression(String),
 }
 
 #[derive(Debug, Error)]
@@ -44,7 +47,7 @@ pub enum DecompressionError {
     Zlib,
 }
 
-#[derive(FromBytes, FromZeroes)]
+#[derive(AsBytes, FromBytes, FromZeroes)]
 #[repr(C, packed)]
 pub struct DcxHeader {
     metadata: Metadata,
@@ -84,6 +87,38 @@ impl DcxHeader {
         })
     }
 
+    /// Compresses `data` with the algorithm and parameters of this header, returning the complete
+    /// DCX file.
+    ///
+    /// Only the DFLT (zlib) and ZSTD algorithms can be compressed. Files using KRAK (Oodle
+    /// Kraken) can be read but not written.
+    ///
+    /// # Errors
+    /// Returns [`DcxError::UnsupportedCompression`] if the algorithm of this header is not one of
+    /// the above.
+    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>, DcxError> {
+        let algorithm = &self.compression_parameters.algorithm;
+        let compressed = match algorithm {
+            MAGIC_ALGORITHM_DEFLATE => deflate::compress(data)?,
+            MAGIC_ALGORITHM_ZSTD => zstd::compress(data)?,
+            _ => {
+                return Err(DcxError::UnsupportedCompression(
+                    String::from_utf8_lossy(algorithm).into_owned(),
+                ))
+            }
+        };
+
+        let mut header = DcxHeader::read_from(self.as_bytes()).ok_or(DcxError::ParserError)?;
+        header.sizes.uncompressed_size =
+            U32::new(u32::try_from(data.len()).map_err(|_| DcxError::ParserError)?);
+        header.sizes.compressed_size =
+            U32::new(u32::try_from(compressed.len()).map_err(|_| DcxError::ParserError)?);
+
+        let mut output = header.as_bytes().to_vec();
+        output.extend_from_slice(&compressed);
+        Ok(output)
+    }
+
     pub fn has_magic(buf: &[u8]) -> bool {
         match U32::<BE>::ref_from_prefix(buf) {
             Some(v) => v.get() == MAGIC_DCX,
@@ -143,7 +178,7 @@ impl<R: Read> Read for DcxContentDecoder<R> {
     }
 }
 
-#[derive(FromZeroes, FromBytes)]
+#[derive(AsBytes, FromZeroes, FromBytes)]
 #[repr(C)]
 #[allow(unused)]
 /// The DCX chunk. Describes the layout of the container.
@@ -174,7 +209,7 @@ impl Debug for Metadata {
     }
 }
 
-#[derive(FromZeroes, FromBytes)]
+#[derive(AsBytes, FromZeroes, FromBytes)]
 #[repr(C)]
 #[allow(unused)]
 /// The DCS Chunk. Describes the sizes before and after compression.
@@ -197,7 +232,7 @@ impl Debug for Sizes {
     }
 }
 
-#[derive(FromZeroes, FromBytes)]
+#[derive(AsBytes, FromZeroes, FromBytes)]
 #[repr(C, packed)]
 #[allow(unused)]
 /// The DCP chunk. Describes parameters used for compression/decompression.
@@ -215,6 +250,13 @@ pub struct CompressionParameters {
     settings: [u8; 20],
 }
 
+impl CompressionParameters {
+    /// Magic of the compression algorithm, e.g. `ZSTD`.
+    pub fn algorithm(&self) -> &[u8; 4] {
+        &self.algorithm
+    }
+}
+
 impl Debug for CompressionParameters {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let algorithm_name = String::from_utf8_lossy(&self.algorithm);
@@ -225,7 +267,7 @@ impl Debug for CompressionParameters {
     }
 }
 
-#[derive(FromZeroes, FromBytes, Debug)]
+#[derive(AsBytes, FromZeroes, FromBytes, Debug)]
 #[repr(C)]
 #[allow(unused)]
 /// The DCA chunk. Describes ???
@@ -5,7 +5,8 @@ pub struct ZstdDecoder<R: Read>(zstd::Decoder<'static, io::BufReader<R>>);
 
 impl<R: Read> ZstdDecoder<R> {
     pub fn new(reader: R) -> io::Result<Self> {
-        Ok(Self(zstd::Decoder::new(reader)?))
+        // Encrypted containers are padded to the block size after the frame
+        Ok(Self(zstd::Decoder::new(reader)?.single_frame()))
     }
 }
 
@@ -14,3 +15,8 @@ impl<R: Read> Read for ZstdDecoder<R> {
         self.0.read(buf)
     }
 }
+
+/// Compresses `data` into a zstd frame.
+pub fn compress(data: &[u8]) -> io::Result<Vec<u8>> {
+    zstd::encode_all(data, zstd::DEFAULT_COMPRESSION_LEVEL)
+}
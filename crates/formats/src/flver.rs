@@ -4,36 +4,55 @@ use std::{
     ops::Deref,
 };
 
-use byteorder::{ByteOrder, LE};
+use byteorder::{ByteOrder, BE, LE};
+use encoding_rs::SHIFT_JIS;
 use header::FlverHeader;
 use vertex_buffer::accessor::{
     VertexAttributeAccessor as Accessor, VertexAttributeAccessor, VertexAttributeIter as Iter,
+    VertexAttributeScaledIter as ScaledIter,
 };
-use zerocopy::{FromBytes, Ref, U16, U32};
+use zerocopy::{FromBytes, Ref, F32, U16, U32};
 
 use crate::{
     flver::{
         bone::Bone,
         dummy::Dummy,
-        face_set::{FaceSet, FaceSetIndices},
+        face_set::{strip_restart_index, unroll_triangle_strip, FaceSet, FaceSetIndices},
         header::FlverHeaderPart,
-        material::Material,
-        mesh::Mesh,
+        material::{gx_items, GxItem, Material},
+        mesh::{Mesh, MeshBoundingBox},
         texture::Texture,
+        transform::{transform_point, Mat4},
         vertex_buffer::{VertexBuffer, VertexBufferAttribute, VertexBufferLayout},
     },
-    io_ext::ReadFormatsExt,
+    io_ext::{read_wide_cstring, ReadFormatsExt},
 };
 
 pub mod bone;
 pub mod dummy;
+pub mod export;
 pub mod face_set;
+pub mod flver0;
 mod header;
+pub mod import;
 pub mod material;
 pub mod mesh;
-pub mod reader;
+pub mod owned;
 pub mod texture;
+pub mod transform;
+pub mod validate;
 pub mod vertex_buffer;
+mod writer;
+
+/// FLVER0 versions, used by Demon's Souls and some Dark Souls models, are all below this.
+pub(crate) const FLVER0_VERSION_END: u32 = 0x20000;
+
+/// FLVER2 versions that can be written. [`FlverInner::parse`] reads every FLVER2 version up to the
+/// newest of these.
+pub(crate) const ALLOWED_VERSIONS: [u32; 2] = [
+    0x2001A, // Elden Ring
+    0x20021, // Nightreign
+];
 
 pub type Flver<'a> = FlverInner<'a, LE>;
 
@@ -46,18 +65,16 @@ pub struct FlverInner<'a, O: ByteOrder> {
 
     /// The data region of this FLVER, containing vertex buffers and strings.
     data: &'a [u8],
-    bones: &'a [Bone<O>],
-    dummys: &'a [Dummy<O>],
+    pub bones: &'a [Bone<O>],
+    pub dummies: &'a [Dummy<O>],
     pub face_sets: &'a [FaceSet<O>],
-    materials: &'a [Material<O>],
+    pub materials: &'a [Material<O>],
     pub meshes: &'a [Mesh<O>],
-    textures: &'a [Texture<O>],
+    pub textures: &'a [Texture<O>],
     pub vertex_buffers: &'a [VertexBuffer<O>],
     pub vertex_buffer_layouts: &'a [VertexBufferLayout<O>],
 }
 
-impl<'a, O: ByteOrder> FlverInner<'a, O> {}
-
 impl<'a, O: ByteOrder + 'static> Deref for FlverInner<'a, O> {
     type Target = FlverHeader<O>;
 
@@ -67,13 +84,26 @@ impl<'a, O: ByteOrder + 'static> Deref for FlverInner<'a, O> {
 }
 
 impl<'a, O: ByteOrder + 'static> FlverInner<'a, O> {
+    /// The indices of a face set as they are stored. Edge-compressed face sets aren't supported.
     pub fn face_set_indices(&self, face_set: &'a FaceSet<O>) -> Option<FaceSetIndices<'a, O>> {
-        let index_size = face_set.index_size.get() as usize;
+        if face_set.is_edge_compressed() {
+            return None;
+        }
+
+        self.face_set_data(face_set)
+    }
+
+    /// The data of a face set split into units of its index size, which are only indices if the
+    /// face set isn't edge-compressed.
+    fn face_set_data(&self, face_set: &'a FaceSet<O>) -> Option<FaceSetIndices<'a, O>> {
+        let index_size = self.face_set_index_size(face_set);
         let index_count = face_set.index_count.get() as usize;
         let index_offset = face_set.index_offset.get() as usize;
-        let index_data = &self.data[index_offset..index_offset + (index_size / 8 * index_count)];
+        let index_data = self
+            .data
+            .get(index_offset..index_offset + (index_size / 8 * index_count))?;
 
-        Some(match face_set.index_size.get() {
+        Some(match index_size {
             8 => FaceSetIndices::U8(index_data),
             16 => FaceSetIndices::U16(U16::slice_from(index_data)?),
             32 => FaceSetIndices::U32(U32::slice_from(index_data)?),
@@ -81,6 +111,56 @@ impl<'a, O: ByteOrder + 'static> FlverInner<'a, O> {
         })
     }
 
+    /// The indices of a face set as a triangle list, with triangle strips unrolled. Edge-compressed
+    /// face sets aren't supported.
+    pub fn face_set_triangles(&self, face_set: &'a FaceSet<O>) -> Option<Vec<u32>> {
+        let indices = self.face_set_indices(face_set)?;
+        Some(if face_set.is_triangle_strip() {
+            let restart = strip_restart_index(self.face_set_index_size(face_set));
+            unroll_triangle_strip(indices.iter(), Some(restart))
+        } else {
+            indices.iter().collect()
+        })
+    }
+
+    /// The size of the indices of a face set in bits.
+    fn face_set_index_size(&self, face_set: &FaceSet<O>) -> usize {
+        match face_set.index_size.get() {
+            0 => self.vertex_index_size as usize,
+            size => size as usize,
+        }
+    }
+
+    /// The indices into [`Self::bones`] of the bones referenced by the bone indices of the
+    /// vertices of a mesh.
+    pub fn mesh_bone_indices(&self, mesh: &Mesh<O>) -> Option<&'a [U32<O>]> {
+        let offset = mesh.bone_offset.get() as usize;
+        let count = mesh.bone_count.get() as usize;
+        let (indices, _) = U32::slice_from_prefix(self.bytes.get(offset..)?, count)?;
+
+        Some(indices)
+    }
+
+    pub fn mesh_material(&self, mesh: &Mesh<O>) -> Option<&'a Material<O>> {
+        self.materials.get(mesh.material_index())
+    }
+
+    pub fn mesh_bounding_box(&self, mesh: &Mesh<O>) -> Option<MeshBoundingBox> {
+        let offset = mesh.bounding_box_offset.get() as usize;
+        if offset == 0 {
+            return None;
+        }
+
+        let count = if self.version() >= 0x2001A { 3 } else { 2 };
+        let (vectors, _) = <[F32<O>; 3]>::slice_from_prefix(self.bytes.get(offset..)?, count)?;
+
+        Some(MeshBoundingBox {
+            min: vec3(&vectors[0]),
+            max: vec3(&vectors[1]),
+            unk: vectors.get(2).map(vec3),
+        })
+    }
+
     pub fn mesh_buffers(&self, mesh: &'a Mesh<O>) -> impl Iterator<Item = &'a VertexBuffer<O>> {
         VertexBuffer::from_indices_at::<U32<O>>(
             self.vertex_buffers,
@@ -99,6 +179,145 @@ impl<'a, O: ByteOrder + 'static> FlverInner<'a, O> {
         )
     }
 
+    /// The face set of a mesh for a level of detail, where 0 is the most detailed. Motion blur
+    /// face sets are skipped.
+    pub fn mesh_lod_face_set(&self, mesh: &'a Mesh<O>, lod_level: u8) -> Option<&'a FaceSet<O>> {
+        self.mesh_face_sets(mesh)
+            .find(|face_set| face_set.lod_level() == lod_level && !face_set.is_motion_blur())
+    }
+
+    /// The raw vertex data of a vertex buffer.
+    pub fn vertex_buffer_data(&self, buffer: &VertexBuffer<O>) -> Option<&'a [u8]> {
+        let buffer_offset = buffer.buffer_offset.get() as usize;
+        let buffer_length = buffer.buffer_length.get() as usize;
+
+        self.data.get(buffer_offset..buffer_offset + buffer_length)
+    }
+
+    pub fn bone_name(&self, bone: &Bone<O>) -> Option<String> {
+        self.string(bone.name_offset.get())
+    }
+
+    /// Finds the first bone with the given name.
+    pub fn find_bone(&self, name: &str) -> Option<(usize, &'a Bone<O>)> {
+        self.bones
+            .iter()
+            .enumerate()
+            .find(|(_, bone)| self.bone_name(bone).is_some_and(|n| n == name))
+    }
+
+    pub fn bone_parent(&self, bone: &Bone<O>) -> Option<&'a Bone<O>> {
+        self.bones.get(bone.parent_index()?)
+    }
+
+    /// The bones without a parent, which are the roots of the skeleton.
+    pub fn root_bones(&self) -> impl Iterator<Item = (usize, &'a Bone<O>)> {
+        self.bones
+            .iter()
+            .enumerate()
+            .filter(|(_, bone)| bone.parent_index().is_none())
+    }
+
+    /// The direct children of a bone, found by following its child link and the sibling links of
+    /// that child.
+    pub fn bone_children(&self, index: usize) -> impl Iterator<Item = (usize, &'a Bone<O>)> {
+        let bones = self.bones;
+        let first_child = bones.get(index).and_then(Bone::child_index);
+
+        // Bound the walk by the bone count in case the sibling links contain a cycle
+        std::iter::successors(first_child, move |child| {
+            bones.get(*child)?.next_sibling_index()
+        })
+        .map_while(move |child| Some((child, bones.get(child)?)))
+        .take(bones.len())
+    }
+
+    /// The model space transform of a bone in its bind pose.
+    pub fn bone_world_transform(&self, index: usize) -> Option<Mat4> {
+        let mut transform = self.bones.get(index)?.local_transform();
+        let mut current = index;
+
+        // Bound the walk by the bone count in case the hierarchy contains a cycle
+        for _ in 0..self.bones.len() {
+            let Some(parent) = self.bones[current].parent_index() else {
+                break;
+            };
+            let parent_bone = self.bones.get(parent)?;
+
+            transform = transform::mul(&parent_bone.local_transform(), &transform);
+            current = parent;
+        }
+
+        Some(transform)
+    }
+
+    /// The model space transforms of all bones in their bind pose.
+    pub fn bone_world_transforms(&self) -> Vec<Mat4> {
+        let local: Vec<Mat4> = self.bones.iter().map(Bone::local_transform).collect();
+        transform::world_transforms(&local, |index| self.bones[index].parent_index())
+    }
+
+    /// The model space position of a dummy in the bind pose of its parent bone. Dummies without a
+    /// parent bone are positioned in model space already.
+    pub fn dummy_world_position(&self, dummy: &Dummy<O>) -> Option<[f32; 3]> {
+        match dummy.parent_bone_index() {
+            Some(parent) => Some(transform_point(
+                &self.bone_world_transform(parent)?,
+                dummy.position(),
+            )),
+            None => Some(dummy.position()),
+        }
+    }
+
+    pub fn material_name(&self, material: &Material<O>) -> Option<String> {
+        self.string(material.name_offset.get())
+    }
+
+    /// The path of the MTD or MATBIN describing the shader of a material.
+    pub fn material_mtd(&self, material: &Material<O>) -> Option<String> {
+        self.string(material.mtd_name_offset.get())
+    }
+
+    /// The raw GX list of a material, including its terminator item. GX lists hold shader
+    /// parameters and may be shared between materials.
+    pub fn material_gx_list(&self, material: &Material<O>) -> Option<&'a [u8]> {
+        let offset = material.gx_offset.get() as usize;
+        if offset == 0 {
+            return None;
+        }
+
+        let bytes = self.bytes.get(offset..)?;
+        let (_, length) = gx_items::<O>(bytes)?;
+        bytes.get(..length)
+    }
+
+    /// The parsed items of the GX list of a material, excluding the terminator item.
+    pub fn material_gx_items(&self, material: &Material<O>) -> Option<Vec<GxItem<'a>>> {
+        let (items, _) = gx_items::<O>(self.material_gx_list(material)?)?;
+        Some(items)
+    }
+
+    pub fn material_textures(&self, material: &Material<O>) -> Option<&'a [Texture<O>]> {
+        let start = material.texture_index();
+        self.textures.get(start..start + material.texture_count())
+    }
+
+    pub fn texture_path(&self, texture: &Texture<O>) -> Option<String> {
+        self.string(texture.path_offset.get())
+    }
+
+    /// The sampler this texture is bound to, e.g. `g_DiffuseTexture`.
+    pub fn texture_type(&self, texture: &Texture<O>) -> Option<String> {
+        self.string(texture.type_offset.get())
+    }
+
+    /// Reads a null-terminated string at the given offset from the start of the file, encoded as
+    /// UTF-16 in unicode FLVERs and as Shift-JIS otherwise.
+    fn string(&self, offset: u32) -> Option<String> {
+        read_string::<O>(self.bytes, offset, self.is_unicode())
+    }
+
+    /// The attributes of a vertex buffer layout, or none if they're out of bounds.
     pub fn vertex_attributes(
         &self,
         vertex_buffer_layout: &'a VertexBufferLayout<O>,
@@ -106,49 +325,34 @@ impl<'a, O: ByteOrder + 'static> FlverInner<'a, O> {
         let attribute_count = vertex_buffer_layout.member_count.get() as usize;
         let attribute_offset = vertex_buffer_layout.member_offset.get() as usize;
 
-        let (attrs, _) = VertexBufferAttribute::slice_from_prefix(
-            &self.bytes[attribute_offset..],
-            attribute_count,
-        )
-        .expect("unaligned_vertex_attributes");
-
-        attrs
+        self.bytes
+            .get(attribute_offset..)
+            .and_then(|bytes| VertexBufferAttribute::slice_from_prefix(bytes, attribute_count))
+            .map(|(attributes, _)| attributes)
+            .unwrap_or_default()
     }
 
     pub fn vertex_attribute_accessor(
         &self,
         buffer: &VertexBuffer<O>,
         attribute: &VertexBufferAttribute<O>,
-    ) -> Option<VertexAttributeAccessor<'a>> {
-        let buffer_offset = buffer.buffer_offset.get() as usize;
-        let buffer_length = buffer.buffer_length.get() as usize;
-
-        let data = &self.data[buffer_offset..buffer_offset + buffer_length];
+    ) -> Option<VertexAttributeAccessor<'a, O>> {
+        let data = self.vertex_buffer_data(buffer)?;
         let vertex_size = buffer.vertex_size.get() as usize;
-        let vertex_offset = attribute.struct_offset.get() as usize;
-
-        use vertex_buffer::VertexFormat::*;
-
-        #[allow(clippy::match_same_arms)]
-        attribute.format().map(|format| match format {
-            Float32x3 => Accessor::Float3(Iter::new(data, vertex_size, vertex_offset)),
-            Float32x2 => Accessor::Float2(Iter::new(data, vertex_size, vertex_offset)),
-            Float32x4 => Accessor::Float4(Iter::new(data, vertex_size, vertex_offset)),
-            Unorm8x4 => Accessor::UNorm8x4(Iter::new(data, vertex_size, vertex_offset)),
-            Snorm8x4 => Accessor::SNorm8x4(Iter::new(data, vertex_size, vertex_offset)),
-            Snorm16x4 => Accessor::SNorm16x4(Iter::new(data, vertex_size, vertex_offset)),
-            Uint8x4 => Accessor::UNorm8x4(Iter::new(data, vertex_size, vertex_offset)),
-            Sint16x4 => Accessor::SNorm16x4(Iter::new(data, vertex_size, vertex_offset)),
-            Sscale16x2 => Accessor::SNorm16x2(Iter::new(data, vertex_size, vertex_offset)),
-            Sscale16x4 => Accessor::SNorm16x4(Iter::new(data, vertex_size, vertex_offset)),
-        })
+        let uv_factor = if self.version() >= 0x2000F {
+            2048.0
+        } else {
+            1024.0
+        };
+
+        attribute_accessor(data, vertex_size, attribute, uv_factor)
     }
 
     fn parse_no_verify(bytes: &'a [u8]) -> Option<Self> {
         let (header_ref, dummy_bytes) = Ref::<_, FlverHeader<O>>::new_from_prefix(bytes)?;
         let header: &'a FlverHeader<O> = header_ref.into_ref();
 
-        let (dummys, next) = Dummy::<O>::slice_from_prefix(dummy_bytes, header.dummy_count())?;
+        let (dummies, next) = Dummy::<O>::slice_from_prefix(dummy_bytes, header.dummy_count())?;
         let (materials, next) = Material::<O>::slice_from_prefix(next, header.material_count())?;
         let (bones, next) = Bone::<O>::slice_from_prefix(next, header.bone_count())?;
         let (meshes, next) = Mesh::<O>::slice_from_prefix(next, header.mesh_count())?;
@@ -162,14 +366,14 @@ impl<'a, O: ByteOrder + 'static> FlverInner<'a, O> {
         let (textures, _) = Texture::<O>::slice_from_prefix(next, header.texture_count())?;
         let data_offset = header.data_offset.get() as usize;
         let data_end = data_offset + header.data_length.get() as usize;
-        let data = &bytes[data_offset..data_end];
+        let data = bytes.get(data_offset..data_end)?;
 
         Some(Self {
             header,
             bytes,
             data,
             bones,
-            dummys,
+            dummies,
             face_sets,
             materials,
             meshes,
@@ -179,17 +383,40 @@ impl<'a, O: ByteOrder + 'static> FlverInner<'a, O> {
         })
     }
 
+    /// Parses a FLVER stored in the byte order `O`. Use [`AnyFlver::parse`] for FLVERs whose
+    /// byte order isn't known ahead of time.
+    ///
+    /// FLVER0s and versions newer than the ones that can be written are rejected.
     pub fn parse(data: &'a [u8]) -> Result<Self, std::io::Error> {
-        let mut header = &data[..8];
+        let mut header = data
+            .get(..8)
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::UnexpectedEof))?;
         header.read_magic(b"FLVER\0")?;
 
-        let mut endianness = vec![0x0u8; 2];
+        let mut endianness = [0x0u8; 2];
         header.read_exact(&mut endianness)?;
 
-        let is_little_endian = endianness == [0x4c, 0x00];
-        if !is_little_endian {
+        if endianness != endianness_marker::<O>() {
+            return Err(std::io::Error::other(format!(
+                "FLVER is {}, expected {}",
+                marker_name(endianness),
+                marker_name(endianness_marker::<O>())
+            )));
+        }
+
+        let version = data
+            .get(8..12)
+            .map(O::read_u32)
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::UnexpectedEof))?;
+        if version < FLVER0_VERSION_END {
             return Err(std::io::Error::other(
-                "only little endian FLVERs are supported",
+                "FLVER0 models must be parsed with `Flver0Inner`",
+            ));
+        }
+        if ALLOWED_VERSIONS.iter().all(|allowed| version > *allowed) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("reading FLVER version 0x{version:X} is not supported"),
             ));
         }
 
@@ -197,6 +424,96 @@ impl<'a, O: ByteOrder + 'static> FlverInner<'a, O> {
     }
 }
 
+/// Creates an accessor for an attribute of the vertices in a vertex buffer, with UVs stored as
+/// integers divided by `uv_factor`.
+pub(crate) fn attribute_accessor<'a, O: ByteOrder>(
+    data: &'a [u8],
+    vertex_size: usize,
+    attribute: &VertexBufferAttribute<O>,
+    uv_factor: f32,
+) -> Option<VertexAttributeAccessor<'a, O>> {
+    let vertex_offset = attribute.struct_offset.get() as usize;
+
+    use vertex_buffer::VertexFormat::*;
+
+    #[allow(clippy::match_same_arms)]
+    attribute.format().map(|format| match format {
+        Float32x3 => Accessor::Float3(Iter::new(data, vertex_size, vertex_offset)),
+        Float32x2 => Accessor::Float2(Iter::new(data, vertex_size, vertex_offset)),
+        Float32x4 => Accessor::Float4(Iter::new(data, vertex_size, vertex_offset)),
+        Unorm8x4 => Accessor::UNorm8x4(Iter::new(data, vertex_size, vertex_offset)),
+        Snorm8x4 => Accessor::SNorm8x4(Iter::new(data, vertex_size, vertex_offset)),
+        Snorm16x4 => Accessor::SNorm16x4(Iter::new(data, vertex_size, vertex_offset)),
+        Biased8x4 => Accessor::BiasedNorm8x4(Iter::new(data, vertex_size, vertex_offset)),
+        Biased16x4 => Accessor::BiasedNorm16x4(Iter::new(data, vertex_size, vertex_offset)),
+        Uint8x4 => Accessor::UInt8x4(Iter::new(data, vertex_size, vertex_offset)),
+        Uint16x4 => Accessor::UInt16x4(Iter::new(data, vertex_size, vertex_offset)),
+        Sscale16x2 => Accessor::UV(ScaledIter::new(data, vertex_size, vertex_offset, uv_factor)),
+        Sscale16x4 => {
+            Accessor::UVPair(ScaledIter::new(data, vertex_size, vertex_offset, uv_factor))
+        }
+    })
+}
+
+/// A FLVER in either byte order. Console FLVERs are big-endian, while PC ones are little-endian.
+pub enum AnyFlver<'a> {
+    LittleEndian(FlverInner<'a, LE>),
+    BigEndian(FlverInner<'a, BE>),
+}
+
+impl<'a> AnyFlver<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self, std::io::Error> {
+        match data.get(6..8) {
+            Some(b"B\0") => FlverInner::parse(data).map(Self::BigEndian),
+            _ => FlverInner::parse(data).map(Self::LittleEndian),
+        }
+    }
+}
+
+/// Reads a null-terminated string at an offset, encoded as UTF-16 if `unicode` is set and as
+/// Shift-JIS otherwise.
+pub(crate) fn read_string<O: ByteOrder>(
+    bytes: &[u8],
+    offset: u32,
+    unicode: bool,
+) -> Option<String> {
+    let bytes = bytes.get(offset as usize..)?;
+    if !unicode {
+        let end = bytes.iter().position(|b| *b == 0)?;
+        let (string, _) = SHIFT_JIS.decode_without_bom_handling(&bytes[..end]);
+        return Some(string.into_owned());
+    }
+
+    read_wide_cstring::<O>(bytes)
+        .ok()
+        .map(|string| string.to_string())
+}
+
+pub(crate) fn is_big_endian<O: ByteOrder>() -> bool {
+    O::read_u16(&[0, 1]) == 1
+}
+
+/// The byte order marker following the FLVER magic.
+pub(crate) fn endianness_marker<O: ByteOrder>() -> [u8; 2] {
+    if is_big_endian::<O>() {
+        *b"B\0"
+    } else {
+        *b"L\0"
+    }
+}
+
+fn marker_name(marker: [u8; 2]) -> &'static str {
+    match &marker {
+        b"L\0" => "little endian",
+        b"B\0" => "big endian",
+        _ => "of unknown endianness",
+    }
+}
+
+pub(crate) fn vec3<O: ByteOrder>(vector: &[F32<O>; 3]) -> [f32; 3] {
+    vector.map(|component| component.get())
+}
+
 impl<'a, O: ByteOrder + 'static> Debug for FlverInner<'a, O> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Flver")
@@ -216,3 +533,225 @@ impl<'a, O: ByteOrder + 'static> Debug for FlverInner<'a, O> {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::flver::{
+        face_set::{FACESET_FLAG_LOD1, FACESET_FLAG_MOTIONBLUR},
+        owned,
+        owned::{FaceSet, VertexBufferAttribute, VertexBufferLayout},
+        vertex_buffer::{accessor::VertexAttributeAccessor, VertexAttributeSemantic},
+        writer::test::test_flver,
+        AnyFlver, Flver,
+    };
+
+    #[test]
+    pub fn resolves_bone_hierarchy() {
+        let mut source = test_flver();
+        source.unicode = false;
+        source.bones[1].name = "背骨".to_string();
+        let bytes = source.to_bytes().expect("supported version");
+        let flver = Flver::parse(&bytes).expect("valid FLVER");
+
+        let (spine, _) = flver.find_bone("背骨").expect("Shift-JIS bone name");
+        assert_eq!(spine, 1);
+        assert_eq!(
+            flver
+                .root_bones()
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>(),
+            [0]
+        );
+        assert_eq!(
+            flver
+                .bone_children(0)
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>(),
+            [1]
+        );
+
+        // The translation of the spine is along the Y axis its parent is rotated around
+        let transforms = flver.bone_world_transforms();
+        assert_eq!(flver.bone_world_transform(1), Some(transforms[1]));
+        let translation = &transforms[1][12..15];
+        assert!(translation
+            .iter()
+            .zip([0.0, 2.0, 0.0])
+            .all(|(a, b)| (a - b).abs() < 1e-5));
+
+        let position = flver
+            .dummy_world_position(&flver.dummies[0])
+            .expect("valid parent bone");
+        assert!(position
+            .iter()
+            .zip([0.0, 3.0, 0.0])
+            .all(|(a, b)| (a - b).abs() < 1e-5));
+    }
+
+    #[test]
+    pub fn resolves_materials() {
+        let bytes = test_flver().to_bytes().expect("supported version");
+        let flver = Flver::parse(&bytes).expect("valid FLVER");
+        let material = &flver.materials[0];
+
+        assert_eq!(flver.material_name(material).as_deref(), Some("body"));
+        assert_eq!(
+            flver.material_mtd(material).as_deref(),
+            Some("N:\\mtd\\body.matxml")
+        );
+
+        let textures = flver.material_textures(material).expect("valid range");
+        assert_eq!(
+            flver.texture_type(&textures[1]).as_deref(),
+            Some("g_BumpmapTexture")
+        );
+        assert_eq!(textures[1].scale(), [1.0, 1.0]);
+
+        let items = flver.material_gx_items(material).expect("valid GX list");
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id_str(), Some("GXMD"));
+        assert_eq!(items[0].unk04, 100);
+        assert_eq!(items[0].i32_values().collect::<Vec<_>>(), [7]);
+    }
+
+    #[test]
+    pub fn decodes_uv_pairs() {
+        let mut source = test_flver();
+        source.buffer_layouts.push(VertexBufferLayout {
+            attributes: vec![VertexBufferAttribute {
+                unk0: 0,
+                struct_offset: 0,
+                format_id: 0x16,
+                semantic: VertexAttributeSemantic::UV,
+                index: 0,
+            }],
+        });
+
+        let buffer = &mut source.meshes[1].vertex_buffers[0];
+        buffer.layout_index = 1;
+        buffer.vertex_size = 8;
+        buffer.data = [1024i16, 2048, -512, 4096]
+            .iter()
+            .cycle()
+            .take(12)
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+
+        let bytes = source.to_bytes().expect("supported version");
+        let flver = Flver::parse(&bytes).expect("valid FLVER");
+        let buffer = flver
+            .mesh_buffers(&flver.meshes[1])
+            .next()
+            .expect("vertex buffer");
+        let attribute = &flver.vertex_attributes(&flver.vertex_buffer_layouts[1])[0];
+        assert_eq!(attribute.uv_count(), 2);
+
+        let Some(VertexAttributeAccessor::UVPair(uvs)) =
+            flver.vertex_attribute_accessor(buffer, attribute)
+        else {
+            panic!("UV pair attribute should be decoded");
+        };
+        assert_eq!(uvs.collect::<Vec<_>>(), [[0.5, 1.0, -0.25, 2.0]; 3]);
+
+        assert_eq!(
+            VertexAttributeSemantic::from(0x9),
+            VertexAttributeSemantic::Unknown(0x9)
+        );
+    }
+
+    #[test]
+    pub fn parses_big_endian_flvers() {
+        let mut source = test_flver();
+        source.big_endian = true;
+        source.meshes[0].vertex_buffers[0].data = [1.0f32, -2.0, 0.5]
+            .iter()
+            .cycle()
+            .take(9)
+            .flat_map(|value| value.to_be_bytes())
+            .collect();
+        source.gx_lists[0] = [
+            *b"GXMD",
+            100u32.to_be_bytes(),
+            16u32.to_be_bytes(),
+            7u32.to_be_bytes(),
+            (i32::MAX as u32).to_be_bytes(),
+            100u32.to_be_bytes(),
+            12u32.to_be_bytes(),
+        ]
+        .concat();
+
+        let bytes = source.to_bytes().expect("supported version");
+        assert_eq!(&bytes[..8], b"FLVER\0B\0");
+        assert!(Flver::parse(&bytes).is_err());
+
+        let AnyFlver::BigEndian(flver) = AnyFlver::parse(&bytes).expect("valid FLVER") else {
+            panic!("FLVER should be big endian");
+        };
+        assert_eq!(flver.version(), 0x2001A);
+        assert_eq!(flver.bone_name(&flver.bones[1]).as_deref(), Some("spine"));
+
+        let buffer = flver
+            .mesh_buffers(&flver.meshes[0])
+            .next()
+            .expect("vertex buffer");
+        let attribute = &flver.vertex_attributes(&flver.vertex_buffer_layouts[0])[0];
+        let Some(VertexAttributeAccessor::Float3(positions)) =
+            flver.vertex_attribute_accessor(buffer, attribute)
+        else {
+            panic!("position attribute should be decoded");
+        };
+        assert_eq!(positions.collect::<Vec<_>>(), [[1.0, -2.0, 0.5]; 3]);
+
+        let items = flver
+            .material_gx_items(&flver.materials[0])
+            .expect("valid GX list");
+        assert_eq!(items[0].id_str(), Some("GXMD"));
+        assert_eq!(items[0].i32_values().collect::<Vec<_>>(), [7]);
+
+        let parsed = owned::Flver::parse(&bytes).expect("valid FLVER");
+        assert!(parsed.big_endian);
+        assert_eq!(parsed.to_bytes().expect("supported version"), bytes);
+    }
+
+    #[test]
+    pub fn selects_lod_triangles() {
+        let mut source = test_flver();
+        let face_sets = &mut source.meshes[1].face_sets;
+        face_sets.push(FaceSet {
+            flags: FACESET_FLAG_LOD1 | FACESET_FLAG_MOTIONBLUR,
+            triangle_strip: false,
+            cull_back_faces: true,
+            unk06: 0,
+            index_size: 32,
+            indices: vec![0, 1, 2],
+        });
+        face_sets.push(FaceSet {
+            flags: FACESET_FLAG_LOD1,
+            triangle_strip: true,
+            cull_back_faces: true,
+            unk06: 0,
+            index_size: 16,
+            indices: vec![0, 1, 2, 0xFFFF, 2, 1, 0, 0],
+        });
+
+        let bytes = source.to_bytes().expect("supported version");
+        let flver = Flver::parse(&bytes).expect("valid FLVER");
+        let mesh = &flver.meshes[1];
+
+        let lod0 = flver.mesh_lod_face_set(mesh, 0).expect("LOD0 face set");
+        assert_eq!(flver.face_set_triangles(lod0), Some(vec![2, 1, 0]));
+
+        let lod1 = flver.mesh_lod_face_set(mesh, 1).expect("LOD1 face set");
+        assert_eq!(lod1.lod_level(), 1);
+        assert!(!lod1.is_motion_blur());
+        assert_eq!(flver.face_set_triangles(lod1), Some(vec![0, 1, 2, 2, 1, 0]));
+        assert!(flver.mesh_lod_face_set(mesh, 2).is_none());
+
+        let parsed = owned::Flver::parse(&bytes).expect("valid FLVER");
+        let lod1 = parsed.meshes[1].lod_face_set(1).expect("LOD1 face set");
+        assert_eq!(
+            parsed.face_set_triangles(lod1),
+            Some(vec![0, 1, 2, 2, 1, 0])
+        );
+    }
+}
@@ -1,14 +1,21 @@
 use byteorder::ByteOrder;
 use zerocopy::{FromBytes, FromZeroes, F32, U16, U32};
 
-use crate::{flver::header::FlverHeaderPart, io_ext::zerocopy::Padding};
+use crate::{
+    flver::{
+        header::FlverHeaderPart,
+        transform::{euler_to_quaternion, from_trs, Mat4},
+        vec3,
+    },
+    io_ext::zerocopy::Padding,
+};
 
 #[derive(Debug, FromZeroes, FromBytes)]
 #[repr(C)]
 #[allow(unused)]
 pub struct Bone<O: ByteOrder> {
     translation: [F32<O>; 3],
-    name_offset: U32<O>,
+    pub(crate) name_offset: U32<O>,
     rotation: [F32<O>; 3],
     parent_index: U16<O>,
     child_index: U16<O>,
@@ -21,4 +28,62 @@ pub struct Bone<O: ByteOrder> {
     _padding0: Padding<0x34>,
 }
 
+impl<O: ByteOrder> Bone<O> {
+    /// Translation of this bone relative to its parent.
+    pub fn translation(&self) -> [f32; 3] {
+        vec3(&self.translation)
+    }
+
+    /// Euler rotation of this bone relative to its parent, in radians.
+    pub fn rotation(&self) -> [f32; 3] {
+        vec3(&self.rotation)
+    }
+
+    pub fn scale(&self) -> [f32; 3] {
+        vec3(&self.scale)
+    }
+
+    /// Transform of this bone relative to its parent.
+    pub fn local_transform(&self) -> Mat4 {
+        from_trs(
+            self.translation(),
+            euler_to_quaternion(self.rotation()),
+            self.scale(),
+        )
+    }
+
+    pub fn bounding_box_min(&self) -> [f32; 3] {
+        vec3(&self.bounding_box_min)
+    }
+
+    pub fn bounding_box_max(&self) -> [f32; 3] {
+        vec3(&self.bounding_box_max)
+    }
+
+    pub fn parent_index(&self) -> Option<usize> {
+        bone_index(self.parent_index.get())
+    }
+
+    pub fn child_index(&self) -> Option<usize> {
+        bone_index(self.child_index.get())
+    }
+
+    pub fn next_sibling_index(&self) -> Option<usize> {
+        bone_index(self.next_sibling_index.get())
+    }
+
+    pub fn previous_sibling_index(&self) -> Option<usize> {
+        bone_index(self.prev_sibling_index.get())
+    }
+
+    pub fn unk3c(&self) -> u32 {
+        self.unk3c.get()
+    }
+}
+
+/// Bone references are stored as signed 16-bit indices, where -1 means no bone.
+pub(crate) fn bone_index(index: u16) -> Option<usize> {
+    (index as i16 >= 0).then_some(index as usize)
+}
+
 impl<O: ByteOrder> FlverHeaderPart for Bone<O> {}
@@ -1,7 +1,10 @@
 use byteorder::ByteOrder;
-use zerocopy::{FromBytes, FromZeroes, F32, U16};
+use zerocopy::{FromBytes, FromZeroes, F32, U16, U32};
 
-use crate::{flver::header::FlverHeaderPart, io_ext::zerocopy::Padding};
+use crate::{
+    flver::{bone::bone_index, header::FlverHeaderPart, vec3},
+    io_ext::zerocopy::Padding,
+};
 
 #[derive(FromZeroes, FromBytes)]
 #[repr(C, packed)]
@@ -13,10 +16,63 @@ pub struct Dummy<O: ByteOrder> {
     ref_id: U16<O>,
     parent_bone_index: U16<O>,
     up_vector: [F32<O>; 3],
-    attached_bone_index: u16,
+    attached_bone_index: U16<O>,
     flag_1: u8,
     use_up_vector: u8,
-    _padding1: Padding<16>,
+    unk30: U32<O>,
+    unk34: U32<O>,
+    _padding1: Padding<8>,
+}
+
+impl<O: ByteOrder> Dummy<O> {
+    /// Position of this dummy relative to its parent bone.
+    pub fn position(&self) -> [f32; 3] {
+        vec3(&self.position)
+    }
+
+    /// Debug color of this dummy as BGRA.
+    pub fn color(&self) -> [u8; 4] {
+        self.color
+    }
+
+    pub fn forward(&self) -> [f32; 3] {
+        vec3(&self.forward)
+    }
+
+    pub fn up_vector(&self) -> [f32; 3] {
+        vec3(&self.up_vector)
+    }
+
+    /// The ID this dummy is referred to by in other formats, e.g. for attaching effects.
+    pub fn reference_id(&self) -> u16 {
+        self.ref_id.get()
+    }
+
+    /// The bone [`Self::position`] is relative to.
+    pub fn parent_bone_index(&self) -> Option<usize> {
+        bone_index(self.parent_bone_index.get())
+    }
+
+    /// The bone this dummy follows when animated.
+    pub fn attached_bone_index(&self) -> Option<usize> {
+        bone_index(self.attached_bone_index.get())
+    }
+
+    pub fn flag_1(&self) -> bool {
+        self.flag_1 != 0
+    }
+
+    pub fn use_up_vector(&self) -> bool {
+        self.use_up_vector != 0
+    }
+
+    pub fn unk30(&self) -> u32 {
+        self.unk30.get()
+    }
+
+    pub fn unk34(&self) -> u32 {
+        self.unk34.get()
+    }
 }
 
 impl<O: ByteOrder> FlverHeaderPart for Dummy<O> {}
@@ -0,0 +1,3 @@
+//! Conversion of FLVER models to interchange formats.
+
+pub mod gltf;
@@ -0,0 +1,699 @@
+//! Export of FLVER models to glTF 2.0.
+//!
+//! FLVERs use a left-handed coordinate system while glTF is right-handed, so the exported model
+//! is mirrored along the X axis. This also turns the clockwise front faces of FLVERs into the
+//! counter-clockwise front faces glTF expects, so indices are written in their original order.
+//!
+//! Textures are referenced by the file name of their path with a `.png` extension, and the
+//! original texture paths and types are kept in the `extras` of each material.
+
+use std::io::{self, Write};
+
+use byteorder::ByteOrder;
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+
+use crate::flver::{
+    mesh::Mesh,
+    transform::{
+        self, affine_inverse, euler_to_quaternion, from_trs, mirror_quaternion, normalize, Mat4,
+    },
+    vertex_buffer::{accessor::VertexAttributeAccessor, VertexAttributeSemantic},
+    FlverInner,
+};
+
+#[derive(Debug, Error)]
+pub enum GltfExportError {
+    #[error("Could not serialize glTF document: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Could not write glTF: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Mesh {mesh} is malformed: {reason}")]
+    MalformedMesh { mesh: usize, reason: &'static str },
+}
+
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const UNSIGNED_SHORT: u32 = 5123;
+const UNSIGNED_INT: u32 = 5125;
+const FLOAT: u32 = 5126;
+const TRIANGLES: u32 = 4;
+
+pub(crate) const GLB_MAGIC: &[u8; 4] = b"glTF";
+pub(crate) const GLB_JSON_CHUNK: &[u8; 4] = b"JSON";
+pub(crate) const GLB_BIN_CHUNK: &[u8; 4] = b"BIN\0";
+
+/// A glTF document and the contents of its single binary buffer.
+pub struct Gltf {
+    document: Value,
+    buffer: Vec<u8>,
+}
+
+impl Gltf {
+    /// Converts a FLVER to glTF. Each FLVER mesh becomes a glTF mesh skinned to a node tree built
+    /// from the bones of the FLVER. Only the highest detail face set of each mesh is exported.
+    pub fn from_flver<O: ByteOrder + 'static>(
+        flver: &FlverInner<'_, O>,
+    ) -> Result<Self, GltfExportError> {
+        let mut builder = BufferBuilder::default();
+        let bone_count = flver.bones.len();
+
+        let world_transforms = world_transforms(flver);
+        let mut nodes: Vec<Value> = flver
+            .bones
+            .iter()
+            .enumerate()
+            .map(|(index, bone)| {
+                let (translation, rotation) = mirror(bone.translation(), bone.rotation());
+                let mut node = json!({
+                    "name": flver.bone_name(bone).unwrap_or_default(),
+                    "translation": translation,
+                    "rotation": rotation,
+                    "scale": bone.scale(),
+                });
+
+                let children: Vec<usize> = flver
+                    .bones
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, child)| child.parent_index() == Some(index))
+                    .map(|(child_index, _)| child_index)
+                    .collect();
+                if !children.is_empty() {
+                    node["children"] = json!(children);
+                }
+
+                node
+            })
+            .collect();
+
+        let mut scene_nodes: Vec<usize> = flver
+            .bones
+            .iter()
+            .enumerate()
+            .filter(|(_, bone)| {
+                bone.parent_index()
+                    .is_none_or(|parent| parent >= bone_count)
+            })
+            .map(|(index, _)| index)
+            .collect();
+
+        let mut meshes = Vec::with_capacity(flver.meshes.len());
+        for (index, mesh) in flver.meshes.iter().enumerate() {
+            meshes.push(export_mesh(flver, index, mesh, &mut builder)?);
+
+            let mut node = json!({ "name": format!("mesh{index}"), "mesh": index });
+            if bone_count > 0 {
+                node["skin"] = json!(0);
+            }
+
+            scene_nodes.push(nodes.len());
+            nodes.push(node);
+        }
+
+        let mut skins = Vec::new();
+        if bone_count > 0 {
+            let inverse_bind_matrices: Vec<Mat4> =
+                world_transforms.iter().map(affine_inverse).collect();
+
+            skins.push(json!({
+                "joints": (0..bone_count).collect::<Vec<_>>(),
+                "inverseBindMatrices": builder.floats(&inverse_bind_matrices, None, false),
+            }));
+        }
+
+        let (materials, textures, images) = export_materials(flver);
+
+        let mut document = Map::new();
+        document.insert(
+            "asset".to_string(),
+            json!({ "version": "2.0", "generator": "fstools" }),
+        );
+        document.insert("scene".to_string(), json!(0));
+        document.insert("scenes".to_string(), json!([{ "nodes": scene_nodes }]));
+        for (key, values) in [
+            ("nodes", nodes),
+            ("meshes", meshes),
+            ("skins", skins),
+            ("materials", materials),
+            ("textures", textures),
+            ("images", images),
+            ("accessors", builder.accessors),
+            ("bufferViews", builder.views),
+        ] {
+            if !values.is_empty() {
+                document.insert(key.to_string(), Value::Array(values));
+            }
+        }
+        document.insert(
+            "buffers".to_string(),
+            json!([{ "byteLength": builder.buffer.len() }]),
+        );
+
+        Ok(Self {
+            document: Value::Object(document),
+            buffer: builder.buffer,
+        })
+    }
+
+    /// The glTF JSON document. Its buffer has no URI, see [`Self::write_gltf`].
+    pub fn document(&self) -> &Value {
+        &self.document
+    }
+
+    /// The contents of the binary buffer referenced by the document.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Writes the JSON document of a `.gltf` file, referencing the binary buffer by the given
+    /// URI. The buffer itself must be written to that location separately.
+    pub fn write_gltf<W: Write>(&self, writer: W, buffer_uri: &str) -> Result<(), GltfExportError> {
+        let mut document = self.document.clone();
+        document["buffers"][0]["uri"] = json!(buffer_uri);
+
+        Ok(serde_json::to_writer_pretty(writer, &document)?)
+    }
+
+    /// Serializes the document and buffer as a single binary `.glb` file.
+    pub fn to_glb(&self) -> Result<Vec<u8>, GltfExportError> {
+        let mut json = serde_json::to_vec(&self.document)?;
+        json.resize(json.len().next_multiple_of(4), b' ');
+        let mut buffer = self.buffer.clone();
+        buffer.resize(buffer.len().next_multiple_of(4), 0);
+
+        let length = 12 + 8 + json.len() + 8 + buffer.len();
+        let mut glb = Vec::with_capacity(length);
+        glb.extend_from_slice(GLB_MAGIC);
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&(length as u32).to_le_bytes());
+        for (chunk_type, chunk) in [(GLB_JSON_CHUNK, json), (GLB_BIN_CHUNK, buffer)] {
+            glb.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+            glb.extend_from_slice(chunk_type);
+            glb.extend_from_slice(&chunk);
+        }
+
+        Ok(glb)
+    }
+
+    pub fn write_glb<W: Write>(&self, mut writer: W) -> Result<(), GltfExportError> {
+        Ok(writer.write_all(&self.to_glb()?)?)
+    }
+}
+
+/// Accumulates the binary buffer of the document along with the buffer views and accessors
+/// describing it.
+#[derive(Default)]
+struct BufferBuilder {
+    buffer: Vec<u8>,
+    views: Vec<Value>,
+    accessors: Vec<Value>,
+}
+
+impl BufferBuilder {
+    fn view(&mut self, bytes: &[u8], target: Option<u32>) -> usize {
+        self.buffer.resize(self.buffer.len().next_multiple_of(4), 0);
+
+        let mut view = json!({
+            "buffer": 0,
+            "byteOffset": self.buffer.len(),
+            "byteLength": bytes.len(),
+        });
+        if let Some(target) = target {
+            view["target"] = json!(target);
+        }
+
+        self.buffer.extend_from_slice(bytes);
+        self.views.push(view);
+        self.views.len() - 1
+    }
+
+    fn accessor(&mut self, accessor: Value) -> usize {
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn floats<const N: usize>(
+        &mut self,
+        values: &[[f32; N]],
+        target: Option<u32>,
+        bounds: bool,
+    ) -> usize {
+        let bytes: Vec<u8> = values
+            .iter()
+            .flatten()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let view = self.view(&bytes, target);
+
+        let mut accessor = json!({
+            "bufferView": view,
+            "componentType": FLOAT,
+            "count": values.len(),
+            "type": accessor_type(N),
+        });
+
+        if bounds {
+            let mut min = [f32::INFINITY; N];
+            let mut max = [f32::NEG_INFINITY; N];
+            for value in values {
+                for component in 0..N {
+                    min[component] = min[component].min(value[component]);
+                    max[component] = max[component].max(value[component]);
+                }
+            }
+
+            accessor["min"] = json!(min.to_vec());
+            accessor["max"] = json!(max.to_vec());
+        }
+
+        self.accessor(accessor)
+    }
+
+    fn joints(&mut self, values: &[[u16; 4]]) -> usize {
+        let bytes: Vec<u8> = values
+            .iter()
+            .flatten()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let view = self.view(&bytes, Some(ARRAY_BUFFER));
+
+        self.accessor(json!({
+            "bufferView": view,
+            "componentType": UNSIGNED_SHORT,
+            "count": values.len(),
+            "type": "VEC4",
+        }))
+    }
+
+    fn indices(&mut self, values: &[u32]) -> usize {
+        let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let view = self.view(&bytes, Some(ELEMENT_ARRAY_BUFFER));
+
+        self.accessor(json!({
+            "bufferView": view,
+            "componentType": UNSIGNED_INT,
+            "count": values.len(),
+            "type": "SCALAR",
+        }))
+    }
+}
+
+fn accessor_type(component_count: usize) -> &'static str {
+    match component_count {
+        1 => "SCALAR",
+        2 => "VEC2",
+        3 => "VEC3",
+        4 => "VEC4",
+        _ => "MAT4",
+    }
+}
+
+/// Vertex attributes of a mesh, gathered from all of its vertex buffers.
+#[derive(Default)]
+struct MeshAttributes {
+    positions: Option<Vec<[f32; 4]>>,
+    normals: Option<Vec<[f32; 4]>>,
+    tangents: Option<Vec<[f32; 4]>>,
+    colors: Option<Vec<[f32; 4]>>,
+    uvs: Vec<(u32, Vec<[f32; 4]>)>,
+    bone_indices: Option<Vec<[u32; 4]>>,
+    bone_weights: Option<Vec<[f32; 4]>>,
+}
+
+fn export_mesh<O: ByteOrder + 'static>(
+    flver: &FlverInner<'_, O>,
+    index: usize,
+    mesh: &Mesh<O>,
+    builder: &mut BufferBuilder,
+) -> Result<Value, GltfExportError> {
+    let malformed = |reason| GltfExportError::MalformedMesh {
+        mesh: index,
+        reason,
+    };
+
+    let mut attributes = MeshAttributes::default();
+    for buffer in flver.mesh_buffers(mesh) {
+        let layout = flver
+            .vertex_buffer_layouts
+            .get(buffer.layout_index.get() as usize)
+            .ok_or_else(|| malformed("vertex buffer layout index out of bounds"))?;
+
+        for attribute in flver.vertex_attributes(layout) {
+            let Some(accessor) = flver.vertex_attribute_accessor(buffer, attribute) else {
+                continue;
+            };
+
+            use VertexAttributeSemantic::*;
+            let slot = match attribute.semantic() {
+                Position => &mut attributes.positions,
+                Normal => &mut attributes.normals,
+                Tangent => &mut attributes.tangents,
+                VertexColor => &mut attributes.colors,
+                BoneWeights => &mut attributes.bone_weights,
+                BoneIndices => {
+                    attributes
+                        .bone_indices
+                        .get_or_insert_with(|| integer_values(accessor));
+                    continue;
+                }
+                UV => {
+                    let uv_index = attribute.index.get();
+                    let uvs = float_values(accessor);
+                    if attribute.uv_count() == 2 {
+                        let second = uvs.iter().map(|[_, _, u, v]| [*u, *v, 0.0, 0.0]).collect();
+                        attributes.uvs.push((uv_index, uvs));
+                        attributes.uvs.push((uv_index, second));
+                    } else {
+                        attributes.uvs.push((uv_index, uvs));
+                    }
+                    continue;
+                }
+                Bitangent | Unknown(_) => continue,
+            };
+
+            slot.get_or_insert_with(|| float_values(accessor));
+        }
+    }
+
+    let positions: Vec<[f32; 3]> = attributes
+        .positions
+        .ok_or_else(|| malformed("no vertex positions"))?
+        .iter()
+        .map(|[x, y, z, _]| [-x, *y, *z])
+        .collect();
+    if positions.is_empty() {
+        return Err(malformed("no vertices"));
+    }
+
+    let vertex_count = positions.len();
+    let mut primitive_attributes = Map::new();
+    primitive_attributes.insert(
+        "POSITION".to_string(),
+        json!(builder.floats(&positions, Some(ARRAY_BUFFER), true)),
+    );
+
+    if let Some(normals) = attributes.normals.filter(|n| n.len() == vertex_count) {
+        let normals: Vec<[f32; 3]> = normals
+            .iter()
+            .map(|[x, y, z, _]| normalize([-x, *y, *z]).unwrap_or([0.0, 1.0, 0.0]))
+            .collect();
+
+        primitive_attributes.insert(
+            "NORMAL".to_string(),
+            json!(builder.floats(&normals, Some(ARRAY_BUFFER), false)),
+        );
+    }
+
+    if let Some(tangents) = attributes.tangents.filter(|t| t.len() == vertex_count) {
+        // Mirroring flips the handedness of the tangent frame
+        let tangents: Vec<[f32; 4]> = tangents
+            .iter()
+            .map(|[x, y, z, w]| {
+                let [x, y, z] = normalize([-x, *y, *z]).unwrap_or([1.0, 0.0, 0.0]);
+                [x, y, z, if *w < 0.0 { 1.0 } else { -1.0 }]
+            })
+            .collect();
+
+        primitive_attributes.insert(
+            "TANGENT".to_string(),
+            json!(builder.floats(&tangents, Some(ARRAY_BUFFER), false)),
+        );
+    }
+
+    attributes.uvs.sort_by_key(|(uv_index, _)| *uv_index);
+    let uvs = attributes.uvs.into_iter().map(|(_, uvs)| uvs);
+    for (uv_index, uvs) in uvs.filter(|uvs| uvs.len() == vertex_count).enumerate() {
+        let uvs: Vec<[f32; 2]> = uvs.iter().map(|[u, v, _, _]| [*u, *v]).collect();
+
+        primitive_attributes.insert(
+            format!("TEXCOORD_{uv_index}"),
+            json!(builder.floats(&uvs, Some(ARRAY_BUFFER), false)),
+        );
+    }
+
+    if let Some(colors) = attributes.colors.filter(|c| c.len() == vertex_count) {
+        primitive_attributes.insert(
+            "COLOR_0".to_string(),
+            json!(builder.floats(&colors, Some(ARRAY_BUFFER), false)),
+        );
+    }
+
+    let bone_count = flver.bones.len();
+    if bone_count > 0 {
+        let (joints, weights) = skin_attributes(
+            flver,
+            mesh,
+            vertex_count,
+            attributes.bone_indices,
+            attributes.bone_weights,
+        );
+
+        primitive_attributes.insert("JOINTS_0".to_string(), json!(builder.joints(&joints)));
+        primitive_attributes.insert(
+            "WEIGHTS_0".to_string(),
+            json!(builder.floats(&weights, Some(ARRAY_BUFFER), false)),
+        );
+    }
+
+    let face_set = flver
+        .mesh_lod_face_set(mesh, 0)
+        .or_else(|| flver.mesh_face_sets(mesh).next())
+        .ok_or_else(|| malformed("no face sets"))?;
+    let indices = flver
+        .face_set_triangles(face_set)
+        .ok_or_else(|| malformed("face set indices out of bounds"))?;
+
+    let mut primitive = json!({
+        "attributes": primitive_attributes,
+        "indices": builder.indices(&indices),
+        "mode": TRIANGLES,
+    });
+    if mesh.material_index() < flver.materials.len() {
+        primitive["material"] = json!(mesh.material_index());
+    }
+
+    Ok(json!({
+        "name": format!("mesh{index}"),
+        "primitives": [primitive],
+    }))
+}
+
+/// Builds the joints and weights of the vertices of a mesh. Bone indices of vertices index the
+/// bone table of their mesh if it has one, and the FLVER bones otherwise. Vertices without
+/// weights are bound to their first bone, and vertices without bone indices to the default
+/// bone of their mesh.
+fn skin_attributes<O: ByteOrder + 'static>(
+    flver: &FlverInner<'_, O>,
+    mesh: &Mesh<O>,
+    vertex_count: usize,
+    bone_indices: Option<Vec<[u32; 4]>>,
+    bone_weights: Option<Vec<[f32; 4]>>,
+) -> (Vec<[u16; 4]>, Vec<[f32; 4]>) {
+    let bone_table = flver.mesh_bone_indices(mesh).unwrap_or_default();
+    let to_joint = |index: u32| {
+        let bone = if bone_table.is_empty() {
+            Some(index)
+        } else {
+            bone_table.get(index as usize).map(|bone| bone.get())
+        };
+
+        bone.filter(|bone| (*bone as usize) < flver.bones.len())
+            .unwrap_or_default() as u16
+    };
+
+    let joints = match bone_indices.filter(|indices| indices.len() == vertex_count) {
+        Some(indices) => indices
+            .iter()
+            .map(|indices| indices.map(to_joint))
+            .collect(),
+        None => {
+            let default_bone = mesh.default_bone_index().unwrap_or_default() as u32;
+            vec![[to_joint(default_bone), 0, 0, 0]; vertex_count]
+        }
+    };
+
+    let weights = match bone_weights.filter(|weights| weights.len() == vertex_count) {
+        Some(weights) => weights
+            .iter()
+            .map(|weights| {
+                let weights = weights.map(|weight| weight.max(0.0));
+                let sum: f32 = weights.iter().sum();
+                if sum > 0.0 {
+                    weights.map(|weight| weight / sum)
+                } else {
+                    [1.0, 0.0, 0.0, 0.0]
+                }
+            })
+            .collect(),
+        None => vec![[1.0, 0.0, 0.0, 0.0]; vertex_count],
+    };
+
+    (joints, weights)
+}
+
+#[allow(clippy::match_same_arms)]
+fn float_values<O: ByteOrder>(accessor: VertexAttributeAccessor<O>) -> Vec<[f32; 4]> {
+    use VertexAttributeAccessor::*;
+
+    match accessor {
+        Float2(it) => it.map(|[x, y]| [x, y, 0.0, 0.0]).collect(),
+        UV(it) => it.map(|[x, y]| [x, y, 0.0, 0.0]).collect(),
+        UVPair(it) => it.collect(),
+        Float3(it) => it.map(|[x, y, z]| [x, y, z, 0.0]).collect(),
+        Float4(it) => it.collect(),
+        UNorm8x4(it) => it.collect(),
+        SNorm8x4(it) => it.collect(),
+        SNorm16x4(it) => it.collect(),
+        BiasedNorm8x4(it) => it.collect(),
+        BiasedNorm16x4(it) => it.collect(),
+        UInt8x4(it) => it.map(|value| value.map(f32::from)).collect(),
+        UInt16x4(it) => it.map(|value| value.map(f32::from)).collect(),
+    }
+}
+
+/// Reads the raw integer components of an attribute, such as bone indices.
+#[allow(clippy::match_same_arms)]
+fn integer_values<O: ByteOrder>(accessor: VertexAttributeAccessor<O>) -> Vec<[u32; 4]> {
+    use VertexAttributeAccessor::*;
+
+    match accessor {
+        UInt8x4(it) => it.map(|value| value.map(u32::from)).collect(),
+        UInt16x4(it) => it.map(|value| value.map(u32::from)).collect(),
+        accessor => float_values(accessor)
+            .into_iter()
+            .map(|value| value.map(|component| component as u32))
+            .collect(),
+    }
+}
+
+fn export_materials<O: ByteOrder + 'static>(
+    flver: &FlverInner<'_, O>,
+) -> (Vec<Value>, Vec<Value>, Vec<Value>) {
+    let mut image_names: Vec<String> = Vec::new();
+    let materials = flver
+        .materials
+        .iter()
+        .map(|material| {
+            let mut pbr = json!({ "metallicFactor": 0.0 });
+            let mut normal_texture = None;
+            let mut texture_info = Vec::new();
+
+            for texture in flver.material_textures(material).unwrap_or_default() {
+                let path = flver.texture_path(texture).unwrap_or_default();
+                let r#type = flver.texture_type(texture).unwrap_or_default();
+                texture_info.push(json!({ "type": r#type, "path": path }));
+
+                let name = texture_name(&path);
+                if name.is_empty() {
+                    continue;
+                }
+
+                let index = match image_names.iter().position(|image| image == name) {
+                    Some(index) => index,
+                    None => {
+                        image_names.push(name.to_string());
+                        image_names.len() - 1
+                    }
+                };
+
+                let is_albedo = r#type.contains("Albedo") || r#type.contains("Diffuse");
+                let is_normal = r#type.contains("Normal") || r#type.contains("Bumpmap");
+                if is_albedo && pbr.get("baseColorTexture").is_none() {
+                    pbr["baseColorTexture"] = json!({ "index": index });
+                } else if is_normal && normal_texture.is_none() {
+                    normal_texture = Some(index);
+                }
+            }
+
+            let mut material_json = json!({
+                "name": flver.material_name(material).unwrap_or_default(),
+                "pbrMetallicRoughness": pbr,
+                "extras": {
+                    "mtd": flver.material_mtd(material).unwrap_or_default(),
+                    "textures": texture_info,
+                },
+            });
+            if let Some(index) = normal_texture {
+                material_json["normalTexture"] = json!({ "index": index });
+            }
+
+            material_json
+        })
+        .collect();
+
+    let textures = (0..image_names.len())
+        .map(|index| json!({ "source": index }))
+        .collect();
+    let images = image_names
+        .iter()
+        .map(|name| json!({ "name": name, "uri": format!("{name}.png") }))
+        .collect();
+
+    (materials, textures, images)
+}
+
+/// Gets the file name without extension from a texture path such as `N:\GR\data\c3000_a.tif`.
+fn texture_name(path: &str) -> &str {
+    let file_name = path.rsplit(['\\', '/']).next().unwrap_or(path);
+    file_name
+        .rsplit_once('.')
+        .map_or(file_name, |(stem, _)| stem)
+}
+
+/// Converts a bone translation and euler rotation to a mirrored glTF translation and rotation
+/// quaternion.
+fn mirror([tx, ty, tz]: [f32; 3], rotation: [f32; 3]) -> ([f32; 3], [f32; 4]) {
+    (
+        [-tx, ty, tz],
+        mirror_quaternion(euler_to_quaternion(rotation)),
+    )
+}
+
+/// Computes the column-major model space transform of every bone in its bind pose.
+fn world_transforms<O: ByteOrder + 'static>(flver: &FlverInner<'_, O>) -> Vec<Mat4> {
+    let local: Vec<Mat4> = flver
+        .bones
+        .iter()
+        .map(|bone| {
+            let (translation, rotation) = mirror(bone.translation(), bone.rotation());
+            from_trs(translation, rotation, bone.scale())
+        })
+        .collect();
+
+    transform::world_transforms(&local, |index| flver.bones[index].parent_index())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::flver::{export::gltf::Gltf, writer::test::test_flver, Flver};
+
+    #[test]
+    pub fn exports_skinned_meshes() {
+        let bytes = test_flver().to_bytes().expect("supported version");
+        let flver = Flver::parse(&bytes).expect("valid FLVER");
+        let gltf = Gltf::from_flver(&flver).expect("exportable FLVER");
+        let document = gltf.document();
+
+        assert_eq!(document["nodes"].as_array().map(Vec::len), Some(4));
+        assert_eq!(document["nodes"][0]["name"], "root");
+        assert_eq!(document["nodes"][0]["children"][0], 1);
+        assert_eq!(document["scenes"][0]["nodes"], serde_json::json!([0, 2, 3]));
+        assert_eq!(document["skins"][0]["joints"], serde_json::json!([0, 1]));
+        assert_eq!(document["materials"][1]["name"], "eyes");
+        assert_eq!(document["images"][0]["uri"], "body_a.png");
+
+        let primitive = &document["meshes"][0]["primitives"][0];
+        assert_eq!(primitive["material"], 0);
+        assert!(primitive["attributes"]["JOINTS_0"].is_u64());
+
+        let glb = gltf.to_glb().expect("serializable document");
+        assert_eq!(&glb[..4], b"glTF");
+        assert_eq!(
+            glb.len() as u32,
+            u32::from_le_bytes([glb[8], glb[9], glb[10], glb[11]])
+        );
+    }
+}
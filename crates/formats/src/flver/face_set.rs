@@ -3,6 +3,11 @@ use zerocopy::{FromBytes, FromZeroes, U16, U32};
 
 use crate::{flver::header::FlverHeaderPart, io_ext::zerocopy::Padding};
 
+pub const FACESET_FLAG_LOD1: u32 = 0x01000000;
+pub const FACESET_FLAG_LOD2: u32 = 0x02000000;
+pub const FACESET_FLAG_EDGECOMPRESSED: u32 = 0x40000000;
+pub const FACESET_FLAG_MOTIONBLUR: u32 = 0x80000000;
+
 pub enum FaceSetIndices<'a, O> {
     None,
     U8(&'a [u8]),
@@ -10,6 +15,35 @@ pub enum FaceSetIndices<'a, O> {
     U32(&'a [U32<O>]),
 }
 
+impl<'a, O: ByteOrder> FaceSetIndices<'a, O> {
+    pub fn len(&self) -> usize {
+        match self {
+            Self::None => 0,
+            Self::U8(indices) => indices.len(),
+            Self::U16(indices) => indices.len(),
+            Self::U32(indices) => indices.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<u32> {
+        match self {
+            Self::None => None,
+            Self::U8(indices) => indices.get(index).map(|index| *index as u32),
+            Self::U16(indices) => indices.get(index).map(|index| index.get() as u32),
+            Self::U32(indices) => indices.get(index).map(|index| index.get()),
+        }
+    }
+
+    /// Iterates over the indices, widened to `u32`.
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..self.len()).filter_map(|index| self.get(index))
+    }
+}
+
 #[derive(FromZeroes, FromBytes, Debug)]
 #[repr(C)]
 #[allow(unused)]
@@ -30,6 +64,126 @@ impl<O: ByteOrder> FaceSet<O> {
     pub fn is_lod0(&self) -> bool {
         self.flags.get() == 0
     }
+
+    pub fn flags(&self) -> u32 {
+        self.flags.get()
+    }
+
+    /// The level of detail of the face set, where 0 is the most detailed.
+    pub fn lod_level(&self) -> u8 {
+        lod_level(self.flags.get())
+    }
+
+    /// Whether the face set is only used while motion blur is applied to the model.
+    pub fn is_motion_blur(&self) -> bool {
+        self.flags.get() & FACESET_FLAG_MOTIONBLUR != 0
+    }
+
+    /// Whether the indices are stored as an edge-compressed index buffer instead of a plain
+    /// list of indices.
+    pub fn is_edge_compressed(&self) -> bool {
+        self.flags.get() & FACESET_FLAG_EDGECOMPRESSED != 0
+    }
+
+    pub fn is_triangle_strip(&self) -> bool {
+        self.triangle_strip != 0
+    }
+
+    pub fn cull_back_faces(&self) -> bool {
+        self.cull_back_faces != 0
+    }
+
+    pub fn unk06(&self) -> u16 {
+        self.unk06.get()
+    }
+
+    pub fn index_count(&self) -> usize {
+        self.index_count.get() as usize
+    }
 }
 
 impl<O: ByteOrder> FlverHeaderPart for FaceSet<O> {}
+
+pub(crate) fn lod_level(flags: u32) -> u8 {
+    if flags & FACESET_FLAG_LOD2 != 0 {
+        2
+    } else if flags & FACESET_FLAG_LOD1 != 0 {
+        1
+    } else {
+        0
+    }
+}
+
+/// The index that restarts a triangle strip for indices of the given size in bits.
+pub(crate) fn strip_restart_index(index_size: usize) -> u32 {
+    u32::MAX >> (32 - index_size.clamp(1, 32))
+}
+
+/// Converts triangle strip indices to triangle list indices. Every other triangle has its first
+/// two indices swapped to keep the winding order of the strip, degenerate triangles used to join
+/// strips are dropped, and `restart` indices start a new strip.
+pub fn unroll_triangle_strip(
+    indices: impl IntoIterator<Item = u32>,
+    restart: Option<u32>,
+) -> Vec<u32> {
+    let mut triangles = Vec::new();
+    let mut strip: Vec<u32> = Vec::with_capacity(3);
+    let mut triangle_index = 0;
+
+    for index in indices {
+        if Some(index) == restart {
+            strip.clear();
+            triangle_index = 0;
+            continue;
+        }
+
+        if strip.len() == 3 {
+            strip.remove(0);
+        }
+        strip.push(index);
+        if strip.len() < 3 {
+            continue;
+        }
+
+        let [a, b, c] = [strip[0], strip[1], strip[2]];
+        if a != b && b != c && a != c {
+            if triangle_index % 2 == 0 {
+                triangles.extend([a, b, c]);
+            } else {
+                triangles.extend([b, a, c]);
+            }
+        }
+        triangle_index += 1;
+    }
+
+    triangles
+}
+
+#[cfg(test)]
+mod test {
+    use crate::flver::face_set::{
+        lod_level, unroll_triangle_strip, FACESET_FLAG_LOD1, FACESET_FLAG_LOD2,
+        FACESET_FLAG_MOTIONBLUR,
+    };
+
+    #[test]
+    pub fn unrolls_triangle_strips() {
+        assert_eq!(
+            unroll_triangle_strip([0, 1, 2, 3, 4], None),
+            [0, 1, 2, 2, 1, 3, 2, 3, 4]
+        );
+
+        // A degenerate join between two strips and a restart before a third
+        assert_eq!(
+            unroll_triangle_strip([0, 1, 2, 2, 5, 5, 6, 7, 0xFFFF, 8, 9, 10], Some(0xFFFF)),
+            [0, 1, 2, 6, 5, 7, 8, 9, 10]
+        );
+    }
+
+    #[test]
+    pub fn reads_lod_levels() {
+        assert_eq!(lod_level(0), 0);
+        assert_eq!(lod_level(FACESET_FLAG_LOD1 | FACESET_FLAG_MOTIONBLUR), 1);
+        assert_eq!(lod_level(FACESET_FLAG_LOD2), 2);
+    }
+}
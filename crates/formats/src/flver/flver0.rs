@@ -0,0 +1,542 @@
+//! FLVER0, the model format of Demon's Souls and some Dark Souls models.
+//!
+//! FLVER0 shares its header, dummies and bones with later FLVERs, but each mesh has a single
+//! list of vertex indices and a single vertex buffer, and vertex buffer layouts are stored per
+//! material. Use [`super::owned::Flver::parse`] to read FLVER0 and later FLVERs into the same
+//! model.
+
+use std::{io::Read, ops::Deref};
+
+use byteorder::{ByteOrder, BE, LE};
+use zerocopy::{FromBytes, FromZeroes, Ref, I16, U16, U32};
+
+use crate::{
+    flver::{
+        attribute_accessor,
+        bone::Bone,
+        dummy::Dummy,
+        endianness_marker,
+        face_set::{strip_restart_index, unroll_triangle_strip, FaceSetIndices},
+        header::FlverHeader,
+        is_big_endian, read_string,
+        vertex_buffer::{accessor::VertexAttributeAccessor, VertexBufferAttribute},
+        FLVER0_VERSION_END,
+    },
+    io_ext::{zerocopy::Padding, ReadFormatsExt},
+};
+
+pub type Flver0<'a> = Flver0Inner<'a, LE>;
+
+pub struct Flver0Inner<'a, O: ByteOrder> {
+    header: &'a FlverHeader<O>,
+
+    /// The entire underlying byte array this FLVER was created from.
+    bytes: &'a [u8],
+
+    /// The data region of this FLVER, containing vertex buffers and vertex indices.
+    data: &'a [u8],
+
+    pub dummies: &'a [Dummy<O>],
+    pub materials: &'a [Material<O>],
+    pub bones: &'a [Bone<O>],
+    pub meshes: &'a [Mesh<O>],
+}
+
+impl<'a, O: ByteOrder + 'static> Deref for Flver0Inner<'a, O> {
+    type Target = FlverHeader<O>;
+
+    fn deref(&self) -> &Self::Target {
+        self.header
+    }
+}
+
+#[derive(Debug, FromZeroes, FromBytes)]
+#[repr(C, packed)]
+#[allow(unused)]
+pub struct Material<O: ByteOrder> {
+    pub(crate) name_offset: U32<O>,
+    pub(crate) mtd_offset: U32<O>,
+    pub(crate) textures_offset: U32<O>,
+    pub(crate) layout_offset: U32<O>,
+    pub(crate) data_length: U32<O>,
+    pub(crate) layout_header_offset: U32<O>,
+    _padding0: Padding<8>,
+}
+
+#[derive(Debug, FromZeroes, FromBytes)]
+#[repr(C, packed)]
+#[allow(unused)]
+struct TextureHeader {
+    count: u8,
+    _padding0: Padding<15>,
+}
+
+#[derive(Debug, FromZeroes, FromBytes)]
+#[repr(C, packed)]
+#[allow(unused)]
+pub struct Texture<O: ByteOrder> {
+    pub(crate) path_offset: U32<O>,
+    pub(crate) type_offset: U32<O>,
+    _padding0: Padding<8>,
+}
+
+#[derive(Debug, FromZeroes, FromBytes)]
+#[repr(C, packed)]
+#[allow(unused)]
+struct LayoutHeader<O: ByteOrder> {
+    count: U32<O>,
+    offsets_offset: U32<O>,
+    _padding0: Padding<8>,
+}
+
+#[derive(Debug, FromZeroes, FromBytes)]
+#[repr(C, packed)]
+#[allow(unused)]
+struct Layout<O: ByteOrder> {
+    member_count: U16<O>,
+    struct_size: U16<O>,
+    _padding0: Padding<12>,
+}
+
+/// A vertex buffer layout of a material, followed directly by its attributes.
+pub struct VertexBufferLayout<'a, O: ByteOrder> {
+    /// Size of a vertex in bytes.
+    pub vertex_size: usize,
+    pub attributes: &'a [VertexBufferAttribute<O>],
+}
+
+#[derive(Debug, FromZeroes, FromBytes)]
+#[repr(C, packed)]
+#[allow(unused)]
+pub struct Mesh<O: ByteOrder> {
+    pub(crate) dynamic: u8,
+    pub(crate) material_index: u8,
+    pub(crate) cull_back_faces: u8,
+    pub(crate) triangle_strip: u8,
+    pub(crate) vertex_index_count: U32<O>,
+    pub(crate) vertex_count: U32<O>,
+    pub(crate) default_bone_index: I16<O>,
+    pub(crate) bone_indices: [I16<O>; 28],
+    pub(crate) unk46: U16<O>,
+    pub(crate) vertex_indices_length: U32<O>,
+    pub(crate) vertex_indices_offset: U32<O>,
+    pub(crate) buffer_data_length: U32<O>,
+    pub(crate) buffer_data_offset: U32<O>,
+    pub(crate) vertex_buffers_offset: U32<O>,
+    pub(crate) vertex_buffers_offset2: U32<O>,
+    _padding0: Padding<4>,
+}
+
+impl<O: ByteOrder> Mesh<O> {
+    /// Whether the vertices of this mesh are weighted to multiple bones.
+    pub fn is_dynamic(&self) -> bool {
+        self.dynamic != 0
+    }
+
+    pub fn material_index(&self) -> usize {
+        self.material_index as usize
+    }
+
+    pub fn cull_back_faces(&self) -> bool {
+        self.cull_back_faces != 0
+    }
+
+    pub fn is_triangle_strip(&self) -> bool {
+        self.triangle_strip != 0
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertex_count.get() as usize
+    }
+
+    /// The bone static vertices of this mesh are attached to.
+    pub fn default_bone_index(&self) -> Option<usize> {
+        let index = self.default_bone_index.get();
+        (index >= 0).then_some(index as usize)
+    }
+
+    /// The indices into the bones of the FLVER referenced by the bone indices of the vertices of
+    /// this mesh. Unused slots at the end of the table are left out.
+    pub fn bone_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.bone_indices
+            .iter()
+            .map_while(|index| usize::try_from(index.get()).ok())
+    }
+
+    pub fn unk46(&self) -> u16 {
+        self.unk46.get()
+    }
+}
+
+#[derive(Debug, FromZeroes, FromBytes)]
+#[repr(C, packed)]
+#[allow(unused)]
+struct VertexBufferHeader<O: ByteOrder> {
+    count: U32<O>,
+    offset: U32<O>,
+    _padding0: Padding<8>,
+}
+
+#[derive(Debug, FromZeroes, FromBytes)]
+#[repr(C, packed)]
+#[allow(unused)]
+struct VertexBuffer<O: ByteOrder> {
+    layout_index: U32<O>,
+    length: U32<O>,
+    offset: U32<O>,
+    _padding0: Padding<4>,
+}
+
+impl<'a, O: ByteOrder + 'static> Flver0Inner<'a, O> {
+    pub fn parse(data: &'a [u8]) -> Result<Self, std::io::Error> {
+        let mut header = data
+            .get(..8)
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::UnexpectedEof))?;
+        header.read_magic(b"FLVER\0")?;
+
+        let mut endianness = [0x0u8; 2];
+        header.read_exact(&mut endianness)?;
+        if endianness != endianness_marker::<O>() {
+            return Err(std::io::Error::other("FLVER is of the wrong endianness"));
+        }
+
+        if !is_flver0(data) {
+            return Err(std::io::Error::other("FLVER is not a FLVER0"));
+        }
+
+        Self::parse_no_verify(data).ok_or_else(|| std::io::Error::other("FLVER data is unaligned"))
+    }
+
+    pub fn bone_name(&self, bone: &Bone<O>) -> Option<String> {
+        self.string(bone.name_offset.get())
+    }
+
+    pub fn material_name(&self, material: &Material<O>) -> Option<String> {
+        self.string(material.name_offset.get())
+    }
+
+    /// The path of the MTD describing the shader of a material.
+    pub fn material_mtd(&self, material: &Material<O>) -> Option<String> {
+        self.string(material.mtd_offset.get())
+    }
+
+    pub fn material_textures(&self, material: &Material<O>) -> Option<&'a [Texture<O>]> {
+        let bytes = self.bytes.get(material.textures_offset.get() as usize..)?;
+        let (header, bytes) = Ref::<_, TextureHeader>::new_from_prefix(bytes)?;
+        let (textures, _) = Texture::slice_from_prefix(bytes, header.count as usize)?;
+
+        Some(textures)
+    }
+
+    pub fn texture_path(&self, texture: &Texture<O>) -> Option<String> {
+        self.string(texture.path_offset.get())
+    }
+
+    /// The type of a texture, which isn't stored by every FLVER0.
+    pub fn texture_type(&self, texture: &Texture<O>) -> Option<String> {
+        match texture.type_offset.get() {
+            0 => None,
+            offset => self.string(offset),
+        }
+    }
+
+    /// The vertex buffer layouts used by the meshes of a material.
+    pub fn material_layouts(
+        &self,
+        material: &Material<O>,
+    ) -> Option<Vec<VertexBufferLayout<'a, O>>> {
+        let header_offset = material.layout_header_offset.get() as usize;
+        if header_offset == 0 {
+            return Some(vec![self.layout(material.layout_offset.get())?]);
+        }
+
+        let bytes = self.bytes.get(header_offset..)?;
+        let (header, _) = Ref::<_, LayoutHeader<O>>::new_from_prefix(bytes)?;
+        let (offsets, _) = U32::<O>::slice_from_prefix(
+            self.bytes.get(header.offsets_offset.get() as usize..)?,
+            header.count.get() as usize,
+        )?;
+
+        offsets
+            .iter()
+            .map(|offset| self.layout(offset.get()))
+            .collect()
+    }
+
+    fn layout(&self, offset: u32) -> Option<VertexBufferLayout<'a, O>> {
+        let bytes = self.bytes.get(offset as usize..)?;
+        let (layout, bytes) = Ref::<_, Layout<O>>::new_from_prefix(bytes)?;
+        let (attributes, _) =
+            VertexBufferAttribute::slice_from_prefix(bytes, layout.member_count.get() as usize)?;
+
+        Some(VertexBufferLayout {
+            vertex_size: layout.struct_size.get() as usize,
+            attributes,
+        })
+    }
+
+    pub fn mesh_material(&self, mesh: &Mesh<O>) -> Option<&'a Material<O>> {
+        self.materials.get(mesh.material_index())
+    }
+
+    /// The raw vertex indices of a mesh, which form a triangle strip if
+    /// [`Mesh::is_triangle_strip`] is set.
+    pub fn mesh_vertex_indices(&self, mesh: &Mesh<O>) -> Option<FaceSetIndices<'a, O>> {
+        let index_count = mesh.vertex_index_count.get() as usize;
+        let index_offset = mesh.vertex_indices_offset.get() as usize;
+        let index_size = self.vertex_index_size() as usize / 8;
+        let index_data = self
+            .data
+            .get(index_offset..index_offset + index_size * index_count)?;
+
+        Some(match index_size {
+            2 => FaceSetIndices::U16(U16::slice_from(index_data)?),
+            4 => FaceSetIndices::U32(U32::slice_from(index_data)?),
+            _ => return None,
+        })
+    }
+
+    /// The vertex indices of a mesh as a triangle list, with triangle strips unrolled.
+    pub fn mesh_triangles(&self, mesh: &Mesh<O>) -> Option<Vec<u32>> {
+        let indices = self.mesh_vertex_indices(mesh)?;
+        Some(if mesh.is_triangle_strip() {
+            let restart = strip_restart_index(self.vertex_index_size() as usize);
+            unroll_triangle_strip(indices.iter(), Some(restart))
+        } else {
+            indices.iter().collect()
+        })
+    }
+
+    /// The index of the layout in [`Self::material_layouts`] used by the vertex buffer of a
+    /// mesh, and the data of that vertex buffer.
+    pub fn mesh_vertex_buffer(&self, mesh: &Mesh<O>) -> Option<(usize, &'a [u8])> {
+        let mut layout_index = 0;
+        let mut offset = mesh.buffer_data_offset.get() as usize;
+        let mut length = mesh.buffer_data_length.get() as usize;
+
+        let buffers_offset = mesh.vertex_buffers_offset.get() as usize;
+        if buffers_offset != 0 {
+            let bytes = self.bytes.get(buffers_offset..)?;
+            let (header, _) = Ref::<_, VertexBufferHeader<O>>::new_from_prefix(bytes)?;
+            let (buffers, _) = VertexBuffer::<O>::slice_from_prefix(
+                self.bytes.get(header.offset.get() as usize..)?,
+                header.count.get() as usize,
+            )?;
+
+            // Meshes may list no buffers and use the buffer data of the mesh header instead
+            if let Some(buffer) = buffers.first() {
+                layout_index = buffer.layout_index.get() as usize;
+                offset = buffer.offset.get() as usize;
+                length = buffer.length.get() as usize;
+            }
+        }
+
+        Some((layout_index, self.data.get(offset..offset + length)?))
+    }
+
+    /// The layout of the vertex buffer of a mesh.
+    pub fn mesh_layout(&self, mesh: &Mesh<O>) -> Option<VertexBufferLayout<'a, O>> {
+        let (layout_index, _) = self.mesh_vertex_buffer(mesh)?;
+        self.material_layouts(self.mesh_material(mesh)?)?
+            .into_iter()
+            .nth(layout_index)
+    }
+
+    pub fn vertex_attribute_accessor(
+        &self,
+        mesh: &Mesh<O>,
+        attribute: &VertexBufferAttribute<O>,
+    ) -> Option<VertexAttributeAccessor<'a, O>> {
+        let (_, data) = self.mesh_vertex_buffer(mesh)?;
+        let vertex_size = self.mesh_layout(mesh)?.vertex_size;
+
+        // Console FLVER0s divide UVs by 1024, PC ones by 2048
+        let uv_factor = if is_big_endian::<O>() { 1024.0 } else { 2048.0 };
+
+        attribute_accessor(data, vertex_size, attribute, uv_factor)
+    }
+
+    fn string(&self, offset: u32) -> Option<String> {
+        read_string::<O>(self.bytes, offset, self.is_unicode())
+    }
+
+    fn parse_no_verify(bytes: &'a [u8]) -> Option<Self> {
+        let (header_ref, dummy_bytes) = Ref::<_, FlverHeader<O>>::new_from_prefix(bytes)?;
+        let header: &'a FlverHeader<O> = header_ref.into_ref();
+
+        let (dummies, next) = Dummy::<O>::slice_from_prefix(dummy_bytes, header.dummy_count())?;
+        let (materials, next) = Material::<O>::slice_from_prefix(next, header.material_count())?;
+        let (bones, next) = Bone::<O>::slice_from_prefix(next, header.bone_count())?;
+        let (meshes, _) = Mesh::<O>::slice_from_prefix(next, header.mesh_count())?;
+
+        let data_offset = header.data_offset.get() as usize;
+        let data_end = data_offset + header.data_length.get() as usize;
+        let data = bytes.get(data_offset..data_end)?;
+
+        Some(Self {
+            header,
+            bytes,
+            data,
+            dummies,
+            materials,
+            bones,
+            meshes,
+        })
+    }
+}
+
+/// Whether a FLVER is a FLVER0, going by its version.
+pub fn is_flver0(bytes: &[u8]) -> bool {
+    let Some(version) = bytes.get(8..12) else {
+        return false;
+    };
+
+    let version = match bytes.get(6..8) {
+        Some(b"B\0") => BE::read_u32(version),
+        _ => LE::read_u32(version),
+    };
+    version < FLVER0_VERSION_END
+}
+
+#[cfg(test)]
+mod test {
+    use byteorder::BE;
+
+    use crate::flver::{
+        flver0::Flver0Inner, owned, vertex_buffer::accessor::VertexAttributeAccessor, AnyFlver,
+    };
+
+    /// A big-endian FLVER0 with a single bone and a quad drawn as a triangle strip.
+    fn test_flver0() -> Vec<u8> {
+        fn set_u32(bytes: &mut [u8], offset: usize, value: u32) {
+            bytes[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+        }
+
+        fn push_string(bytes: &mut Vec<u8>, string: &str) -> u32 {
+            let offset = bytes.len() as u32;
+            bytes.extend(string.encode_utf16().flat_map(u16::to_be_bytes));
+            bytes.extend([0, 0]);
+            offset
+        }
+
+        let mut bytes = vec![0u8; 0x184];
+        bytes[..8].copy_from_slice(b"FLVER\0B\0");
+        set_u32(&mut bytes, 0x08, 0x15);
+        for (offset, count) in [(0x18, 1), (0x1C, 1), (0x20, 1), (0x24, 1)] {
+            set_u32(&mut bytes, offset, count);
+        }
+        bytes[0x48] = 16;
+        bytes[0x49] = 1;
+
+        // Bone at 0xA0, with no parent, child or siblings and a scale of 1
+        for offset in [0xBC, 0xBE, 0xCC, 0xCE] {
+            bytes[offset..offset + 2].copy_from_slice(&(-1i16).to_be_bytes());
+        }
+        for offset in [0xC0, 0xC4, 0xC8] {
+            set_u32(&mut bytes, offset, 1.0f32.to_bits());
+        }
+
+        // Mesh at 0x120
+        bytes[0x122] = 1;
+        bytes[0x123] = 1;
+        set_u32(&mut bytes, 0x124, 4);
+        set_u32(&mut bytes, 0x128, 4);
+        bytes[0x12E..0x166].fill(0xFF);
+        set_u32(&mut bytes, 0x168, 8);
+        set_u32(&mut bytes, 0x170, 48);
+        set_u32(&mut bytes, 0x174, 8);
+
+        let name = push_string(&mut bytes, "body");
+        let mtd = push_string(&mut bytes, "N:\\mtd\\body.mtd");
+        let texture_path = push_string(&mut bytes, "body_a.tga");
+        let bone_name = push_string(&mut bytes, "root");
+        set_u32(&mut bytes, 0x80, name);
+        set_u32(&mut bytes, 0x84, mtd);
+        set_u32(&mut bytes, 0xAC, bone_name);
+
+        let textures = bytes.len() as u32;
+        bytes.push(1);
+        bytes.extend([0; 15]);
+        bytes.extend(texture_path.to_be_bytes());
+        bytes.extend([0; 12]);
+        set_u32(&mut bytes, 0x88, textures);
+
+        // A layout with a single position attribute
+        let layout = bytes.len() as u32;
+        bytes.extend(1u16.to_be_bytes());
+        bytes.extend(12u16.to_be_bytes());
+        bytes.extend([0; 12]);
+        for value in [0u32, 0, 0x2, 0, 0] {
+            bytes.extend(value.to_be_bytes());
+        }
+        set_u32(&mut bytes, 0x8C, layout);
+
+        bytes.resize(bytes.len().next_multiple_of(16), 0);
+        let data_offset = bytes.len() as u32;
+        bytes.extend([0u16, 1, 2, 3].iter().flat_map(|index| index.to_be_bytes()));
+        for position in [
+            [0.0f32, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [1.0, 1.0, 0.0],
+        ] {
+            bytes.extend(
+                position
+                    .iter()
+                    .flat_map(|component| component.to_be_bytes()),
+            );
+        }
+        set_u32(&mut bytes, 0x0C, data_offset);
+        let data_length = bytes.len() as u32 - data_offset;
+        set_u32(&mut bytes, 0x10, data_length);
+
+        bytes
+    }
+
+    #[test]
+    pub fn parses_flver0() {
+        let bytes = test_flver0();
+        assert!(AnyFlver::parse(&bytes).is_err());
+
+        let flver = Flver0Inner::<BE>::parse(&bytes).expect("valid FLVER0");
+        assert_eq!(flver.version(), 0x15);
+        assert_eq!(flver.bone_name(&flver.bones[0]).as_deref(), Some("root"));
+        assert_eq!(flver.bones[0].parent_index(), None);
+
+        let material = &flver.materials[0];
+        assert_eq!(flver.material_name(material).as_deref(), Some("body"));
+        let textures = flver.material_textures(material).expect("valid textures");
+        assert_eq!(
+            flver.texture_path(&textures[0]).as_deref(),
+            Some("body_a.tga")
+        );
+        assert_eq!(flver.texture_type(&textures[0]), None);
+
+        let mesh = &flver.meshes[0];
+        assert_eq!(mesh.default_bone_index(), Some(0));
+        assert_eq!(mesh.bone_indices().count(), 0);
+        assert_eq!(flver.mesh_triangles(mesh), Some(vec![0, 1, 2, 2, 1, 3]));
+
+        let layout = flver.mesh_layout(mesh).expect("valid layout");
+        assert_eq!(layout.vertex_size, 12);
+        let Some(VertexAttributeAccessor::Float3(positions)) =
+            flver.vertex_attribute_accessor(mesh, &layout.attributes[0])
+        else {
+            panic!("position attribute should be decoded");
+        };
+        assert_eq!(positions.last(), Some([1.0, 1.0, 0.0]));
+
+        let owned = owned::Flver::parse(&bytes).expect("valid FLVER0");
+        assert!(owned.big_endian);
+        assert_eq!(owned.buffer_layouts.len(), 1);
+        assert_eq!(owned.materials[0].mtd, "N:\\mtd\\body.mtd");
+        let owned_mesh = &owned.meshes[0];
+        assert_eq!(owned_mesh.vertex_buffers[0].vertex_count, 4);
+        assert_eq!(owned_mesh.vertex_buffers[0].data.len(), 48);
+        assert_eq!(
+            owned.face_set_triangles(&owned_mesh.face_sets[0]),
+            Some(vec![0, 1, 2, 2, 1, 3])
+        );
+        assert!(owned.to_bytes().is_err());
+    }
+}
@@ -3,7 +3,7 @@ use std::mem::size_of;
 use byteorder::ByteOrder;
 use zerocopy::{AsBytes, FromBytes, FromZeroes, F32, U32};
 
-use crate::io_ext::zerocopy::Padding;
+use crate::{flver::vec3, io_ext::zerocopy::Padding};
 
 #[derive(AsBytes, FromZeroes, FromBytes)]
 #[repr(C, packed)]
@@ -42,6 +42,36 @@ pub struct FlverHeader<O: ByteOrder> {
 }
 
 impl<O: ByteOrder + 'static> FlverHeader<O> {
+    pub fn version(&self) -> u32 {
+        self.version.get()
+    }
+
+    pub fn bounding_box_min(&self) -> [f32; 3] {
+        vec3(&self.bounding_box_min)
+    }
+
+    pub fn bounding_box_max(&self) -> [f32; 3] {
+        vec3(&self.bounding_box_max)
+    }
+
+    pub fn face_count(&self) -> u32 {
+        self.face_count.get()
+    }
+
+    pub fn total_face_count(&self) -> u32 {
+        self.total_face_count.get()
+    }
+
+    /// Size in bits of the indices of face sets that don't specify their own index size.
+    pub fn vertex_index_size(&self) -> u8 {
+        self.vertex_index_size
+    }
+
+    /// Whether strings in this FLVER are encoded as UTF-16 rather than Shift-JIS.
+    pub fn is_unicode(&self) -> bool {
+        self.unicode != 0
+    }
+
     pub fn bone_count(&self) -> usize {
         self.bone_count.get() as usize
     }
@@ -76,6 +106,9 @@ impl<O: ByteOrder + 'static> FlverHeader<O> {
 }
 
 pub(crate) trait FlverHeaderPart: FromBytes + FromZeroes + Sized {
+    /// Resolves a list of indices into `parts`. Indices outside of `parts` are skipped, and a
+    /// list extending past the end of `data` yields no parts at all. Use
+    /// [`super::FlverInner::validate`] to find such lists.
     fn from_indices_at<'a, I>(
         parts: &'a [Self],
         data: &'a [u8],
@@ -85,12 +118,13 @@ pub(crate) trait FlverHeaderPart: FromBytes + FromZeroes + Sized {
     where
         I: Into<u32> + FromBytes + FromZeroes + Copy + 'static,
     {
-        let data = &data[indices_offset..indices_offset + (indices_count * size_of::<I>())];
-        let indices = I::slice_from(data).expect("buffer data was not aligned");
+        let indices = data
+            .get(indices_offset..indices_offset + (indices_count * size_of::<I>()))
+            .and_then(I::slice_from)
+            .unwrap_or_default();
 
-        indices.iter().map(|index| {
-            let index = *index;
-            &parts[index.into() as usize]
-        })
+        indices
+            .iter()
+            .filter_map(|index| parts.get((*index).into() as usize))
     }
 }
@@ -0,0 +1,694 @@
+//! Conversion of interchange formats to FLVER models.
+//!
+//! Importers gather bones, materials and meshes in the right-handed coordinate system shared by
+//! glTF and OBJ, and [`build_flver`] mirrors them along the X axis into the left-handed
+//! coordinate system of FLVERs. Mirroring also turns counter-clockwise front faces into the
+//! clockwise front faces FLVERs use, so indices keep their original order.
+
+use std::io;
+
+use thiserror::Error;
+
+use crate::flver::{
+    face_set::{FACESET_FLAG_LOD1, FACESET_FLAG_LOD2, FACESET_FLAG_MOTIONBLUR},
+    mesh::{BoundingBox, MeshBoundingBox},
+    owned::{
+        Bone, FaceSet, Flver, Material, Mesh, Texture, VertexBuffer, VertexBufferAttribute,
+        VertexBufferLayout,
+    },
+    transform::{
+        self, affine_inverse, euler_to_quaternion, from_trs, mirror_quaternion, normalize,
+        quaternion_to_euler, transform_point, Mat4,
+    },
+    vertex_buffer::{VertexAttributeFormat, VertexAttributeSemantic},
+};
+
+pub mod gltf;
+pub mod obj;
+
+#[derive(Debug, Error)]
+pub enum FlverImportError {
+    #[error("Could not parse glTF document: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Could not read glTF buffer: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Invalid glTF: {0}")]
+    InvalidGltf(String),
+
+    #[error("Unsupported glTF feature: {0}")]
+    UnsupportedGltf(&'static str),
+
+    #[error("Invalid OBJ on line {line}: {reason}")]
+    InvalidObj { line: usize, reason: String },
+
+    #[error("Mesh {mesh} is malformed: {reason}")]
+    MalformedMesh { mesh: usize, reason: &'static str },
+}
+
+/// Options controlling how imported models are laid out as a FLVER.
+#[derive(Clone, Debug)]
+pub struct ImportOptions {
+    /// FLVER version to target, see [`Flver::to_bytes`] for the supported versions.
+    pub version: u32,
+
+    /// Add LOD1 and LOD2 face sets to every mesh. Meshes aren't decimated, so these use the same
+    /// indices as the full detail face set.
+    pub lod_face_sets: bool,
+
+    /// Add a motion blur copy of every face set.
+    pub motion_blur_face_sets: bool,
+
+    /// MTD or matdef path of materials that don't specify one.
+    pub default_mtd: String,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            version: 0x2001A,
+            lod_face_sets: true,
+            motion_blur_face_sets: true,
+            default_mtd: String::new(),
+        }
+    }
+}
+
+/// A bone in a right-handed coordinate system, with an `[x, y, z, w]` rotation quaternion.
+pub(crate) struct SourceBone {
+    pub name: String,
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+    pub parent_index: Option<usize>,
+}
+
+pub(crate) struct SourceMaterial {
+    pub name: String,
+    pub mtd: Option<String>,
+    /// Pairs of texture types and paths.
+    pub textures: Vec<(String, String)>,
+}
+
+/// A triangle list in a right-handed coordinate system.
+pub(crate) struct SourceMesh {
+    pub material_index: usize,
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Option<Vec<[f32; 3]>>,
+    /// Tangents with the handedness of the bitangent in `w`, as in glTF.
+    pub tangents: Option<Vec<[f32; 4]>>,
+    pub colors: Option<Vec<[f32; 4]>>,
+    pub uvs: Vec<Vec<[f32; 2]>>,
+    /// Bone indices and weights of every vertex. Meshes without them are bound to `bone_index`.
+    pub skin: Option<Vec<([usize; 4], [f32; 4])>>,
+    pub bone_index: usize,
+    pub indices: Vec<u32>,
+}
+
+pub(crate) struct SourceModel {
+    pub bones: Vec<SourceBone>,
+    pub materials: Vec<SourceMaterial>,
+    pub meshes: Vec<SourceMesh>,
+}
+
+const UV_FACTOR: f32 = 2048.0;
+
+/// Builds a FLVER from an imported model, generating vertex buffer layouts, face sets, bone
+/// tables and bounding boxes.
+///
+/// Vertices are encoded with the formats used by the game: float positions, normals and
+/// tangents as biased bytes, bone weights as normalized shorts, bone indices as bytes (or
+/// shorts for meshes using more than 256 bones) and UVs as shorts scaled by 2048.
+pub(crate) fn build_flver(
+    mut model: SourceModel,
+    options: &ImportOptions,
+) -> Result<Flver, FlverImportError> {
+    if model.bones.is_empty() {
+        model.bones.push(SourceBone {
+            name: "root".to_string(),
+            translation: [0.0; 3],
+            rotation: [0.0, 0.0, 0.0, 1.0],
+            scale: [1.0; 3],
+            parent_index: None,
+        });
+    }
+
+    let mut bones: Vec<Bone> = model
+        .bones
+        .iter()
+        .map(|bone| {
+            let [tx, ty, tz] = bone.translation;
+            Bone {
+                name: bone.name.clone(),
+                translation: [-tx, ty, tz],
+                rotation: quaternion_to_euler(mirror_quaternion(bone.rotation)),
+                scale: bone.scale,
+                parent_index: bone.parent_index,
+                child_index: None,
+                next_sibling_index: None,
+                previous_sibling_index: None,
+                bounding_box_min: [0.0; 3],
+                bounding_box_max: [0.0; 3],
+                unk3c: 0,
+            }
+        })
+        .collect();
+    link_bones(&mut bones);
+
+    let inverse_bind_matrices: Vec<Mat4> =
+        bind_matrices(&bones).iter().map(affine_inverse).collect();
+    let mut bone_bounds = vec![BoundingBox::default(); bones.len()];
+
+    let materials: Vec<Material> = model
+        .materials
+        .into_iter()
+        .enumerate()
+        .map(|(index, material)| Material {
+            name: material.name,
+            mtd: material.mtd.unwrap_or_else(|| options.default_mtd.clone()),
+            flags: 0,
+            gx_index: None,
+            unk18: index as u32,
+            textures: material
+                .textures
+                .into_iter()
+                .map(|(r#type, path)| Texture {
+                    path,
+                    r#type,
+                    scale: [1.0, 1.0],
+                    unk10: 1,
+                    unk11: true,
+                    unk14: 0.0,
+                    unk18: 0.0,
+                    unk1c: 0.0,
+                })
+                .collect(),
+        })
+        .collect();
+
+    let mut face_set_flags = vec![0];
+    if options.lod_face_sets {
+        face_set_flags.extend([FACESET_FLAG_LOD1, FACESET_FLAG_LOD2]);
+    }
+    if options.motion_blur_face_sets {
+        let motion_blur = face_set_flags.iter().map(|f| f | FACESET_FLAG_MOTIONBLUR);
+        face_set_flags.extend(motion_blur.collect::<Vec<_>>());
+    }
+
+    let mut model_bounds = BoundingBox::default();
+    let mut buffer_layouts: Vec<VertexBufferLayout> = Vec::new();
+    let mut meshes = Vec::with_capacity(model.meshes.len());
+    let mut face_count = 0;
+    let mut total_face_count = 0;
+    let mut vertex_index_size = 16u8;
+
+    for (index, mut mesh) in model.meshes.into_iter().enumerate() {
+        let malformed = |reason| FlverImportError::MalformedMesh {
+            mesh: index,
+            reason,
+        };
+
+        let vertex_count = mesh.positions.len();
+        if vertex_count == 0 {
+            return Err(malformed("no vertices"));
+        }
+        if !mesh.indices.len().is_multiple_of(3) {
+            return Err(malformed("index count is not a multiple of 3"));
+        }
+        if mesh.indices.iter().any(|i| *i as usize >= vertex_count) {
+            return Err(malformed("vertex index out of bounds"));
+        }
+        if mesh
+            .normals
+            .as_ref()
+            .is_some_and(|n| n.len() != vertex_count)
+            || mesh
+                .tangents
+                .as_ref()
+                .is_some_and(|t| t.len() != vertex_count)
+            || mesh
+                .colors
+                .as_ref()
+                .is_some_and(|c| c.len() != vertex_count)
+            || mesh.skin.as_ref().is_some_and(|s| s.len() != vertex_count)
+            || mesh.uvs.iter().any(|uvs| uvs.len() != vertex_count)
+        {
+            return Err(malformed("vertex attribute counts differ"));
+        }
+        if mesh.material_index >= materials.len() {
+            return Err(malformed("material index out of bounds"));
+        }
+
+        let normals = mesh
+            .normals
+            .take()
+            .unwrap_or_else(|| smooth_normals(&mesh.positions, &mesh.indices));
+        let tangents = mesh
+            .tangents
+            .take()
+            .unwrap_or_else(|| generate_tangents(&mesh, &normals));
+
+        // Bone indices of vertices index the bone table of their mesh
+        let mut bone_table: Vec<usize> = Vec::new();
+        let skin: Option<Vec<([usize; 4], [f32; 4])>> = match mesh.skin.take() {
+            Some(skin) => Some(
+                skin.into_iter()
+                    .map(|(indices, weights)| {
+                        let sum: f32 = weights.iter().map(|w| w.max(0.0)).sum();
+                        let weights = if sum > 0.0 {
+                            weights.map(|w| w.max(0.0) / sum)
+                        } else {
+                            [1.0, 0.0, 0.0, 0.0]
+                        };
+
+                        let mut table_indices = [0; 4];
+                        for slot in 0..4 {
+                            if weights[slot] <= 0.0 {
+                                continue;
+                            }
+
+                            let bone = indices[slot];
+                            if bone >= bones.len() {
+                                return Err(malformed("bone index out of bounds"));
+                            }
+
+                            table_indices[slot] = match bone_table.iter().position(|b| *b == bone) {
+                                Some(table_index) => table_index,
+                                None => {
+                                    bone_table.push(bone);
+                                    bone_table.len() - 1
+                                }
+                            };
+                        }
+
+                        Ok((table_indices, weights))
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            None => {
+                if mesh.bone_index >= bones.len() {
+                    return Err(malformed("bone index out of bounds"));
+                }
+
+                bone_table.push(mesh.bone_index);
+                None
+            }
+        };
+
+        let positions: Vec<[f32; 3]> = mesh.positions.iter().map(|p| mirror(*p)).collect();
+        let mut mesh_bounds = BoundingBox::default();
+        for (vertex, position) in positions.iter().enumerate() {
+            mesh_bounds.add(*position);
+
+            let influences = match &skin {
+                Some(skin) => {
+                    let (indices, weights) = skin[vertex];
+                    (0..4)
+                        .filter(|slot| weights[*slot] > 0.0)
+                        .map(|slot| bone_table[indices[slot]])
+                        .collect()
+                }
+                None => vec![bone_table[0]],
+            };
+
+            for bone in influences {
+                let local = transform_point(&inverse_bind_matrices[bone], *position);
+                bone_bounds[bone].add(local);
+            }
+        }
+        model_bounds.merge(&mesh_bounds);
+
+        let short_bone_indices = bone_table.len() > 256;
+        let mut attributes = vec![(
+            VertexAttributeSemantic::Position,
+            0,
+            VertexAttributeFormat::Float3,
+        )];
+        if skin.is_some() {
+            attributes.push((
+                VertexAttributeSemantic::BoneWeights,
+                0,
+                VertexAttributeFormat::Short4ToFloat4A,
+            ));
+            attributes.push(if short_bone_indices {
+                (
+                    VertexAttributeSemantic::BoneIndices,
+                    0,
+                    VertexAttributeFormat::ShortBoneIndices,
+                )
+            } else {
+                (
+                    VertexAttributeSemantic::BoneIndices,
+                    0,
+                    VertexAttributeFormat::Byte4B,
+                )
+            });
+        }
+        attributes.push((
+            VertexAttributeSemantic::Normal,
+            0,
+            VertexAttributeFormat::Byte4A,
+        ));
+        attributes.push((
+            VertexAttributeSemantic::Tangent,
+            0,
+            VertexAttributeFormat::Byte4A,
+        ));
+        if mesh.colors.is_some() {
+            attributes.push((
+                VertexAttributeSemantic::VertexColor,
+                0,
+                VertexAttributeFormat::Byte4C,
+            ));
+        }
+        for uv_index in 0..mesh.uvs.len() {
+            attributes.push((
+                VertexAttributeSemantic::UV,
+                uv_index as u32,
+                VertexAttributeFormat::UV,
+            ));
+        }
+
+        let mut layout = VertexBufferLayout {
+            attributes: Vec::with_capacity(attributes.len()),
+        };
+        let mut vertex_size = 0;
+        for (semantic, index, format) in attributes {
+            let size = match format {
+                VertexAttributeFormat::Float3 => 12,
+                VertexAttributeFormat::Short4ToFloat4A
+                | VertexAttributeFormat::ShortBoneIndices => 8,
+                _ => 4,
+            };
+
+            layout.attributes.push(VertexBufferAttribute {
+                unk0: 0,
+                struct_offset: vertex_size,
+                format_id: format as u32,
+                semantic,
+                index,
+            });
+            vertex_size += size;
+        }
+
+        let mut data = Vec::with_capacity(vertex_count * vertex_size as usize);
+        for vertex in 0..vertex_count {
+            for attribute in &layout.attributes {
+                match attribute.semantic {
+                    VertexAttributeSemantic::Position => {
+                        data.extend(positions[vertex].iter().flat_map(|c| c.to_le_bytes()));
+                    }
+                    VertexAttributeSemantic::BoneWeights => {
+                        let (_, weights) = skin.as_ref().map(|s| s[vertex]).unwrap_or_default();
+                        data.extend(
+                            weights
+                                .iter()
+                                .flat_map(|w| ((w * 32767.0).round() as i16).to_le_bytes()),
+                        );
+                    }
+                    VertexAttributeSemantic::BoneIndices => {
+                        let (indices, _) = skin.as_ref().map(|s| s[vertex]).unwrap_or_default();
+                        if short_bone_indices {
+                            data.extend(indices.iter().flat_map(|i| (*i as u16).to_le_bytes()));
+                        } else {
+                            data.extend(indices.iter().map(|i| *i as u8));
+                        }
+                    }
+                    VertexAttributeSemantic::Normal => {
+                        let normal = normalize(mirror(normals[vertex])).unwrap_or([0.0, 1.0, 0.0]);
+                        data.extend(normal.map(snorm8));
+                        // Static meshes store the index of their bone in the bone table here
+                        data.push(if skin.is_some() { 127 } else { 0 });
+                    }
+                    VertexAttributeSemantic::Tangent => {
+                        let [x, y, z, w] = tangents[vertex];
+                        let tangent = normalize(mirror([x, y, z])).unwrap_or([1.0, 0.0, 0.0]);
+                        data.extend(tangent.map(snorm8));
+                        // Mirroring flips the handedness of the tangent frame
+                        data.push(if w < 0.0 { 254 } else { 0 });
+                    }
+                    VertexAttributeSemantic::VertexColor => {
+                        let color = mesh.colors.as_ref().map(|c| c[vertex]).unwrap_or([1.0; 4]);
+                        data.extend(color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8));
+                    }
+                    VertexAttributeSemantic::UV => {
+                        let uv = mesh.uvs[attribute.index as usize][vertex];
+                        data.extend(uv.iter().flat_map(|c| {
+                            let scaled = (c * UV_FACTOR).round();
+                            (scaled.clamp(i16::MIN as f32, i16::MAX as f32) as i16).to_le_bytes()
+                        }));
+                    }
+                    VertexAttributeSemantic::Bitangent | VertexAttributeSemantic::Unknown(_) => {}
+                }
+            }
+        }
+
+        let layout_index = match buffer_layouts
+            .iter()
+            .position(|existing| same_layout(existing, &layout))
+        {
+            Some(layout_index) => layout_index,
+            None => {
+                buffer_layouts.push(layout);
+                buffer_layouts.len() - 1
+            }
+        };
+
+        let index_size = if vertex_count > u16::MAX as usize {
+            32
+        } else {
+            16
+        };
+        vertex_index_size = vertex_index_size.max(index_size as u8);
+
+        let triangle_count = (mesh.indices.len() / 3) as u32;
+        face_count += triangle_count;
+        total_face_count += triangle_count * face_set_flags.len() as u32;
+
+        let face_sets = face_set_flags
+            .iter()
+            .map(|flags| FaceSet {
+                flags: *flags,
+                triangle_strip: false,
+                cull_back_faces: true,
+                unk06: 0,
+                index_size,
+                indices: mesh.indices.clone(),
+            })
+            .collect();
+
+        meshes.push(Mesh {
+            dynamic: skin.is_some(),
+            material_index: mesh.material_index,
+            default_bone_index: bone_table.first().copied(),
+            bone_indices: bone_table.iter().map(|bone| *bone as u32).collect(),
+            bounding_box: Some(MeshBoundingBox {
+                min: mesh_bounds.min,
+                max: mesh_bounds.max,
+                unk: (options.version >= 0x2001A).then_some([0.0; 3]),
+            }),
+            face_sets,
+            vertex_buffers: vec![VertexBuffer {
+                buffer_index: 0,
+                layout_index: layout_index as u32,
+                vertex_size,
+                vertex_count: vertex_count as u32,
+                data,
+            }],
+        });
+    }
+
+    for (bone, bounds) in bones.iter_mut().zip(bone_bounds) {
+        if !bounds.is_empty() {
+            bone.bounding_box_min = bounds.min;
+            bone.bounding_box_max = bounds.max;
+        }
+    }
+
+    let (bounding_box_min, bounding_box_max) = if model_bounds.is_empty() {
+        ([0.0; 3], [0.0; 3])
+    } else {
+        (model_bounds.min, model_bounds.max)
+    };
+
+    Ok(Flver {
+        big_endian: false,
+        version: options.version,
+        bounding_box_min,
+        bounding_box_max,
+        face_count,
+        total_face_count,
+        vertex_index_size,
+        unicode: true,
+        unk4a: 0,
+        unk4b: 0,
+        unk4c: 0,
+        unk5c: 0,
+        unk5d: 0,
+        unk68: 4,
+        dummies: Vec::new(),
+        materials,
+        bones,
+        meshes,
+        buffer_layouts,
+        gx_lists: Vec::new(),
+    })
+}
+
+fn mirror([x, y, z]: [f32; 3]) -> [f32; 3] {
+    [-x, y, z]
+}
+
+fn snorm8(value: f32) -> u8 {
+    (value.clamp(-1.0, 1.0) * 127.0 + 127.0).round() as u8
+}
+
+fn same_layout(a: &VertexBufferLayout, b: &VertexBufferLayout) -> bool {
+    a.attributes.len() == b.attributes.len()
+        && a.attributes.iter().zip(&b.attributes).all(|(a, b)| {
+            a.struct_offset == b.struct_offset
+                && a.format_id == b.format_id
+                && a.semantic == b.semantic
+                && a.index == b.index
+        })
+}
+
+/// Fills in the child and sibling links of bones from their parents.
+fn link_bones(bones: &mut [Bone]) {
+    let parents: Vec<Option<usize>> = bones.iter().map(|bone| bone.parent_index).collect();
+    let mut previous_child: Vec<Option<usize>> = vec![None; bones.len()];
+    let mut previous_root = None;
+
+    for (index, parent) in parents.iter().enumerate() {
+        let previous = match parent {
+            Some(parent) => previous_child.get_mut(*parent),
+            None => Some(&mut previous_root),
+        };
+        let Some(previous) = previous else {
+            continue;
+        };
+
+        match previous.replace(index) {
+            Some(sibling) => {
+                bones[sibling].next_sibling_index = Some(index);
+                bones[index].previous_sibling_index = Some(sibling);
+            }
+            None => {
+                if let Some(parent) = parent {
+                    bones[*parent].child_index = Some(index);
+                }
+            }
+        }
+    }
+}
+
+/// Computes the model space transform of every bone in its bind pose.
+fn bind_matrices(bones: &[Bone]) -> Vec<Mat4> {
+    let local: Vec<Mat4> = bones
+        .iter()
+        .map(|bone| {
+            from_trs(
+                bone.translation,
+                euler_to_quaternion(bone.rotation),
+                bone.scale,
+            )
+        })
+        .collect();
+
+    transform::world_transforms(&local, |index| bones[index].parent_index)
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Averages the normals of the faces around every vertex, weighted by their area.
+fn smooth_normals(positions: &[[f32; 3]], indices: &[u32]) -> Vec<[f32; 3]> {
+    let mut normals = vec![[0.0; 3]; positions.len()];
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|corner| positions[triangle[corner] as usize]);
+        let normal = cross(sub(b, a), sub(c, a));
+
+        for index in triangle {
+            let sum: &mut [f32; 3] = &mut normals[*index as usize];
+            for axis in 0..3 {
+                sum[axis] += normal[axis];
+            }
+        }
+    }
+
+    normals
+        .into_iter()
+        .map(|normal| normalize(normal).unwrap_or([0.0, 1.0, 0.0]))
+        .collect()
+}
+
+/// Generates tangents from the first UV set of a mesh, or an arbitrary tangent perpendicular to
+/// the normal for meshes without UVs.
+fn generate_tangents(mesh: &SourceMesh, normals: &[[f32; 3]]) -> Vec<[f32; 4]> {
+    let mut tangents = vec![[0.0; 3]; mesh.positions.len()];
+    let mut bitangents = vec![[0.0; 3]; mesh.positions.len()];
+
+    if let Some(uvs) = mesh.uvs.first() {
+        for triangle in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|corner| triangle[corner] as usize);
+            let (edge1, edge2) = (
+                sub(mesh.positions[b], mesh.positions[a]),
+                sub(mesh.positions[c], mesh.positions[a]),
+            );
+            let (du1, dv1) = (uvs[b][0] - uvs[a][0], uvs[b][1] - uvs[a][1]);
+            let (du2, dv2) = (uvs[c][0] - uvs[a][0], uvs[c][1] - uvs[a][1]);
+
+            let determinant = du1 * dv2 - du2 * dv1;
+            if determinant.abs() <= f32::EPSILON {
+                continue;
+            }
+
+            let r = 1.0 / determinant;
+            let tangent: [f32; 3] = std::array::from_fn(|i| (edge1[i] * dv2 - edge2[i] * dv1) * r);
+            let bitangent: [f32; 3] =
+                std::array::from_fn(|i| (edge2[i] * du1 - edge1[i] * du2) * r);
+
+            for index in triangle {
+                for axis in 0..3 {
+                    tangents[*index as usize][axis] += tangent[axis];
+                    bitangents[*index as usize][axis] += bitangent[axis];
+                }
+            }
+        }
+    }
+
+    normals
+        .iter()
+        .zip(tangents.into_iter().zip(bitangents))
+        .map(|(normal, (tangent, bitangent))| {
+            // Orthogonalize against the normal, falling back to any perpendicular axis
+            let projected = sub(tangent, normal.map(|n| n * dot(*normal, tangent)));
+            let [x, y, z] = normalize(projected)
+                .or_else(|| normalize(cross(*normal, [0.0, 1.0, 0.0])))
+                .or_else(|| normalize(cross(*normal, [1.0, 0.0, 0.0])))
+                .unwrap_or([1.0, 0.0, 0.0]);
+
+            let w = if dot(cross(*normal, [x, y, z]), bitangent) < 0.0 {
+                -1.0
+            } else {
+                1.0
+            };
+
+            [x, y, z, w]
+        })
+        .collect()
+}
@@ -0,0 +1,753 @@
+//! Import of glTF 2.0 scenes as FLVER models.
+//!
+//! The joints of all skins in the scene become the bones of the FLVER, or a single root bone is
+//! created for scenes without skins. Every primitive becomes a FLVER mesh. Skinned primitives are
+//! expected to be in their bind pose in model space, as written by the exporter, while the
+//! vertices of other primitives are transformed to model space and bound to the first bone.
+//!
+//! Materials keep the MTD and texture paths stored in their `extras` by the exporter. Materials
+//! without them reference their base color and normal textures by image name.
+
+use std::{borrow::Cow, io};
+
+use serde_json::Value;
+
+use crate::flver::{
+    export::gltf::{GLB_BIN_CHUNK, GLB_JSON_CHUNK, GLB_MAGIC},
+    import::{
+        build_flver, FlverImportError, ImportOptions, SourceBone, SourceMaterial, SourceMesh,
+        SourceModel,
+    },
+    owned::Flver,
+    transform::{
+        self, affine_inverse, decompose, from_trs, normalize, transform_point, transform_vector,
+        Mat4, IDENTITY,
+    },
+};
+
+const BYTE: u64 = 5120;
+const UNSIGNED_BYTE: u64 = 5121;
+const SHORT: u64 = 5122;
+const UNSIGNED_SHORT: u64 = 5123;
+const UNSIGNED_INT: u64 = 5125;
+const FLOAT: u64 = 5126;
+const TRIANGLES: u64 = 4;
+
+/// Imports a binary `.glb` file.
+pub fn import_glb(bytes: &[u8], options: &ImportOptions) -> Result<Flver, FlverImportError> {
+    if bytes.get(..4) != Some(GLB_MAGIC) {
+        return Err(invalid("missing GLB magic"));
+    }
+    if read_u32(bytes, 4) != Some(2) {
+        return Err(FlverImportError::UnsupportedGltf(
+            "GLB versions other than 2",
+        ));
+    }
+
+    let mut json = None;
+    let mut binary = None;
+    let mut offset = 12;
+    while offset < bytes.len() {
+        let length = read_u32(bytes, offset).ok_or_else(|| invalid("truncated GLB chunk"))?;
+        let chunk_type = bytes.get(offset + 4..offset + 8);
+        let chunk = bytes
+            .get(offset + 8..offset + 8 + length as usize)
+            .ok_or_else(|| invalid("truncated GLB chunk"))?;
+
+        if chunk_type == Some(GLB_JSON_CHUNK) && json.is_none() {
+            json = Some(chunk);
+        } else if chunk_type == Some(GLB_BIN_CHUNK) && binary.is_none() {
+            binary = Some(chunk);
+        }
+
+        offset += 8 + length as usize;
+    }
+
+    let json = json.ok_or_else(|| invalid("missing JSON chunk"))?;
+    import(
+        serde_json::from_slice(json)?,
+        binary,
+        |uri| {
+            Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("external buffer {uri} in GLB"),
+            ))
+        },
+        options,
+    )
+}
+
+/// Imports the JSON document of a `.gltf` file. Buffers with a URI other than a base64 data URI
+/// are read with `load_uri`.
+pub fn import_gltf<F>(
+    json: &[u8],
+    load_uri: F,
+    options: &ImportOptions,
+) -> Result<Flver, FlverImportError>
+where
+    F: FnMut(&str) -> Result<Vec<u8>, io::Error>,
+{
+    import(serde_json::from_slice(json)?, None, load_uri, options)
+}
+
+fn import<F>(
+    json: Value,
+    binary: Option<&[u8]>,
+    mut load_uri: F,
+    options: &ImportOptions,
+) -> Result<Flver, FlverImportError>
+where
+    F: FnMut(&str) -> Result<Vec<u8>, io::Error>,
+{
+    let buffers = array(&json, "buffers")
+        .iter()
+        .map(|buffer| match buffer["uri"].as_str() {
+            None => binary
+                .map(Cow::Borrowed)
+                .ok_or_else(|| invalid("buffer without URI outside of a GLB")),
+            Some(uri) => match uri.strip_prefix("data:") {
+                Some(data) => data
+                    .split_once(";base64,")
+                    .and_then(|(_, data)| decode_base64(data))
+                    .map(Cow::Owned)
+                    .ok_or_else(|| invalid("malformed data URI")),
+                None => Ok(Cow::Owned(load_uri(uri)?)),
+            },
+        })
+        .collect::<Result<_, _>>()?;
+
+    let document = Document { json, buffers };
+    let nodes = document.scene_nodes()?;
+
+    let mut model = SourceModel {
+        bones: Vec::new(),
+        materials: Vec::new(),
+        meshes: Vec::new(),
+    };
+
+    // Map the joints of every skin to bones
+    let mut bone_nodes: Vec<usize> = Vec::new();
+    let skin_bones: Vec<Vec<usize>> = array(&document.json, "skins")
+        .iter()
+        .map(|skin| {
+            array(skin, "joints")
+                .iter()
+                .map(|joint| {
+                    let joint = index(joint).ok_or_else(|| invalid("malformed skin joint"))?;
+                    Ok(match bone_nodes.iter().position(|node| *node == joint) {
+                        Some(bone) => bone,
+                        None => {
+                            bone_nodes.push(joint);
+                            bone_nodes.len() - 1
+                        }
+                    })
+                })
+                .collect::<Result<_, FlverImportError>>()
+        })
+        .collect::<Result<_, _>>()?;
+
+    for node_index in &bone_nodes {
+        let node = document.get("nodes", *node_index)?;
+        let parent_index = nodes[*node_index]
+            .parent
+            .and_then(|parent| bone_nodes.iter().position(|node| *node == parent));
+
+        // Bones whose parent isn't a joint are placed at their position in the scene
+        let (translation, rotation, scale) = match parent_index {
+            Some(_) if node.get("matrix").is_none() => (
+                floats(&node["translation"], [0.0; 3]),
+                floats(&node["rotation"], [0.0, 0.0, 0.0, 1.0]),
+                floats(&node["scale"], [1.0; 3]),
+            ),
+            Some(_) => decompose(&local_transform(node)),
+            None => decompose(&nodes[*node_index].world),
+        };
+
+        model.bones.push(SourceBone {
+            name: node["name"]
+                .as_str()
+                .map_or_else(|| format!("bone{}", model.bones.len()), str::to_string),
+            translation,
+            rotation,
+            scale,
+            parent_index,
+        });
+    }
+
+    let material_count = array(&document.json, "materials").len();
+    let mut default_material = None;
+    for (node_index, node_info) in nodes.iter().enumerate() {
+        if !node_info.in_scene {
+            continue;
+        }
+
+        let node = document.get("nodes", node_index)?;
+        let Some(mesh) = node.get("mesh").and_then(index) else {
+            continue;
+        };
+
+        let skin = node.get("skin").and_then(index);
+        for primitive in array(document.get("meshes", mesh)?, "primitives") {
+            let material_index = match primitive.get("material").and_then(index) {
+                Some(material) if material < material_count => material,
+                Some(_) => return Err(invalid("material index out of bounds")),
+                None => *default_material.get_or_insert(material_count),
+            };
+
+            let joints = skin.and_then(|skin| skin_bones.get(skin));
+            model.meshes.push(document.primitive(
+                primitive,
+                material_index,
+                joints,
+                &node_info.world,
+            )?);
+        }
+    }
+
+    for material in array(&document.json, "materials") {
+        model
+            .materials
+            .push(document.material(material, model.materials.len()));
+    }
+    if default_material.is_some() {
+        model.materials.push(SourceMaterial {
+            name: "default".to_string(),
+            mtd: None,
+            textures: Vec::new(),
+        });
+    }
+
+    build_flver(model, options)
+}
+
+struct Document<'a> {
+    json: Value,
+    buffers: Vec<Cow<'a, [u8]>>,
+}
+
+#[derive(Clone)]
+struct NodeInfo {
+    parent: Option<usize>,
+    world: Mat4,
+    in_scene: bool,
+}
+
+impl Document<'_> {
+    fn get(&self, key: &str, index: usize) -> Result<&Value, FlverImportError> {
+        self.json[key]
+            .get(index)
+            .ok_or_else(|| invalid(format!("{key} index {index} out of bounds")))
+    }
+
+    /// Finds the parent and world transform of every node, and whether it's part of the scene.
+    fn scene_nodes(&self) -> Result<Vec<NodeInfo>, FlverImportError> {
+        let nodes = array(&self.json, "nodes");
+        let mut info = vec![
+            NodeInfo {
+                parent: None,
+                world: IDENTITY,
+                in_scene: false,
+            };
+            nodes.len()
+        ];
+
+        for (index, node) in nodes.iter().enumerate() {
+            for child in array(node, "children") {
+                let child = index_in(child, nodes.len())?;
+                info[child].parent = Some(index);
+            }
+        }
+
+        let scene = self.json["scene"].as_u64().unwrap_or(0) as usize;
+        let mut stack: Vec<(usize, Mat4)> = match self.json["scenes"].get(scene) {
+            Some(scene) => array(scene, "nodes")
+                .iter()
+                .map(|node| Ok((index_in(node, nodes.len())?, IDENTITY)))
+                .collect::<Result<_, FlverImportError>>()?,
+            None => (0..nodes.len())
+                .filter(|index| info[*index].parent.is_none())
+                .map(|index| (index, IDENTITY))
+                .collect(),
+        };
+
+        while let Some((index, parent_world)) = stack.pop() {
+            if info[index].in_scene {
+                return Err(invalid("node hierarchy contains a cycle"));
+            }
+
+            let world = transform::mul(&parent_world, &local_transform(&nodes[index]));
+            info[index].world = world;
+            info[index].in_scene = true;
+
+            for child in array(&nodes[index], "children") {
+                stack.push((index_in(child, nodes.len())?, world));
+            }
+        }
+
+        // Joints outside of the scene still need a transform to be placed at
+        for index in 0..nodes.len() {
+            if !info[index].in_scene {
+                info[index].world = local_transform(&nodes[index]);
+            }
+        }
+
+        Ok(info)
+    }
+
+    fn primitive(
+        &self,
+        primitive: &Value,
+        material_index: usize,
+        joints: Option<&Vec<usize>>,
+        world: &Mat4,
+    ) -> Result<SourceMesh, FlverImportError> {
+        if primitive["mode"].as_u64().unwrap_or(TRIANGLES) != TRIANGLES {
+            return Err(FlverImportError::UnsupportedGltf(
+                "primitive modes other than triangle lists",
+            ));
+        }
+
+        let attributes = &primitive["attributes"];
+        let attribute = |name: &str| attributes.get(name).and_then(index);
+
+        let mut positions: Vec<[f32; 3]> = vectors(self.accessor(
+            attribute("POSITION").ok_or_else(|| invalid("primitive without positions"))?,
+        )?);
+        let mut normals: Option<Vec<[f32; 3]>> = attribute("NORMAL")
+            .map(|accessor| self.accessor(accessor).map(vectors))
+            .transpose()?;
+        let mut tangents: Option<Vec<[f32; 4]>> = attribute("TANGENT")
+            .map(|accessor| self.accessor(accessor).map(vectors))
+            .transpose()?;
+        let colors = attribute("COLOR_0")
+            .map(|accessor| {
+                self.accessor(accessor).map(|(components, values)| {
+                    let mut colors: Vec<[f32; 4]> = vectors((components, values));
+                    if components == 3 {
+                        colors.iter_mut().for_each(|color| color[3] = 1.0);
+                    }
+                    colors
+                })
+            })
+            .transpose()?;
+
+        let mut uvs = Vec::new();
+        while let Some(accessor) = attribute(&format!("TEXCOORD_{}", uvs.len())) {
+            uvs.push(vectors(self.accessor(accessor)?));
+        }
+
+        let skin = match (joints, attribute("JOINTS_0"), attribute("WEIGHTS_0")) {
+            (Some(joints), Some(joint_accessor), Some(weight_accessor)) => {
+                let indices: Vec<[f32; 4]> = vectors(self.accessor(joint_accessor)?);
+                let weights: Vec<[f32; 4]> = vectors(self.accessor(weight_accessor)?);
+                let skin = indices
+                    .iter()
+                    .zip(weights)
+                    .map(|(indices, weights)| {
+                        let bones = indices
+                            .map(|joint| joints.get(joint as usize).copied().unwrap_or(usize::MAX));
+                        (bones, weights)
+                    })
+                    .collect();
+
+                Some(skin)
+            }
+            _ => None,
+        };
+
+        if skin.is_none() {
+            // Transform normals with the inverse transpose to handle non-uniform scale
+            let inverse = affine_inverse(world);
+            let normal_matrix: Mat4 = std::array::from_fn(|i| {
+                if i % 4 == 3 || i >= 12 {
+                    0.0
+                } else {
+                    inverse[(i % 4) * 4 + i / 4]
+                }
+            });
+
+            positions = positions
+                .iter()
+                .map(|position| transform_point(world, *position))
+                .collect();
+            normals = normals.map(|normals| {
+                normals
+                    .iter()
+                    .map(|normal| {
+                        normalize(transform_vector(&normal_matrix, *normal)).unwrap_or(*normal)
+                    })
+                    .collect()
+            });
+            tangents = tangents.map(|tangents| {
+                tangents
+                    .iter()
+                    .map(|[x, y, z, w]| {
+                        let [x, y, z] = transform_vector(world, [*x, *y, *z]);
+                        [x, y, z, *w]
+                    })
+                    .collect()
+            });
+        }
+
+        let indices = match primitive.get("indices").and_then(index) {
+            Some(accessor) => self
+                .accessor(accessor)?
+                .1
+                .into_iter()
+                .map(|index| index as u32)
+                .collect(),
+            None => (0..positions.len() as u32).collect(),
+        };
+
+        Ok(SourceMesh {
+            material_index,
+            positions,
+            normals,
+            tangents,
+            colors,
+            uvs,
+            skin,
+            bone_index: 0,
+            indices,
+        })
+    }
+
+    /// Reads the components of every element of an accessor, converting normalized integers to
+    /// floats.
+    fn accessor(&self, accessor_index: usize) -> Result<(usize, Vec<f64>), FlverImportError> {
+        let accessor = self.get("accessors", accessor_index)?;
+        if accessor.get("sparse").is_some() {
+            return Err(FlverImportError::UnsupportedGltf("sparse accessors"));
+        }
+
+        let count = accessor["count"]
+            .as_u64()
+            .ok_or_else(|| invalid("accessor without count"))? as usize;
+        let components = match accessor["type"].as_str() {
+            Some("SCALAR") => 1,
+            Some("VEC2") => 2,
+            Some("VEC3") => 3,
+            Some("VEC4") => 4,
+            Some("MAT4") => 16,
+            _ => return Err(FlverImportError::UnsupportedGltf("accessor type")),
+        };
+
+        let component_type = accessor["componentType"].as_u64().unwrap_or_default();
+        let size = match component_type {
+            BYTE | UNSIGNED_BYTE => 1,
+            SHORT | UNSIGNED_SHORT => 2,
+            UNSIGNED_INT | FLOAT => 4,
+            _ => return Err(FlverImportError::UnsupportedGltf("accessor component type")),
+        };
+        let normalized = accessor["normalized"].as_bool().unwrap_or(false);
+
+        // Without sparse values, an accessor without a buffer view is all zeroes
+        let Some(view_index) = accessor.get("bufferView").and_then(index) else {
+            return Err(FlverImportError::UnsupportedGltf(
+                "accessors without a buffer view",
+            ));
+        };
+        let view = self.get("bufferViews", view_index)?;
+        let buffer = view
+            .get("buffer")
+            .and_then(index)
+            .and_then(|buffer| self.buffers.get(buffer))
+            .ok_or_else(|| invalid("buffer index out of bounds"))?;
+
+        let view_offset = view["byteOffset"].as_u64().unwrap_or(0) as usize;
+        let view_length = view["byteLength"].as_u64().unwrap_or(0) as usize;
+        let data = view_offset
+            .checked_add(view_length)
+            .and_then(|end| buffer.get(view_offset..end))
+            .ok_or_else(|| invalid("buffer view out of bounds"))?;
+
+        let offset = accessor["byteOffset"].as_u64().unwrap_or(0) as usize;
+        let stride = view["byteStride"]
+            .as_u64()
+            .map_or(components * size, |stride| stride as usize);
+
+        // The count comes straight from the file, so check that every element lies within the
+        // buffer view before allocating for them
+        if stride < components * size {
+            return Err(invalid("buffer view stride smaller than its elements"));
+        }
+        let in_bounds = match count.checked_sub(1) {
+            Some(last) => last
+                .checked_mul(stride)
+                .and_then(|start| start.checked_add(offset))
+                .and_then(|start| start.checked_add(components * size))
+                .is_some_and(|end| end <= data.len()),
+            None => true,
+        };
+        if !in_bounds {
+            return Err(invalid("accessor out of bounds"));
+        }
+
+        let mut values = Vec::with_capacity(count * components);
+        for element in 0..count {
+            for component in 0..components {
+                let start = offset + element * stride + component * size;
+                let bytes = data
+                    .get(start..start + size)
+                    .ok_or_else(|| invalid("accessor out of bounds"))?;
+
+                let value = match component_type {
+                    BYTE => f64::from(bytes[0] as i8),
+                    UNSIGNED_BYTE => f64::from(bytes[0]),
+                    SHORT => f64::from(i16::from_le_bytes([bytes[0], bytes[1]])),
+                    UNSIGNED_SHORT => f64::from(u16::from_le_bytes([bytes[0], bytes[1]])),
+                    UNSIGNED_INT => {
+                        f64::from(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                    }
+                    _ => f64::from(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+                };
+
+                values.push(match (normalized, component_type) {
+                    (true, BYTE) => (value / 127.0).max(-1.0),
+                    (true, UNSIGNED_BYTE) => value / 255.0,
+                    (true, SHORT) => (value / 32767.0).max(-1.0),
+                    (true, UNSIGNED_SHORT) => value / 65535.0,
+                    _ => value,
+                });
+            }
+        }
+
+        Ok((components, values))
+    }
+
+    fn material(&self, material: &Value, index: usize) -> SourceMaterial {
+        let extras = &material["extras"];
+        let mut textures: Vec<(String, String)> = array(extras, "textures")
+            .iter()
+            .filter_map(|texture| {
+                let r#type = texture["type"].as_str()?;
+                let path = texture["path"].as_str()?;
+                Some((r#type.to_string(), path.to_string()))
+            })
+            .collect();
+
+        if textures.is_empty() {
+            for (r#type, texture) in [
+                (
+                    "g_DiffuseTexture",
+                    &material["pbrMetallicRoughness"]["baseColorTexture"],
+                ),
+                ("g_BumpmapTexture", &material["normalTexture"]),
+            ] {
+                if let Some(name) = texture.get("index").and_then(|t| self.image_name(t)) {
+                    textures.push((r#type.to_string(), format!("{name}.tif")));
+                }
+            }
+        }
+
+        SourceMaterial {
+            name: material["name"]
+                .as_str()
+                .map_or_else(|| format!("material{index}"), str::to_string),
+            mtd: extras["mtd"].as_str().map(str::to_string),
+            textures,
+        }
+    }
+
+    /// Gets the name of the image of a texture, or the file name of its URI without extension.
+    fn image_name(&self, texture: &Value) -> Option<String> {
+        let texture = self.json["textures"].get(index(texture)?)?;
+        let image = self.json["images"].get(texture.get("source").and_then(index)?)?;
+
+        if let Some(name) = image["name"].as_str() {
+            return Some(name.to_string());
+        }
+
+        let uri = image["uri"]
+            .as_str()
+            .filter(|uri| !uri.starts_with("data:"))?;
+        let file_name = uri.rsplit(['\\', '/']).next().unwrap_or(uri);
+        Some(
+            file_name
+                .rsplit_once('.')
+                .map_or(file_name, |(stem, _)| stem)
+                .to_string(),
+        )
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> FlverImportError {
+    FlverImportError::InvalidGltf(reason.into())
+}
+
+fn array<'v>(value: &'v Value, key: &str) -> &'v [Value] {
+    value[key].as_array().map_or(&[], Vec::as_slice)
+}
+
+fn index(value: &Value) -> Option<usize> {
+    value.as_u64().map(|index| index as usize)
+}
+
+fn index_in(value: &Value, len: usize) -> Result<usize, FlverImportError> {
+    index(value)
+        .filter(|index| *index < len)
+        .ok_or_else(|| invalid("node index out of bounds"))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let bytes = bytes.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn floats<const N: usize>(value: &Value, default: [f32; N]) -> [f32; N] {
+    match value.as_array() {
+        Some(values) if values.len() == N => {
+            std::array::from_fn(|i| values[i].as_f64().map_or(default[i], |v| v as f32))
+        }
+        _ => default,
+    }
+}
+
+fn local_transform(node: &Value) -> Mat4 {
+    if node.get("matrix").is_some() {
+        return floats(&node["matrix"], IDENTITY);
+    }
+
+    from_trs(
+        floats(&node["translation"], [0.0; 3]),
+        floats(&node["rotation"], [0.0, 0.0, 0.0, 1.0]),
+        floats(&node["scale"], [1.0; 3]),
+    )
+}
+
+/// Groups accessor components into vectors, filling missing components with zeroes.
+fn vectors<const N: usize>((components, values): (usize, Vec<f64>)) -> Vec<[f32; N]> {
+    values
+        .chunks_exact(components)
+        .map(|element| std::array::from_fn(|i| element.get(i).map_or(0.0, |v| *v as f32)))
+        .collect()
+}
+
+fn decode_base64(data: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(data.len() / 4 * 3);
+    let mut accumulator = 0u32;
+    let mut bits = 0;
+
+    for character in data.bytes().take_while(|c| *c != b'=') {
+        let value = match character {
+            b'A'..=b'Z' => character - b'A',
+            b'a'..=b'z' => character - b'a' + 26,
+            b'0'..=b'9' => character - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return None,
+        };
+
+        accumulator = (accumulator << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((accumulator >> bits) as u8);
+        }
+    }
+
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io;
+
+    use serde_json::{json, Value};
+
+    use crate::flver::{
+        export::gltf::Gltf,
+        face_set::FACESET_FLAG_MOTIONBLUR,
+        import::{
+            gltf::{import, import_glb},
+            FlverImportError, ImportOptions,
+        },
+        owned,
+        writer::test::test_flver,
+        Flver,
+    };
+
+    #[test]
+    pub fn imports_exported_models() {
+        let source = test_flver();
+        let bytes = source.to_bytes().expect("supported version");
+        let flver = Flver::parse(&bytes).expect("valid FLVER");
+        let glb = Gltf::from_flver(&flver)
+            .and_then(|gltf| gltf.to_glb())
+            .expect("exportable FLVER");
+
+        let imported = import_glb(&glb, &ImportOptions::default()).expect("importable glTF");
+        assert_eq!(imported.bones.len(), 2);
+        assert_eq!(imported.bones[1].name, "spine");
+        assert_eq!(imported.bones[1].parent_index, Some(0));
+        assert_eq!(imported.bones[0].child_index, Some(1));
+        for (bone, source) in imported.bones.iter().zip(&source.bones) {
+            assert!(bone
+                .translation
+                .iter()
+                .chain(&bone.rotation)
+                .zip(source.translation.iter().chain(&source.rotation))
+                .all(|(a, b)| (a - b).abs() < 1e-5));
+        }
+
+        assert_eq!(imported.materials[1].name, "eyes");
+        assert_eq!(imported.materials[0].mtd, "N:\\mtd\\body.matxml");
+        assert_eq!(imported.materials[0].textures[1].path, "body_n.tif");
+
+        assert_eq!(imported.meshes.len(), 2);
+        let mesh = &imported.meshes[0];
+        assert!(mesh.dynamic);
+        assert_eq!(mesh.bone_indices, [0]);
+        assert_eq!(mesh.face_sets.len(), 6);
+        assert_eq!(mesh.face_sets[3].flags, FACESET_FLAG_MOTIONBLUR);
+        assert_eq!(mesh.face_sets[0].indices, [0, 1, 2]);
+        assert_eq!(mesh.vertex_buffers[0].vertex_count, 3);
+        assert_eq!(imported.face_count, 2);
+        assert_eq!(imported.total_face_count, 12);
+
+        // The imported FLVER can be written and read back
+        let bytes = imported.to_bytes().expect("supported version");
+        let parsed = owned::Flver::parse(&bytes).expect("valid FLVER");
+        assert_eq!(parsed.buffer_layouts.len(), 1);
+        assert_eq!(
+            parsed.meshes[1].vertex_buffers[0].data,
+            imported.meshes[1].vertex_buffers[0].data
+        );
+    }
+
+    #[test]
+    pub fn rejects_accessors_outside_of_their_buffer_view() {
+        let bytes = test_flver().to_bytes().expect("supported version");
+        let flver = Flver::parse(&bytes).expect("valid FLVER");
+        let glb = Gltf::from_flver(&flver)
+            .and_then(|gltf| gltf.to_glb())
+            .expect("exportable FLVER");
+
+        // Split the GLB into its JSON and binary chunks
+        let json_length = u32::from_le_bytes([glb[12], glb[13], glb[14], glb[15]]) as usize;
+        let json: Value = serde_json::from_slice(&glb[20..20 + json_length]).expect("valid JSON");
+        let binary = &glb[20 + json_length + 8..];
+
+        for (key, value) in [
+            ("count", json!(u64::MAX)),
+            ("count", json!(1u64 << 40)),
+            ("byteOffset", json!(u64::MAX - 3)),
+        ] {
+            let mut json = json.clone();
+            for accessor in json["accessors"].as_array_mut().expect("accessors") {
+                accessor[key] = value.clone();
+            }
+
+            let result = import(
+                json,
+                Some(binary),
+                |_| Err(io::Error::from(io::ErrorKind::NotFound)),
+                &ImportOptions::default(),
+            );
+            assert!(
+                matches!(result, Err(FlverImportError::InvalidGltf(_))),
+                "{key} = {value}"
+            );
+        }
+    }
+}
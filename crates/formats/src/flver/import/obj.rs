@@ -0,0 +1,297 @@
+//! Import of Wavefront OBJ models as FLVER models.
+//!
+//! OBJ models have no skeleton, so a single root bone is created and every material becomes a
+//! static mesh bound to it. Faces with more than three vertices are triangulated as fans.
+
+use std::collections::HashMap;
+
+use crate::flver::{
+    import::{
+        build_flver, FlverImportError, ImportOptions, SourceMaterial, SourceMesh, SourceModel,
+    },
+    owned::Flver,
+};
+
+/// Imports an OBJ model. Texture maps of materials are read from the MTL library if one is
+/// given, with diffuse maps (`map_Kd`) and bump maps (`map_Bump`, `bump` or `norm`) referenced by
+/// their file name.
+pub fn import_obj(
+    obj: &str,
+    mtl: Option<&str>,
+    options: &ImportOptions,
+) -> Result<Flver, FlverImportError> {
+    let textures = mtl.map(material_textures).unwrap_or_default();
+
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut uvs: Vec<[f32; 2]> = Vec::new();
+    let mut normals: Vec<[f32; 3]> = Vec::new();
+
+    let mut material_names: Vec<String> = Vec::new();
+    let mut meshes: Vec<ObjMesh> = Vec::new();
+    let mut current_mesh = None;
+
+    for (line_index, line) in obj.lines().enumerate() {
+        let error = |reason: &str| FlverImportError::InvalidObj {
+            line: line_index + 1,
+            reason: reason.to_string(),
+        };
+
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("v") => {
+                positions.push(parse_floats(tokens).ok_or_else(|| error("malformed vertex"))?);
+            }
+            Some("vt") => {
+                let [u, v] = parse_floats(tokens).ok_or_else(|| error("malformed UV"))?;
+                // OBJ UVs start at the bottom of the texture
+                uvs.push([u, 1.0 - v]);
+            }
+            Some("vn") => {
+                normals.push(parse_floats(tokens).ok_or_else(|| error("malformed normal"))?);
+            }
+            Some("usemtl") => {
+                let name = tokens.collect::<Vec<_>>().join(" ");
+                current_mesh = Some(mesh_index(&mut meshes, &mut material_names, name));
+            }
+            Some("f") => {
+                let mesh = *current_mesh.get_or_insert_with(|| {
+                    mesh_index(&mut meshes, &mut material_names, "default".to_string())
+                });
+
+                let corners = tokens
+                    .map(|corner| {
+                        let mut parts = corner.split('/');
+                        let mut reference =
+                            |count: usize| -> Result<Option<usize>, FlverImportError> {
+                                match parts.next().filter(|part| !part.is_empty()) {
+                                    None => Ok(None),
+                                    Some(part) => resolve_index(part, count)
+                                        .map(Some)
+                                        .ok_or_else(|| error("face index out of bounds")),
+                                }
+                            };
+
+                        let position = reference(positions.len())?
+                            .ok_or_else(|| error("face corner without a vertex"))?;
+                        Ok([
+                            Some(position),
+                            reference(uvs.len())?,
+                            reference(normals.len())?,
+                        ])
+                    })
+                    .collect::<Result<Vec<_>, FlverImportError>>()?;
+
+                if corners.len() < 3 {
+                    return Err(error("face with fewer than 3 vertices"));
+                }
+
+                let mesh = &mut meshes[mesh];
+                let corners: Vec<u32> = corners
+                    .into_iter()
+                    .map(|corner| mesh.vertex(corner, &positions, &uvs, &normals))
+                    .collect();
+                for i in 1..corners.len() - 1 {
+                    mesh.indices
+                        .extend([corners[0], corners[i], corners[i + 1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let materials = material_names
+        .into_iter()
+        .map(|name| SourceMaterial {
+            textures: textures.get(&name).cloned().unwrap_or_default(),
+            name,
+            mtd: None,
+        })
+        .collect();
+
+    let meshes = meshes
+        .into_iter()
+        .enumerate()
+        .filter(|(_, mesh)| !mesh.indices.is_empty())
+        .map(|(material_index, mesh)| SourceMesh {
+            material_index,
+            normals: mesh.all_normals.then_some(mesh.normals),
+            tangents: None,
+            colors: None,
+            uvs: if mesh.any_uvs { vec![mesh.uvs] } else { vec![] },
+            skin: None,
+            bone_index: 0,
+            positions: mesh.positions,
+            indices: mesh.indices,
+        })
+        .collect();
+
+    build_flver(
+        SourceModel {
+            bones: Vec::new(),
+            materials,
+            meshes,
+        },
+        options,
+    )
+}
+
+/// The vertices of the faces using one material, deduplicated by the attributes referenced by
+/// their face corners.
+struct ObjMesh {
+    vertices: HashMap<[Option<usize>; 3], u32>,
+    positions: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    normals: Vec<[f32; 3]>,
+    indices: Vec<u32>,
+    any_uvs: bool,
+    all_normals: bool,
+}
+
+impl ObjMesh {
+    fn vertex(
+        &mut self,
+        corner: [Option<usize>; 3],
+        positions: &[[f32; 3]],
+        uvs: &[[f32; 2]],
+        normals: &[[f32; 3]],
+    ) -> u32 {
+        if let Some(vertex) = self.vertices.get(&corner) {
+            return *vertex;
+        }
+
+        let [position, uv, normal] = corner;
+        self.positions
+            .push(position.map_or([0.0; 3], |index| positions[index]));
+        self.uvs.push(uv.map_or([0.0; 2], |index| uvs[index]));
+        self.normals
+            .push(normal.map_or([0.0; 3], |index| normals[index]));
+        self.any_uvs |= uv.is_some();
+        self.all_normals &= normal.is_some();
+
+        let vertex = self.positions.len() as u32 - 1;
+        self.vertices.insert(corner, vertex);
+        vertex
+    }
+}
+
+fn mesh_index(meshes: &mut Vec<ObjMesh>, material_names: &mut Vec<String>, name: String) -> usize {
+    if let Some(index) = material_names.iter().position(|existing| *existing == name) {
+        return index;
+    }
+
+    material_names.push(name);
+    meshes.push(ObjMesh {
+        vertices: HashMap::new(),
+        positions: Vec::new(),
+        uvs: Vec::new(),
+        normals: Vec::new(),
+        indices: Vec::new(),
+        any_uvs: false,
+        all_normals: true,
+    });
+    meshes.len() - 1
+}
+
+/// Parses the first `N` components of an element, ignoring optional trailing components.
+fn parse_floats<'a, const N: usize>(mut tokens: impl Iterator<Item = &'a str>) -> Option<[f32; N]> {
+    let mut values = [0.0; N];
+    for value in &mut values {
+        *value = tokens.next()?.parse().ok()?;
+    }
+
+    Some(values)
+}
+
+/// Resolves a one-based or negative relative OBJ index.
+fn resolve_index(index: &str, count: usize) -> Option<usize> {
+    let index: isize = index.parse().ok()?;
+    let resolved = match index {
+        1.. => index as usize - 1,
+        ..=-1 => count.checked_sub(index.unsigned_abs())?,
+        0 => return None,
+    };
+
+    (resolved < count).then_some(resolved)
+}
+
+/// Reads the diffuse and bump maps of every material of an MTL library.
+fn material_textures(mtl: &str) -> HashMap<String, Vec<(String, String)>> {
+    let mut materials: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    let mut current = None;
+
+    for line in mtl.lines() {
+        let mut tokens = line.split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+
+        let r#type = match keyword {
+            "newmtl" => {
+                let name = tokens.collect::<Vec<_>>().join(" ");
+                materials.entry(name.clone()).or_default();
+                current = Some(name);
+                continue;
+            }
+            "map_Kd" => "g_DiffuseTexture",
+            "map_Bump" | "map_bump" | "bump" | "norm" => "g_BumpmapTexture",
+            _ => continue,
+        };
+
+        // Options such as `-bm 1.0` come before the file name
+        let (Some(material), Some(path)) = (&current, tokens.last()) else {
+            continue;
+        };
+        let file_name = path.rsplit(['\\', '/']).next().unwrap_or(path);
+        let stem = file_name
+            .rsplit_once('.')
+            .map_or(file_name, |(stem, _)| stem);
+
+        materials
+            .entry(material.clone())
+            .or_default()
+            .push((r#type.to_string(), format!("{stem}.tif")));
+    }
+
+    materials
+}
+
+#[cfg(test)]
+mod test {
+    use crate::flver::{
+        import::{obj::import_obj, ImportOptions},
+        owned,
+    };
+
+    #[test]
+    pub fn imports_obj_models() {
+        let obj = "\
+            v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+            vt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\n\
+            vn 0 0 1\n\
+            usemtl stone\n\
+            f 1/1/1 2/2/1 3/3/1 4/4/1\n\
+            usemtl moss\n\
+            f -4 -2 -1\n";
+        let mtl = "newmtl stone\nmap_Kd textures/stone_a.png\nmap_Bump -bm 1 stone_n.png\n";
+
+        let flver = import_obj(obj, Some(mtl), &ImportOptions::default()).expect("valid OBJ");
+        assert_eq!(flver.bones.len(), 1);
+        assert_eq!(flver.materials[0].name, "stone");
+        assert_eq!(flver.materials[0].textures[0].path, "stone_a.tif");
+        assert_eq!(flver.materials[0].textures[1].r#type, "g_BumpmapTexture");
+        assert!(flver.materials[1].textures.is_empty());
+
+        assert_eq!(flver.meshes.len(), 2);
+        assert!(!flver.meshes[0].dynamic);
+        assert_eq!(flver.meshes[0].vertex_buffers[0].vertex_count, 4);
+        assert_eq!(flver.meshes[0].face_sets[0].indices, [0, 1, 2, 0, 2, 3]);
+        assert_eq!(flver.meshes[1].material_index, 1);
+        assert_eq!(flver.bounding_box_min, [-1.0, 0.0, 0.0]);
+        assert_eq!(flver.bounding_box_max, [0.0, 1.0, 0.0]);
+
+        let bytes = flver.to_bytes().expect("supported version");
+        let parsed = owned::Flver::parse(&bytes).expect("valid FLVER");
+        assert_eq!(parsed.meshes[1].face_sets[0].indices, [0, 1, 2]);
+        assert_eq!(parsed.buffer_layouts.len(), 2);
+    }
+}
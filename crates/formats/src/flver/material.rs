@@ -1,20 +1,108 @@
-use byteorder::ByteOrder;
+use byteorder::{ByteOrder, BE, LE};
 use zerocopy::{FromBytes, FromZeroes, U32};
 
-use crate::flver::header::FlverHeaderPart;
+use crate::flver::{header::FlverHeaderPart, is_big_endian};
 
 #[derive(FromZeroes, FromBytes)]
 #[repr(C, packed)]
 #[allow(unused)]
 pub struct Material<O: ByteOrder> {
-    name_offset: U32<O>,
-    mtd_name_offset: U32<O>,
+    pub(crate) name_offset: U32<O>,
+    pub(crate) mtd_name_offset: U32<O>,
     texture_count: U32<O>,
     texture_index: U32<O>,
     flags: U32<O>,
-    gx_offset: U32<O>,
+    pub(crate) gx_offset: U32<O>,
     unk18: U32<O>,
     unk1c: U32<O>,
 }
 
+impl<O: ByteOrder> Material<O> {
+    pub fn texture_count(&self) -> usize {
+        self.texture_count.get() as usize
+    }
+
+    /// Index of the first texture of this material in the FLVER texture list.
+    pub fn texture_index(&self) -> usize {
+        self.texture_index.get() as usize
+    }
+
+    pub fn flags(&self) -> u32 {
+        self.flags.get()
+    }
+
+    pub fn unk18(&self) -> u32 {
+        self.unk18.get()
+    }
+}
+
 impl<O: ByteOrder> FlverHeaderPart for Material<O> {}
+
+/// An item of a GX list, holding parameters for one part of the shader of a material.
+#[derive(Debug)]
+pub struct GxItem<'a> {
+    /// Four character ID of the item, such as `GXMD` or `GX00`.
+    pub id: [u8; 4],
+    pub unk04: i32,
+    pub data: &'a [u8],
+    big_endian: bool,
+}
+
+impl<'a> GxItem<'a> {
+    /// The ID as a string, if it's ASCII.
+    pub fn id_str(&self) -> Option<&str> {
+        std::str::from_utf8(&self.id)
+            .ok()
+            .filter(|id| id.is_ascii())
+    }
+
+    /// Reads the data of this item as 32-bit floats.
+    pub fn f32_values(&self) -> impl Iterator<Item = f32> + 'a {
+        let read = if self.big_endian {
+            BE::read_f32
+        } else {
+            LE::read_f32
+        };
+        self.data.chunks_exact(4).map(read)
+    }
+
+    /// Reads the data of this item as 32-bit signed integers.
+    pub fn i32_values(&self) -> impl Iterator<Item = i32> + 'a {
+        let read = if self.big_endian {
+            BE::read_i32
+        } else {
+            LE::read_i32
+        };
+        self.data.chunks_exact(4).map(read)
+    }
+}
+
+/// Splits a GX list into its items, stopping at the terminator item, which has an ID of
+/// `i32::MAX` (or -1 in older versions). Returns the items and the length of the list including
+/// the terminator.
+pub(crate) fn gx_items<O: ByteOrder>(list: &[u8]) -> Option<(Vec<GxItem<'_>>, usize)> {
+    let mut items = Vec::new();
+    let mut position = 0;
+
+    loop {
+        let header = list.get(position..position + 12)?;
+        let id = O::read_i32(&header[..4]);
+        let length = O::read_i32(&header[8..]);
+        if length < 12 {
+            return None;
+        }
+
+        let end = position + length as usize;
+        if id == i32::MAX || id == -1 {
+            return Some((items, end));
+        }
+
+        items.push(GxItem {
+            id: [header[0], header[1], header[2], header[3]],
+            unk04: O::read_i32(&header[4..8]),
+            data: list.get(position + 12..end)?,
+            big_endian: is_big_endian::<O>(),
+        });
+        position = end;
+    }
+}
@@ -21,4 +21,86 @@ pub struct Mesh<O: ByteOrder = LE> {
     pub vertex_buffer_offset: U32<O>,
 }
 
+/// The bounds of a mesh, stored outside of the mesh header.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeshBoundingBox {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+    /// Only present from version 0x2001A onwards.
+    pub unk: Option<[f32; 3]>,
+}
+
+/// Axis-aligned bounds of a set of points. The default bounds are empty and contain no points.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoundingBox {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+impl Default for BoundingBox {
+    fn default() -> Self {
+        Self {
+            min: [f32::INFINITY; 3],
+            max: [f32::NEG_INFINITY; 3],
+        }
+    }
+}
+
+impl BoundingBox {
+    pub fn add(&mut self, point: [f32; 3]) {
+        self.min = std::array::from_fn(|axis| self.min[axis].min(point[axis]));
+        self.max = std::array::from_fn(|axis| self.max[axis].max(point[axis]));
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        if !other.is_empty() {
+            self.add(other.min);
+            self.add(other.max);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min[0] > self.max[0]
+    }
+
+    /// Whether `other` lies within these bounds, allowing for an error of `tolerance` on each
+    /// axis.
+    pub fn contains(&self, other: &Self, tolerance: f32) -> bool {
+        other.is_empty()
+            || (0..3).all(|axis| {
+                other.min[axis] >= self.min[axis] - tolerance
+                    && other.max[axis] <= self.max[axis] + tolerance
+            })
+    }
+}
+
+impl<O: ByteOrder> Mesh<O> {
+    /// Whether the vertices of this mesh are weighted to multiple bones.
+    pub fn is_dynamic(&self) -> bool {
+        self.dynamic != 0
+    }
+
+    pub fn material_index(&self) -> usize {
+        self.material_index.get() as usize
+    }
+
+    /// The bone static vertices of this mesh are attached to.
+    pub fn default_bone_index(&self) -> Option<usize> {
+        let index = self.default_bone_index.get() as i32;
+        (index >= 0).then_some(index as usize)
+    }
+
+    pub fn bone_count(&self) -> usize {
+        self.bone_count.get() as usize
+    }
+
+    pub fn face_set_count(&self) -> usize {
+        self.face_set_count.get() as usize
+    }
+
+    pub fn vertex_buffer_count(&self) -> usize {
+        self.vertex_buffer_count.get() as usize
+    }
+}
+
 impl<O: ByteOrder> FlverHeaderPart for Mesh<O> {}
@@ -0,0 +1,612 @@
+use std::io;
+
+use byteorder::{ByteOrder, BE, LE};
+
+use crate::flver::{
+    face_set::{
+        lod_level, strip_restart_index, unroll_triangle_strip, FACESET_FLAG_EDGECOMPRESSED,
+        FACESET_FLAG_MOTIONBLUR,
+    },
+    flver0::{is_flver0, Flver0Inner},
+    is_big_endian,
+    material::{gx_items, GxItem},
+    mesh::MeshBoundingBox,
+    vertex_buffer::VertexAttributeSemantic,
+    AnyFlver, FlverInner,
+};
+
+/// An owned copy of a FLVER, detached from the bytes it was parsed from.
+///
+/// This is built from the zero-copy [`FlverInner`] view, so both share the same parser. Parts
+/// that reference each other by offset in the file are nested instead: textures are stored in
+/// their material, and face sets and vertex buffers in their mesh.
+#[derive(Debug)]
+pub struct Flver {
+    /// Whether the FLVER is stored big-endian, as on consoles. Vertex buffers and GX lists are
+    /// kept in this byte order.
+    pub big_endian: bool,
+    pub version: u32,
+    pub bounding_box_min: [f32; 3],
+    pub bounding_box_max: [f32; 3],
+    pub face_count: u32,
+    pub total_face_count: u32,
+    pub vertex_index_size: u8,
+    pub unicode: bool,
+    pub unk4a: u8,
+    pub unk4b: u8,
+    pub unk4c: u32,
+    pub unk5c: u8,
+    pub unk5d: u8,
+    pub unk68: u32,
+
+    pub dummies: Vec<Dummy>,
+    pub materials: Vec<Material>,
+    pub bones: Vec<Bone>,
+    pub meshes: Vec<Mesh>,
+    pub buffer_layouts: Vec<VertexBufferLayout>,
+    /// Raw GX lists referenced by materials, including their terminator items.
+    pub gx_lists: Vec<Vec<u8>>,
+}
+
+#[derive(Debug)]
+pub struct Dummy {
+    pub position: [f32; 3],
+    pub color: [u8; 4],
+    pub forward: [f32; 3],
+    pub up_vector: [f32; 3],
+    pub reference_id: u16,
+    pub parent_bone_index: Option<usize>,
+    pub attached_bone_index: Option<usize>,
+    pub flag_1: bool,
+    pub use_up_vector: bool,
+    pub unk30: u32,
+    pub unk34: u32,
+}
+
+#[derive(Debug)]
+pub struct Material {
+    pub name: String,
+    pub mtd: String,
+    pub flags: u32,
+    /// Index into [`Flver::gx_lists`].
+    pub gx_index: Option<usize>,
+    pub unk18: u32,
+    pub textures: Vec<Texture>,
+}
+
+#[derive(Debug)]
+pub struct Texture {
+    pub path: String,
+    pub r#type: String,
+    pub scale: [f32; 2],
+    pub unk10: u8,
+    pub unk11: bool,
+    pub unk14: f32,
+    pub unk18: f32,
+    pub unk1c: f32,
+}
+
+#[derive(Debug)]
+pub struct Bone {
+    pub name: String,
+    pub translation: [f32; 3],
+    pub rotation: [f32; 3],
+    pub scale: [f32; 3],
+    pub parent_index: Option<usize>,
+    pub child_index: Option<usize>,
+    pub next_sibling_index: Option<usize>,
+    pub previous_sibling_index: Option<usize>,
+    pub bounding_box_min: [f32; 3],
+    pub bounding_box_max: [f32; 3],
+    pub unk3c: u32,
+}
+
+#[derive(Debug)]
+pub struct Mesh {
+    pub dynamic: bool,
+    pub material_index: usize,
+    pub default_bone_index: Option<usize>,
+    pub bone_indices: Vec<u32>,
+    pub bounding_box: Option<MeshBoundingBox>,
+    pub face_sets: Vec<FaceSet>,
+    pub vertex_buffers: Vec<VertexBuffer>,
+}
+
+#[derive(Debug)]
+pub struct FaceSet {
+    pub flags: u32,
+    pub triangle_strip: bool,
+    pub cull_back_faces: bool,
+    pub unk06: u16,
+    /// Size of the indices in bits, or 0 to use [`Flver::vertex_index_size`].
+    pub index_size: u32,
+    /// The indices of the face set. For edge-compressed face sets this is the compressed data
+    /// split into units of the index size, kept so the face set can be written back unchanged.
+    pub indices: Vec<u32>,
+}
+
+impl FaceSet {
+    /// The level of detail of the face set, where 0 is the most detailed.
+    pub fn lod_level(&self) -> u8 {
+        lod_level(self.flags)
+    }
+
+    pub fn is_motion_blur(&self) -> bool {
+        self.flags & FACESET_FLAG_MOTIONBLUR != 0
+    }
+
+    pub fn is_edge_compressed(&self) -> bool {
+        self.flags & FACESET_FLAG_EDGECOMPRESSED != 0
+    }
+}
+
+#[derive(Debug)]
+pub struct VertexBuffer {
+    pub buffer_index: u32,
+    pub layout_index: u32,
+    pub vertex_size: u32,
+    pub vertex_count: u32,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct VertexBufferLayout {
+    pub attributes: Vec<VertexBufferAttribute>,
+}
+
+impl VertexBufferLayout {
+    pub fn attribute_by_semantic(
+        &self,
+        semantic: VertexAttributeSemantic,
+    ) -> Option<&VertexBufferAttribute> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.semantic == semantic)
+    }
+}
+
+#[derive(Debug)]
+pub struct VertexBufferAttribute {
+    pub unk0: u32,
+    pub struct_offset: u32,
+    pub format_id: u32,
+    pub semantic: VertexAttributeSemantic,
+    pub index: u32,
+}
+
+impl Flver {
+    /// Parses a FLVER of either byte order, including FLVER0s.
+    pub fn parse(bytes: &[u8]) -> Result<Self, io::Error> {
+        if is_flver0(bytes) {
+            return match bytes.get(6..8) {
+                Some(b"B\0") => Self::try_from(&Flver0Inner::<BE>::parse(bytes)?),
+                _ => Self::try_from(&Flver0Inner::<LE>::parse(bytes)?),
+            };
+        }
+
+        match AnyFlver::parse(bytes)? {
+            AnyFlver::LittleEndian(flver) => Self::try_from(&flver),
+            AnyFlver::BigEndian(flver) => Self::try_from(&flver),
+        }
+    }
+
+    /// The parsed items of the GX list of a material, excluding the terminator item.
+    pub fn material_gx_items(&self, material: &Material) -> Option<Vec<GxItem<'_>>> {
+        let gx_list = self.gx_lists.get(material.gx_index?)?;
+        let (items, _) = if self.big_endian {
+            gx_items::<BE>(gx_list)?
+        } else {
+            gx_items::<LE>(gx_list)?
+        };
+        Some(items)
+    }
+
+    /// The indices of a face set as a triangle list, with triangle strips unrolled. Edge-compressed
+    /// face sets aren't supported.
+    pub fn face_set_triangles(&self, face_set: &FaceSet) -> Option<Vec<u32>> {
+        if face_set.is_edge_compressed() {
+            return None;
+        }
+
+        Some(if face_set.triangle_strip {
+            let index_size = match face_set.index_size {
+                0 => self.vertex_index_size as usize,
+                size => size as usize,
+            };
+            let restart = strip_restart_index(index_size);
+            unroll_triangle_strip(face_set.indices.iter().copied(), Some(restart))
+        } else {
+            face_set.indices.clone()
+        })
+    }
+}
+
+impl Mesh {
+    /// The face set for a level of detail, where 0 is the most detailed. Motion blur face sets
+    /// are skipped.
+    pub fn lod_face_set(&self, lod_level: u8) -> Option<&FaceSet> {
+        self.face_sets
+            .iter()
+            .find(|face_set| face_set.lod_level() == lod_level && !face_set.is_motion_blur())
+    }
+}
+
+impl<'a, O: ByteOrder + 'static> TryFrom<&FlverInner<'a, O>> for Flver {
+    type Error = io::Error;
+
+    fn try_from(flver: &FlverInner<'a, O>) -> Result<Self, Self::Error> {
+        let dummies = flver
+            .dummies
+            .iter()
+            .map(|dummy| Dummy {
+                position: dummy.position(),
+                color: dummy.color(),
+                forward: dummy.forward(),
+                up_vector: dummy.up_vector(),
+                reference_id: dummy.reference_id(),
+                parent_bone_index: dummy.parent_bone_index(),
+                attached_bone_index: dummy.attached_bone_index(),
+                flag_1: dummy.flag_1(),
+                use_up_vector: dummy.use_up_vector(),
+                unk30: dummy.unk30(),
+                unk34: dummy.unk34(),
+            })
+            .collect();
+
+        let mut gx_offsets = Vec::new();
+        let mut gx_lists = Vec::new();
+        let materials = flver
+            .materials
+            .iter()
+            .map(|material| {
+                let gx_offset = material.gx_offset.get();
+                let gx_index = match gx_offsets.iter().position(|offset| *offset == gx_offset) {
+                    _ if gx_offset == 0 => None,
+                    Some(index) => Some(index),
+                    None => {
+                        let gx_list = flver
+                            .material_gx_list(material)
+                            .ok_or_else(|| invalid("material GX list"))?;
+
+                        gx_offsets.push(gx_offset);
+                        gx_lists.push(gx_list.to_vec());
+                        Some(gx_lists.len() - 1)
+                    }
+                };
+
+                let textures = flver
+                    .material_textures(material)
+                    .ok_or_else(|| invalid("material texture range"))?
+                    .iter()
+                    .map(|texture| {
+                        Ok(Texture {
+                            path: flver
+                                .texture_path(texture)
+                                .ok_or_else(|| invalid("texture path"))?,
+                            r#type: flver
+                                .texture_type(texture)
+                                .ok_or_else(|| invalid("texture type"))?,
+                            scale: texture.scale(),
+                            unk10: texture.unk10(),
+                            unk11: texture.unk11(),
+                            unk14: texture.unk14(),
+                            unk18: texture.unk18(),
+                            unk1c: texture.unk1c(),
+                        })
+                    })
+                    .collect::<Result<_, io::Error>>()?;
+
+                Ok(Material {
+                    name: flver
+                        .material_name(material)
+                        .ok_or_else(|| invalid("material name"))?,
+                    mtd: flver
+                        .material_mtd(material)
+                        .ok_or_else(|| invalid("material MTD path"))?,
+                    flags: material.flags(),
+                    gx_index,
+                    unk18: material.unk18(),
+                    textures,
+                })
+            })
+            .collect::<Result<_, io::Error>>()?;
+
+        let bones = flver
+            .bones
+            .iter()
+            .map(|bone| {
+                Ok(Bone {
+                    name: flver.bone_name(bone).ok_or_else(|| invalid("bone name"))?,
+                    translation: bone.translation(),
+                    rotation: bone.rotation(),
+                    scale: bone.scale(),
+                    parent_index: bone.parent_index(),
+                    child_index: bone.child_index(),
+                    next_sibling_index: bone.next_sibling_index(),
+                    previous_sibling_index: bone.previous_sibling_index(),
+                    bounding_box_min: bone.bounding_box_min(),
+                    bounding_box_max: bone.bounding_box_max(),
+                    unk3c: bone.unk3c(),
+                })
+            })
+            .collect::<Result<_, io::Error>>()?;
+
+        let meshes = flver
+            .meshes
+            .iter()
+            .map(|mesh| {
+                let face_sets = flver
+                    .mesh_face_sets(mesh)
+                    .map(|face_set| {
+                        let indices = flver
+                            .face_set_data(face_set)
+                            .ok_or_else(|| invalid("face set indices"))?;
+
+                        Ok(FaceSet {
+                            flags: face_set.flags(),
+                            triangle_strip: face_set.is_triangle_strip(),
+                            cull_back_faces: face_set.cull_back_faces(),
+                            unk06: face_set.unk06(),
+                            index_size: face_set.index_size.get(),
+                            indices: indices.iter().collect(),
+                        })
+                    })
+                    .collect::<Result<_, io::Error>>()?;
+
+                let vertex_buffers = flver
+                    .mesh_buffers(mesh)
+                    .map(|buffer| {
+                        Ok(VertexBuffer {
+                            buffer_index: buffer.buffer_index.get(),
+                            layout_index: buffer.layout_index.get(),
+                            vertex_size: buffer.vertex_size.get(),
+                            vertex_count: buffer.vertex_count.get(),
+                            data: flver
+                                .vertex_buffer_data(buffer)
+                                .ok_or_else(|| invalid("vertex buffer data"))?
+                                .to_vec(),
+                        })
+                    })
+                    .collect::<Result<_, io::Error>>()?;
+
+                let bone_indices = flver
+                    .mesh_bone_indices(mesh)
+                    .ok_or_else(|| invalid("mesh bone indices"))?
+                    .iter()
+                    .map(|index| index.get())
+                    .collect();
+
+                Ok(Mesh {
+                    dynamic: mesh.is_dynamic(),
+                    material_index: mesh.material_index(),
+                    default_bone_index: mesh.default_bone_index(),
+                    bone_indices,
+                    bounding_box: flver.mesh_bounding_box(mesh),
+                    face_sets,
+                    vertex_buffers,
+                })
+            })
+            .collect::<Result<_, io::Error>>()?;
+
+        let buffer_layouts = flver
+            .vertex_buffer_layouts
+            .iter()
+            .map(|layout| VertexBufferLayout {
+                attributes: flver
+                    .vertex_attributes(layout)
+                    .iter()
+                    .map(|attribute| VertexBufferAttribute {
+                        unk0: attribute.unk0.get(),
+                        struct_offset: attribute.struct_offset.get(),
+                        format_id: attribute.format_id.get(),
+                        semantic: attribute.semantic(),
+                        index: attribute.index.get(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Ok(Self {
+            big_endian: is_big_endian::<O>(),
+            version: flver.version(),
+            bounding_box_min: flver.bounding_box_min(),
+            bounding_box_max: flver.bounding_box_max(),
+            face_count: flver.face_count(),
+            total_face_count: flver.total_face_count(),
+            vertex_index_size: flver.vertex_index_size(),
+            unicode: flver.is_unicode(),
+            unk4a: flver._unk4a,
+            unk4b: flver._unk4b,
+            unk4c: flver._unk4c.get(),
+            unk5c: flver._unk5c,
+            unk5d: flver._unk5d,
+            unk68: flver._unk68.get(),
+            dummies,
+            materials,
+            bones,
+            meshes,
+            buffer_layouts,
+            gx_lists,
+        })
+    }
+}
+
+/// Converts a FLVER0 to the layout of later FLVERs. Each mesh gets a single face set and vertex
+/// buffer, and the vertex buffer layouts of all materials are gathered in
+/// [`Flver::buffer_layouts`].
+impl<'a, O: ByteOrder + 'static> TryFrom<&Flver0Inner<'a, O>> for Flver {
+    type Error = io::Error;
+
+    fn try_from(flver: &Flver0Inner<'a, O>) -> Result<Self, Self::Error> {
+        let dummies = flver
+            .dummies
+            .iter()
+            .map(|dummy| Dummy {
+                position: dummy.position(),
+                color: dummy.color(),
+                forward: dummy.forward(),
+                up_vector: dummy.up_vector(),
+                reference_id: dummy.reference_id(),
+                parent_bone_index: dummy.parent_bone_index(),
+                attached_bone_index: dummy.attached_bone_index(),
+                flag_1: dummy.flag_1(),
+                use_up_vector: dummy.use_up_vector(),
+                unk30: dummy.unk30(),
+                unk34: dummy.unk34(),
+            })
+            .collect();
+
+        let mut buffer_layouts = Vec::new();
+        let mut material_layout_offsets = Vec::with_capacity(flver.materials.len());
+        let materials = flver
+            .materials
+            .iter()
+            .map(|material| {
+                material_layout_offsets.push(buffer_layouts.len());
+                let layouts = flver
+                    .material_layouts(material)
+                    .ok_or_else(|| invalid("material buffer layouts"))?;
+                buffer_layouts.extend(layouts.iter().map(|layout| {
+                    VertexBufferLayout {
+                        attributes: layout
+                            .attributes
+                            .iter()
+                            .map(|attribute| VertexBufferAttribute {
+                                unk0: attribute.unk0.get(),
+                                struct_offset: attribute.struct_offset.get(),
+                                format_id: attribute.format_id.get(),
+                                semantic: attribute.semantic(),
+                                index: attribute.index.get(),
+                            })
+                            .collect(),
+                    }
+                }));
+
+                let textures = flver
+                    .material_textures(material)
+                    .ok_or_else(|| invalid("material textures"))?
+                    .iter()
+                    .map(|texture| {
+                        Ok(Texture {
+                            path: flver
+                                .texture_path(texture)
+                                .ok_or_else(|| invalid("texture path"))?,
+                            r#type: flver.texture_type(texture).unwrap_or_default(),
+                            scale: [1.0, 1.0],
+                            unk10: 0,
+                            unk11: false,
+                            unk14: 0.0,
+                            unk18: 0.0,
+                            unk1c: 0.0,
+                        })
+                    })
+                    .collect::<Result<_, io::Error>>()?;
+
+                Ok(Material {
+                    name: flver
+                        .material_name(material)
+                        .ok_or_else(|| invalid("material name"))?,
+                    mtd: flver
+                        .material_mtd(material)
+                        .ok_or_else(|| invalid("material MTD path"))?,
+                    flags: 0,
+                    gx_index: None,
+                    unk18: 0,
+                    textures,
+                })
+            })
+            .collect::<Result<_, io::Error>>()?;
+
+        let bones = flver
+            .bones
+            .iter()
+            .map(|bone| {
+                Ok(Bone {
+                    name: flver.bone_name(bone).ok_or_else(|| invalid("bone name"))?,
+                    translation: bone.translation(),
+                    rotation: bone.rotation(),
+                    scale: bone.scale(),
+                    parent_index: bone.parent_index(),
+                    child_index: bone.child_index(),
+                    next_sibling_index: bone.next_sibling_index(),
+                    previous_sibling_index: bone.previous_sibling_index(),
+                    bounding_box_min: bone.bounding_box_min(),
+                    bounding_box_max: bone.bounding_box_max(),
+                    unk3c: bone.unk3c(),
+                })
+            })
+            .collect::<Result<_, io::Error>>()?;
+
+        let meshes = flver
+            .meshes
+            .iter()
+            .map(|mesh| {
+                let indices = flver
+                    .mesh_vertex_indices(mesh)
+                    .ok_or_else(|| invalid("mesh vertex indices"))?;
+                let (layout_index, data) = flver
+                    .mesh_vertex_buffer(mesh)
+                    .ok_or_else(|| invalid("mesh vertex buffer"))?;
+                let layout = flver
+                    .mesh_layout(mesh)
+                    .ok_or_else(|| invalid("mesh buffer layout"))?;
+                let layout_offset = material_layout_offsets
+                    .get(mesh.material_index())
+                    .ok_or_else(|| invalid("mesh material"))?;
+
+                Ok(Mesh {
+                    dynamic: mesh.is_dynamic(),
+                    material_index: mesh.material_index(),
+                    default_bone_index: mesh.default_bone_index(),
+                    bone_indices: mesh.bone_indices().map(|index| index as u32).collect(),
+                    bounding_box: None,
+                    face_sets: vec![FaceSet {
+                        flags: 0,
+                        triangle_strip: mesh.is_triangle_strip(),
+                        cull_back_faces: mesh.cull_back_faces(),
+                        unk06: 0,
+                        index_size: 0,
+                        indices: indices.iter().collect(),
+                    }],
+                    vertex_buffers: vec![VertexBuffer {
+                        buffer_index: 0,
+                        layout_index: (layout_offset + layout_index) as u32,
+                        vertex_size: layout.vertex_size as u32,
+                        vertex_count: mesh.vertex_count() as u32,
+                        data: data.to_vec(),
+                    }],
+                })
+            })
+            .collect::<Result<_, io::Error>>()?;
+
+        Ok(Self {
+            big_endian: is_big_endian::<O>(),
+            version: flver.version(),
+            bounding_box_min: flver.bounding_box_min(),
+            bounding_box_max: flver.bounding_box_max(),
+            face_count: flver.face_count(),
+            total_face_count: flver.total_face_count(),
+            vertex_index_size: flver.vertex_index_size(),
+            unicode: flver.is_unicode(),
+            unk4a: flver._unk4a,
+            unk4b: flver._unk4b,
+            unk4c: flver._unk4c.get(),
+            unk5c: flver._unk5c,
+            unk5d: flver._unk5d,
+            unk68: flver._unk68.get(),
+            dummies,
+            materials,
+            bones,
+            meshes,
+            buffer_layouts,
+            gx_lists: Vec::new(),
+        })
+    }
+}
+
+fn invalid(part: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("FLVER {part} is out of bounds or malformed"),
+    )
+}
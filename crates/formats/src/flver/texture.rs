@@ -4,6 +4,7 @@ use zerocopy::{FromBytes, FromZeroes, F32, U32};
 use crate::{flver::header::FlverHeaderPart, io_ext::zerocopy::Padding};
 
 #[derive(FromBytes, FromZeroes)]
+#[repr(C, packed)]
 #[allow(unused)]
 pub struct Texture<O: ByteOrder> {
     pub path_offset: U32<O>,
@@ -17,4 +18,30 @@ pub struct Texture<O: ByteOrder> {
     unk1c: F32<O>,
 }
 
+impl<O: ByteOrder> Texture<O> {
+    pub fn scale(&self) -> [f32; 2] {
+        [self.scale[0].get(), self.scale[1].get()]
+    }
+
+    pub fn unk10(&self) -> u8 {
+        self.unk10
+    }
+
+    pub fn unk11(&self) -> bool {
+        self.unk11 != 0
+    }
+
+    pub fn unk14(&self) -> f32 {
+        self.unk14.get()
+    }
+
+    pub fn unk18(&self) -> f32 {
+        self.unk18.get()
+    }
+
+    pub fn unk1c(&self) -> f32 {
+        self.unk1c.get()
+    }
+}
+
 impl<O: ByteOrder> FlverHeaderPart for Texture<O> {}
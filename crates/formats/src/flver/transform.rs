@@ -0,0 +1,243 @@
+//! Transform math for FLVER bones.
+//!
+//! Matrices are column-major 4x4 matrices, as used by glTF. Bone rotations are stored as euler
+//! angles in radians, applied around X, then Z, then Y.
+
+/// A column-major 4x4 matrix.
+pub type Mat4 = [f32; 16];
+
+pub const IDENTITY: Mat4 = [
+    1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+];
+
+/// Converts a bone rotation to an `[x, y, z, w]` quaternion.
+pub fn euler_to_quaternion([rx, ry, rz]: [f32; 3]) -> [f32; 4] {
+    let axis = |axis: usize, angle: f32| {
+        let mut quaternion = [0.0, 0.0, 0.0, (angle / 2.0).cos()];
+        quaternion[axis] = (angle / 2.0).sin();
+        quaternion
+    };
+
+    quaternion_mul(quaternion_mul(axis(1, ry), axis(2, rz)), axis(0, rx))
+}
+
+/// Converts an `[x, y, z, w]` quaternion to a bone rotation.
+pub fn quaternion_to_euler(quaternion: [f32; 4]) -> [f32; 3] {
+    let m = from_trs([0.0; 3], quaternion, [1.0; 3]);
+    let a = |row: usize, column: usize| m[column * 4 + row];
+
+    // The rotation matrix is Ry * Rz * Rx, whose element (1, 0) is sin(z)
+    let sin_z = a(1, 0).clamp(-1.0, 1.0);
+    if sin_z.abs() > 0.9999 {
+        return [0.0, a(0, 2).atan2(a(2, 2)), sin_z.asin()];
+    }
+
+    [
+        (-a(1, 2)).atan2(a(1, 1)),
+        (-a(2, 0)).atan2(a(0, 0)),
+        sin_z.asin(),
+    ]
+}
+
+fn quaternion_mul([ax, ay, az, aw]: [f32; 4], [bx, by, bz, bw]: [f32; 4]) -> [f32; 4] {
+    [
+        aw * bx + ax * bw + ay * bz - az * by,
+        aw * by - ax * bz + ay * bw + az * bx,
+        aw * bz + ax * by - ay * bx + az * bw,
+        aw * bw - ax * bx - ay * by - az * bz,
+    ]
+}
+
+/// Mirrors a rotation along the X axis, converting it between the left-handed coordinate
+/// system of FLVERs and a right-handed one.
+pub fn mirror_quaternion([x, y, z, w]: [f32; 4]) -> [f32; 4] {
+    [x, -y, -z, w]
+}
+
+/// Builds a matrix from a translation, an `[x, y, z, w]` rotation quaternion and a scale.
+pub fn from_trs([tx, ty, tz]: [f32; 3], [x, y, z, w]: [f32; 4], [sx, sy, sz]: [f32; 3]) -> Mat4 {
+    [
+        (1.0 - 2.0 * (y * y + z * z)) * sx,
+        (2.0 * (x * y + z * w)) * sx,
+        (2.0 * (x * z - y * w)) * sx,
+        0.0,
+        (2.0 * (x * y - z * w)) * sy,
+        (1.0 - 2.0 * (x * x + z * z)) * sy,
+        (2.0 * (y * z + x * w)) * sy,
+        0.0,
+        (2.0 * (x * z + y * w)) * sz,
+        (2.0 * (y * z - x * w)) * sz,
+        (1.0 - 2.0 * (x * x + y * y)) * sz,
+        0.0,
+        tx,
+        ty,
+        tz,
+        1.0,
+    ]
+}
+
+/// Splits a matrix without shear into a translation, an `[x, y, z, w]` rotation quaternion and
+/// a scale.
+pub fn decompose(m: &Mat4) -> ([f32; 3], [f32; 4], [f32; 3]) {
+    let column = |index: usize| [m[index * 4], m[index * 4 + 1], m[index * 4 + 2]];
+    let length = |[x, y, z]: [f32; 3]| (x * x + y * y + z * z).sqrt();
+    let scale = [length(column(0)), length(column(1)), length(column(2))];
+
+    let a = |row: usize, column: usize| {
+        let scale = scale[column];
+        if scale > f32::EPSILON {
+            m[column * 4 + row] / scale
+        } else {
+            0.0
+        }
+    };
+
+    let trace = a(0, 0) + a(1, 1) + a(2, 2);
+    let rotation = if trace > 0.0 {
+        let s = (trace + 1.0).sqrt() * 2.0;
+        [
+            (a(2, 1) - a(1, 2)) / s,
+            (a(0, 2) - a(2, 0)) / s,
+            (a(1, 0) - a(0, 1)) / s,
+            s / 4.0,
+        ]
+    } else if a(0, 0) > a(1, 1) && a(0, 0) > a(2, 2) {
+        let s = (1.0 + a(0, 0) - a(1, 1) - a(2, 2)).sqrt() * 2.0;
+        [
+            s / 4.0,
+            (a(0, 1) + a(1, 0)) / s,
+            (a(0, 2) + a(2, 0)) / s,
+            (a(2, 1) - a(1, 2)) / s,
+        ]
+    } else if a(1, 1) > a(2, 2) {
+        let s = (1.0 + a(1, 1) - a(0, 0) - a(2, 2)).sqrt() * 2.0;
+        [
+            (a(0, 1) + a(1, 0)) / s,
+            s / 4.0,
+            (a(1, 2) + a(2, 1)) / s,
+            (a(0, 2) - a(2, 0)) / s,
+        ]
+    } else {
+        let s = (1.0 + a(2, 2) - a(0, 0) - a(1, 1)).sqrt() * 2.0;
+        [
+            (a(0, 2) + a(2, 0)) / s,
+            (a(1, 2) + a(2, 1)) / s,
+            s / 4.0,
+            (a(1, 0) - a(0, 1)) / s,
+        ]
+    };
+
+    ([m[12], m[13], m[14]], rotation, scale)
+}
+
+pub fn mul(a: &Mat4, b: &Mat4) -> Mat4 {
+    std::array::from_fn(|index| {
+        let (column, row) = (index / 4, index % 4);
+        (0..4).map(|k| a[k * 4 + row] * b[column * 4 + k]).sum()
+    })
+}
+
+/// Composes the local transforms of a hierarchy with those of their ancestors, yielding model
+/// space transforms. Parents outside of the hierarchy are ignored.
+pub fn world_transforms(local: &[Mat4], parent: impl Fn(usize) -> Option<usize>) -> Vec<Mat4> {
+    (0..local.len())
+        .map(|index| {
+            let mut transform = local[index];
+            let mut current = index;
+
+            // Bound the walk by the node count in case the hierarchy contains a cycle
+            for _ in 0..local.len() {
+                match parent(current) {
+                    Some(parent) if parent < local.len() => {
+                        transform = mul(&local[parent], &transform);
+                        current = parent;
+                    }
+                    _ => break,
+                }
+            }
+
+            transform
+        })
+        .collect()
+}
+
+/// Inverts a matrix made of a translation, rotation and scale. Singular matrices yield the
+/// identity.
+pub fn affine_inverse(m: &Mat4) -> Mat4 {
+    let a = |row: usize, column: usize| m[column * 4 + row];
+    let cofactor = |row: usize, column: usize| {
+        let (r0, r1) = ((row + 1) % 3, (row + 2) % 3);
+        let (c0, c1) = ((column + 1) % 3, (column + 2) % 3);
+        a(r0, c0) * a(r1, c1) - a(r0, c1) * a(r1, c0)
+    };
+
+    let determinant: f32 = (0..3)
+        .map(|column| a(0, column) * cofactor(0, column))
+        .sum();
+    if determinant.abs() <= f32::EPSILON {
+        return IDENTITY;
+    }
+
+    let mut inverse = [0.0; 16];
+    for row in 0..3 {
+        for column in 0..3 {
+            inverse[column * 4 + row] = cofactor(column, row) / determinant;
+        }
+    }
+    for row in 0..3 {
+        inverse[12 + row] = -(0..3)
+            .map(|k| inverse[k * 4 + row] * m[12 + k])
+            .sum::<f32>();
+    }
+    inverse[15] = 1.0;
+
+    inverse
+}
+
+pub fn transform_point(m: &Mat4, [x, y, z]: [f32; 3]) -> [f32; 3] {
+    std::array::from_fn(|row| m[row] * x + m[4 + row] * y + m[8 + row] * z + m[12 + row])
+}
+
+/// Transforms a direction, ignoring the translation of the matrix.
+pub fn transform_vector(m: &Mat4, [x, y, z]: [f32; 3]) -> [f32; 3] {
+    std::array::from_fn(|row| m[row] * x + m[4 + row] * y + m[8 + row] * z)
+}
+
+pub fn normalize([x, y, z]: [f32; 3]) -> Option<[f32; 3]> {
+    let length = (x * x + y * y + z * z).sqrt();
+    (length > f32::EPSILON).then(|| [x / length, y / length, z / length])
+}
+
+#[cfg(test)]
+mod test {
+    use crate::flver::transform::{
+        affine_inverse, decompose, euler_to_quaternion, from_trs, mul, quaternion_to_euler,
+    };
+
+    #[test]
+    pub fn inverts_and_decomposes_transforms() {
+        let rotation = euler_to_quaternion([0.3, -1.2, 2.0]);
+        let transform = from_trs([1.0, 2.0, 3.0], rotation, [2.0, 0.5, 1.5]);
+
+        let identity = mul(&transform, &affine_inverse(&transform));
+        for (index, value) in identity.iter().enumerate() {
+            let expected = if index % 5 == 0 { 1.0 } else { 0.0 };
+            assert!((value - expected).abs() < 1e-5, "{identity:?}");
+        }
+
+        let (translation, decomposed, scale) = decompose(&transform);
+        let recomposed = from_trs(translation, decomposed, scale);
+        assert!(transform
+            .iter()
+            .zip(recomposed)
+            .all(|(a, b)| (a - b).abs() < 1e-5));
+
+        // Euler angles aren't unique, so compare the rotations they describe
+        let round_trip = euler_to_quaternion(quaternion_to_euler(rotation));
+        let dot: f32 = rotation.iter().zip(round_trip).map(|(a, b)| a * b).sum();
+        assert!(
+            (dot.abs() - 1.0).abs() < 1e-5,
+            "{rotation:?} {round_trip:?}"
+        );
+    }
+}
@@ -0,0 +1,599 @@
+//! Validation of the offsets, counts and indices of a FLVER.
+//!
+//! The accessors of [`FlverInner`] return `None` or skip parts that are out of bounds, so a
+//! malformed FLVER can look like one with missing geometry. [`FlverInner::validate`] checks every
+//! part up front and reports what's wrong with it, along with bounding boxes recomputed from the
+//! vertices of each mesh.
+
+use std::{
+    fmt::{Display, Formatter},
+    mem::size_of,
+};
+
+use byteorder::ByteOrder;
+use thiserror::Error;
+use zerocopy::{FromBytes, U32};
+
+use crate::flver::{
+    face_set::strip_restart_index,
+    mesh::{BoundingBox, Mesh},
+    vertex_buffer::{accessor::VertexAttributeAccessor, VertexAttributeSemantic, VertexBuffer},
+    FlverInner,
+};
+
+/// The error allowed between stored and recomputed bounding boxes on each axis.
+const BOUNDS_TOLERANCE: f32 = 1e-3;
+
+/// A part of a FLVER, identified by its index in the list of parts of its kind.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlverPart {
+    Model,
+    Dummy(usize),
+    Material(usize),
+    Bone(usize),
+    Mesh(usize),
+    FaceSet(usize),
+    VertexBuffer(usize),
+    VertexBufferLayout(usize),
+    Texture(usize),
+}
+
+impl Display for FlverPart {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Model => write!(f, "the model"),
+            Self::Dummy(index) => write!(f, "dummy {index}"),
+            Self::Material(index) => write!(f, "material {index}"),
+            Self::Bone(index) => write!(f, "bone {index}"),
+            Self::Mesh(index) => write!(f, "mesh {index}"),
+            Self::FaceSet(index) => write!(f, "face set {index}"),
+            Self::VertexBuffer(index) => write!(f, "vertex buffer {index}"),
+            Self::VertexBufferLayout(index) => write!(f, "vertex buffer layout {index}"),
+            Self::Texture(index) => write!(f, "texture {index}"),
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ValidationIssue {
+    #[error("{what} of {part} is out of bounds")]
+    OutOfBounds { part: FlverPart, what: &'static str },
+
+    #[error("{part} references {target} {index}, but there are only {count}")]
+    InvalidReference {
+        part: FlverPart,
+        target: &'static str,
+        index: usize,
+        count: usize,
+    },
+
+    #[error(
+        "{face_set} of mesh {mesh} references vertex {vertex}, but the mesh only has \
+         {vertex_count} vertices"
+    )]
+    VertexIndexOutOfRange {
+        mesh: usize,
+        face_set: FlverPart,
+        vertex: u32,
+        vertex_count: usize,
+    },
+
+    #[error("{buffer} holds {length} bytes, but its vertices take up {expected}")]
+    VertexBufferTooShort {
+        buffer: FlverPart,
+        length: usize,
+        expected: usize,
+    },
+
+    #[error("attribute {attribute} of {layout} extends past the end of the vertices of {buffer}")]
+    AttributeOutsideVertex {
+        layout: FlverPart,
+        attribute: usize,
+        buffer: FlverPart,
+    },
+
+    #[error("the bounding box of {part} doesn't contain its vertices")]
+    BoundingBoxMismatch { part: FlverPart },
+
+    #[error("{face_set} is edge-compressed, so its indices can't be checked")]
+    EdgeCompressed { face_set: FlverPart },
+}
+
+/// The result of [`FlverInner::validate`].
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+
+    /// Bounds of the vertex positions of each mesh, or `None` for meshes whose positions can't
+    /// be read.
+    pub mesh_bounding_boxes: Vec<Option<BoundingBox>>,
+
+    /// Bounds of the vertex positions of all meshes.
+    pub bounding_box: BoundingBox,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl<'a, O: ByteOrder + 'static> FlverInner<'a, O> {
+    /// Checks every offset and count of this FLVER against the length of its data, every
+    /// reference between parts and every vertex index against the vertex count of its mesh.
+    /// Bounding boxes are recomputed from vertex positions and compared to the stored ones.
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+
+        self.validate_dummies(&mut report.issues);
+        self.validate_materials(&mut report.issues);
+        self.validate_bones(&mut report.issues);
+        self.validate_layouts(&mut report.issues);
+
+        for (index, mesh) in self.meshes.iter().enumerate() {
+            let bounds = self.validate_mesh(index, mesh, &mut report.issues);
+            if let Some(bounds) = &bounds {
+                report.bounding_box.merge(bounds);
+            }
+            report.mesh_bounding_boxes.push(bounds);
+        }
+
+        let stored = BoundingBox {
+            min: self.bounding_box_min(),
+            max: self.bounding_box_max(),
+        };
+        if !stored.contains(&report.bounding_box, BOUNDS_TOLERANCE) {
+            report.issues.push(ValidationIssue::BoundingBoxMismatch {
+                part: FlverPart::Model,
+            });
+        }
+
+        report
+    }
+
+    fn validate_dummies(&self, issues: &mut Vec<ValidationIssue>) {
+        for (index, dummy) in self.dummies.iter().enumerate() {
+            let part = FlverPart::Dummy(index);
+            for bone in [dummy.parent_bone_index(), dummy.attached_bone_index()] {
+                check_reference(issues, part, "bone", bone, self.bones.len());
+            }
+        }
+    }
+
+    fn validate_materials(&self, issues: &mut Vec<ValidationIssue>) {
+        for (index, material) in self.materials.iter().enumerate() {
+            let part = FlverPart::Material(index);
+            if self.material_name(material).is_none() {
+                issues.push(ValidationIssue::OutOfBounds { part, what: "name" });
+            }
+            if self.material_mtd(material).is_none() {
+                issues.push(ValidationIssue::OutOfBounds {
+                    part,
+                    what: "MTD path",
+                });
+            }
+            if material.gx_offset.get() != 0 && self.material_gx_list(material).is_none() {
+                issues.push(ValidationIssue::OutOfBounds {
+                    part,
+                    what: "GX list",
+                });
+            }
+
+            let texture_end = material.texture_index() + material.texture_count();
+            if texture_end > self.textures.len() {
+                issues.push(ValidationIssue::InvalidReference {
+                    part,
+                    target: "texture",
+                    index: texture_end - 1,
+                    count: self.textures.len(),
+                });
+            }
+        }
+
+        for (index, texture) in self.textures.iter().enumerate() {
+            let part = FlverPart::Texture(index);
+            if self.texture_path(texture).is_none() {
+                issues.push(ValidationIssue::OutOfBounds { part, what: "path" });
+            }
+            if self.texture_type(texture).is_none() {
+                issues.push(ValidationIssue::OutOfBounds { part, what: "type" });
+            }
+        }
+    }
+
+    fn validate_bones(&self, issues: &mut Vec<ValidationIssue>) {
+        for (index, bone) in self.bones.iter().enumerate() {
+            let part = FlverPart::Bone(index);
+            if self.bone_name(bone).is_none() {
+                issues.push(ValidationIssue::OutOfBounds { part, what: "name" });
+            }
+
+            for linked in [
+                bone.parent_index(),
+                bone.child_index(),
+                bone.next_sibling_index(),
+                bone.previous_sibling_index(),
+            ] {
+                check_reference(issues, part, "bone", linked, self.bones.len());
+            }
+        }
+    }
+
+    fn validate_layouts(&self, issues: &mut Vec<ValidationIssue>) {
+        for (index, layout) in self.vertex_buffer_layouts.iter().enumerate() {
+            if self.vertex_attributes(layout).len() != layout.member_count() {
+                issues.push(ValidationIssue::OutOfBounds {
+                    part: FlverPart::VertexBufferLayout(index),
+                    what: "attributes",
+                });
+            }
+        }
+    }
+
+    /// Validates a mesh and the face sets and vertex buffers it references, returning the
+    /// bounds of its vertex positions.
+    fn validate_mesh(
+        &self,
+        mesh_index: usize,
+        mesh: &'a Mesh<O>,
+        issues: &mut Vec<ValidationIssue>,
+    ) -> Option<BoundingBox> {
+        let part = FlverPart::Mesh(mesh_index);
+        let bone_count = self.bones.len();
+
+        let material = Some(mesh.material_index());
+        check_reference(issues, part, "material", material, self.materials.len());
+        check_reference(issues, part, "bone", mesh.default_bone_index(), bone_count);
+
+        match self.mesh_bone_indices(mesh) {
+            Some(bones) => {
+                for bone in bones {
+                    let bone = Some(bone.get() as usize);
+                    check_reference(issues, part, "bone", bone, bone_count);
+                }
+            }
+            None => issues.push(ValidationIssue::OutOfBounds {
+                part,
+                what: "bone table",
+            }),
+        }
+
+        let buffer_indices = self.part_indices(
+            issues,
+            part,
+            "vertex buffer list",
+            mesh.vertex_buffer_offset.get(),
+            mesh.vertex_buffer_count(),
+        );
+        let face_set_indices = self.part_indices(
+            issues,
+            part,
+            "face set list",
+            mesh.face_set_offset.get(),
+            mesh.face_set_count(),
+        );
+
+        let mut vertex_count = None;
+        let mut bounds = None;
+        for index in buffer_indices {
+            let Some(buffer) = self.vertex_buffers.get(index) else {
+                check_reference(
+                    issues,
+                    part,
+                    "vertex buffer",
+                    Some(index),
+                    self.vertex_buffers.len(),
+                );
+                continue;
+            };
+
+            let count = buffer.vertex_count.get() as usize;
+            vertex_count = Some(vertex_count.map_or(count, |vertices: usize| vertices.min(count)));
+            if let Some(buffer_bounds) = self.validate_vertex_buffer(index, buffer, issues) {
+                bounds
+                    .get_or_insert_with(BoundingBox::default)
+                    .merge(&buffer_bounds);
+            }
+        }
+
+        let vertex_count = vertex_count.unwrap_or_default();
+        for index in face_set_indices {
+            let Some(face_set) = self.face_sets.get(index) else {
+                check_reference(issues, part, "face set", Some(index), self.face_sets.len());
+                continue;
+            };
+            let face_set_part = FlverPart::FaceSet(index);
+            if face_set.is_edge_compressed() {
+                issues.push(ValidationIssue::EdgeCompressed {
+                    face_set: face_set_part,
+                });
+                continue;
+            }
+
+            let Some(indices) = self.face_set_indices(face_set) else {
+                issues.push(ValidationIssue::OutOfBounds {
+                    part: face_set_part,
+                    what: "indices",
+                });
+                continue;
+            };
+
+            let restart = face_set
+                .is_triangle_strip()
+                .then(|| strip_restart_index(self.face_set_index_size(face_set)));
+            let out_of_range = indices
+                .iter()
+                .find(|vertex| Some(*vertex) != restart && *vertex as usize >= vertex_count);
+            if let Some(vertex) = out_of_range {
+                issues.push(ValidationIssue::VertexIndexOutOfRange {
+                    mesh: mesh_index,
+                    face_set: face_set_part,
+                    vertex,
+                    vertex_count,
+                });
+            }
+        }
+
+        match (self.mesh_bounding_box(mesh), &bounds) {
+            (Some(stored), Some(bounds)) => {
+                let stored = BoundingBox {
+                    min: stored.min,
+                    max: stored.max,
+                };
+                if !stored.contains(bounds, BOUNDS_TOLERANCE) {
+                    issues.push(ValidationIssue::BoundingBoxMismatch { part });
+                }
+            }
+            (None, _) if mesh.bounding_box_offset.get() != 0 => {
+                issues.push(ValidationIssue::OutOfBounds {
+                    part,
+                    what: "bounding box",
+                });
+            }
+            _ => {}
+        }
+
+        bounds
+    }
+
+    /// Validates a vertex buffer against its layout, returning the bounds of its vertex
+    /// positions if it has any.
+    fn validate_vertex_buffer(
+        &self,
+        index: usize,
+        buffer: &VertexBuffer<O>,
+        issues: &mut Vec<ValidationIssue>,
+    ) -> Option<BoundingBox> {
+        let part = FlverPart::VertexBuffer(index);
+        let Some(data) = self.vertex_buffer_data(buffer) else {
+            issues.push(ValidationIssue::OutOfBounds { part, what: "data" });
+            return None;
+        };
+
+        let vertex_size = buffer.vertex_size.get() as usize;
+        let expected = vertex_size * buffer.vertex_count.get() as usize;
+        if data.len() < expected {
+            issues.push(ValidationIssue::VertexBufferTooShort {
+                buffer: part,
+                length: data.len(),
+                expected,
+            });
+        }
+
+        let layout_index = buffer.layout_index.get() as usize;
+        let Some(layout) = self.vertex_buffer_layouts.get(layout_index) else {
+            let count = self.vertex_buffer_layouts.len();
+            check_reference(
+                issues,
+                part,
+                "vertex buffer layout",
+                Some(layout_index),
+                count,
+            );
+            return None;
+        };
+
+        let mut bounds = None;
+        for (attribute_index, attribute) in self.vertex_attributes(layout).iter().enumerate() {
+            let Some(format) = attribute.format() else {
+                continue;
+            };
+
+            if attribute.struct_offset.get() as usize + format.size() > vertex_size {
+                issues.push(ValidationIssue::AttributeOutsideVertex {
+                    layout: FlverPart::VertexBufferLayout(layout_index),
+                    attribute: attribute_index,
+                    buffer: part,
+                });
+                continue;
+            }
+
+            if attribute.semantic() != VertexAttributeSemantic::Position {
+                continue;
+            }
+
+            let mut positions = BoundingBox::default();
+            match self.vertex_attribute_accessor(buffer, attribute) {
+                Some(VertexAttributeAccessor::Float3(it)) => it.for_each(|p| positions.add(p)),
+                Some(VertexAttributeAccessor::Float4(it)) => {
+                    it.for_each(|[x, y, z, _]| positions.add([x, y, z]));
+                }
+                Some(VertexAttributeAccessor::SNorm16x4(it)) => {
+                    it.for_each(|[x, y, z, _]| positions.add([x, y, z]));
+                }
+                _ => continue,
+            }
+            bounds
+                .get_or_insert_with(BoundingBox::default)
+                .merge(&positions);
+        }
+
+        bounds
+    }
+
+    /// Reads a list of indices of parts, reporting it if it's out of bounds.
+    fn part_indices(
+        &self,
+        issues: &mut Vec<ValidationIssue>,
+        part: FlverPart,
+        what: &'static str,
+        offset: u32,
+        count: usize,
+    ) -> Vec<usize> {
+        let offset = offset as usize;
+        let indices = self
+            .bytes
+            .get(offset..offset + count * size_of::<U32<O>>())
+            .and_then(U32::<O>::slice_from);
+
+        match indices {
+            Some(indices) => indices.iter().map(|index| index.get() as usize).collect(),
+            None => {
+                issues.push(ValidationIssue::OutOfBounds { part, what });
+                Vec::new()
+            }
+        }
+    }
+}
+
+fn check_reference(
+    issues: &mut Vec<ValidationIssue>,
+    part: FlverPart,
+    target: &'static str,
+    index: Option<usize>,
+    count: usize,
+) {
+    if let Some(index) = index.filter(|index| *index >= count) {
+        issues.push(ValidationIssue::InvalidReference {
+            part,
+            target,
+            index,
+            count,
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::mem::offset_of;
+
+    use byteorder::LE;
+
+    use crate::flver::{
+        face_set::FACESET_FLAG_EDGECOMPRESSED,
+        mesh::Mesh,
+        owned::{self, VertexBuffer, VertexBufferAttribute, VertexBufferLayout},
+        validate::{FlverPart, ValidationIssue},
+        vertex_buffer::VertexAttributeSemantic,
+        writer::test::test_flver,
+        Flver,
+    };
+
+    #[test]
+    pub fn validates_flvers() {
+        let bytes = test_flver().to_bytes().expect("supported version");
+        let report = Flver::parse(&bytes).expect("valid FLVER").validate();
+        assert!(report.is_valid(), "{:?}", report.issues);
+        assert_eq!(report.mesh_bounding_boxes.len(), 2);
+
+        let mut source = test_flver();
+        source.meshes[1].face_sets[0].flags |= FACESET_FLAG_EDGECOMPRESSED;
+        let bytes = source.to_bytes().expect("supported version");
+        let flver = Flver::parse(&bytes).expect("valid FLVER");
+        assert!(flver.face_set_indices(&flver.face_sets[1]).is_none());
+        assert_eq!(flver.face_set_triangles(&flver.face_sets[1]), None);
+        let owned = owned::Flver::parse(&bytes).expect("valid FLVER");
+        assert_eq!(owned.meshes[1].face_sets[0].indices, [2, 1, 0]);
+        assert_eq!(
+            flver.validate().issues,
+            [ValidationIssue::EdgeCompressed {
+                face_set: FlverPart::FaceSet(1),
+            }]
+        );
+
+        let mut source = test_flver();
+        source.meshes[0].vertex_buffers[0].data = [0.5f32, 4.0, 0.0]
+            .iter()
+            .cycle()
+            .take(9)
+            .flat_map(|component| component.to_le_bytes())
+            .collect();
+        source.meshes[1].face_sets[0].indices = vec![2, 1, 5];
+        let mut bytes = source.to_bytes().expect("supported version");
+
+        // Point the face set list of the first mesh past the end of the file
+        let flver = Flver::parse(&bytes).expect("valid FLVER");
+        let mesh_offset = flver.meshes.as_ptr() as usize - bytes.as_ptr() as usize;
+        let list_offset = mesh_offset + offset_of!(Mesh<LE>, face_set_offset);
+        bytes[list_offset..list_offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        let flver = Flver::parse(&bytes).expect("valid FLVER");
+        assert_eq!(flver.mesh_face_sets(&flver.meshes[0]).count(), 0);
+
+        let report = flver.validate();
+        let bounds = report.mesh_bounding_boxes[0].as_ref().expect("positions");
+        assert_eq!(bounds.max, [0.5, 4.0, 0.0]);
+        assert_eq!(report.bounding_box.max[1], 4.0);
+        assert_eq!(
+            report.issues,
+            [
+                ValidationIssue::OutOfBounds {
+                    part: FlverPart::Mesh(0),
+                    what: "face set list",
+                },
+                ValidationIssue::BoundingBoxMismatch {
+                    part: FlverPart::Mesh(0),
+                },
+                ValidationIssue::VertexIndexOutOfRange {
+                    mesh: 1,
+                    face_set: FlverPart::FaceSet(1),
+                    vertex: 5,
+                    vertex_count: 3,
+                },
+                ValidationIssue::BoundingBoxMismatch {
+                    part: FlverPart::Model,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    pub fn merges_bounds_of_vertex_buffers() {
+        let mut source = test_flver();
+        source.meshes[0].vertex_buffers[0].data = [0.5f32, 0.5, 0.0]
+            .iter()
+            .cycle()
+            .take(9)
+            .flat_map(|component| component.to_le_bytes())
+            .collect();
+        source.buffer_layouts.push(VertexBufferLayout {
+            attributes: vec![VertexBufferAttribute {
+                unk0: 0,
+                struct_offset: 0,
+                format_id: 0x1A,
+                semantic: VertexAttributeSemantic::Position,
+                index: 0,
+            }],
+        });
+        source.meshes[0].vertex_buffers.push(VertexBuffer {
+            buffer_index: 1,
+            layout_index: 1,
+            vertex_size: 8,
+            vertex_count: 3,
+            data: [-32767i16, 0, 32767, 0]
+                .iter()
+                .cycle()
+                .take(12)
+                .flat_map(|component| component.to_le_bytes())
+                .collect(),
+        });
+
+        let bytes = source.to_bytes().expect("supported version");
+        let report = Flver::parse(&bytes).expect("valid FLVER").validate();
+        assert!(report.is_valid(), "{:?}", report.issues);
+
+        let bounds = report.mesh_bounding_boxes[0].as_ref().expect("positions");
+        assert_eq!(bounds.min, [-1.0, 0.0, 0.0]);
+        assert_eq!(bounds.max, [0.5, 0.5, 1.0]);
+    }
+}
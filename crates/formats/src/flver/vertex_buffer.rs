@@ -1,10 +1,7 @@
 use byteorder::ByteOrder;
 use zerocopy::{FromBytes, FromZeroes, U32};
 
-use crate::{
-    flver::{header::FlverHeaderPart, reader::VertexAttributeSemantic},
-    io_ext::zerocopy::Padding,
-};
+use crate::{flver::header::FlverHeaderPart, io_ext::zerocopy::Padding};
 
 pub mod accessor;
 mod normalization;
@@ -33,6 +30,12 @@ pub struct VertexBufferLayout<O: ByteOrder> {
     pub(crate) member_offset: U32<O>,
 }
 
+impl<O: ByteOrder> VertexBufferLayout<O> {
+    pub fn member_count(&self) -> usize {
+        self.member_count.get() as usize
+    }
+}
+
 impl<O: ByteOrder> FlverHeaderPart for VertexBufferLayout<O> {}
 
 #[derive(Debug, FromBytes, FromZeroes)]
@@ -47,59 +50,173 @@ pub struct VertexBufferAttribute<O: ByteOrder> {
 }
 
 impl<O: ByteOrder> VertexBufferAttribute<O> {
+    pub fn semantic(&self) -> VertexAttributeSemantic {
+        VertexAttributeSemantic::from(self.semantic_id.get())
+    }
+
+    /// The encoding of this attribute, or `None` for combinations of semantic and format that
+    /// aren't known, including edge compressed positions.
     #[allow(clippy::match_same_arms)]
     pub fn format(&self) -> Option<VertexFormat> {
         use VertexAttributeSemantic::*;
         use VertexFormat::*;
 
-        let format = match (
-            VertexAttributeSemantic::from(self.semantic_id.get()),
-            self.format_id.get(),
-        ) {
+        let format = match (self.semantic(), self.format_id.get()) {
             (Position | UV, 0x02) => Float32x3,
             (Position | UV, 0x03) => Float32x4,
+            (Position, 0x1A) => Snorm16x4,
             (UV, 0x01) => Float32x2,
             (UV, 0x10 | 0x11 | 0x12 | 0x13 | 0x15) => Sscale16x2,
             (UV, 0x16) => Sscale16x4,
-            (UV, 0x11A | 0x2E) => Sscale16x4,
-            (Normal, 0x04) => Float32x4,
-            (Normal, 0x10 | 0x11 | 0x13 | 0x2F) => Snorm8x4,
-            (Normal, 0x12) => Snorm8x4, // soulstruct says unorm clamped to 127
-            (Normal, 0x1A) => Snorm16x4,
-            (Normal, 0x2E) => Snorm16x4, // soulstruct says unorm clamped to 127,
+            (UV, 0x11A | 0x1A | 0x2E) => Sscale16x4,
+            (Normal, 0x02) => Float32x3,
+            (Normal | Tangent | Bitangent, 0x03) => Float32x4,
+            (Normal | Tangent | Bitangent, 0x10 | 0x11 | 0x13 | 0x2F) => Biased8x4,
+            (Normal, 0x12) => Biased8x4,
+            (Normal | Tangent | Bitangent, 0x1A) => Snorm16x4,
+            (Normal, 0x2E) => Biased16x4,
             (BoneWeights, 0x10) => Snorm8x4,
             (BoneWeights, 0x13) => Unorm8x4,
             (BoneWeights, 0x16 | 0x1A) => Snorm16x4,
-            (BoneIndices, 0x11 | 0x24) => Uint8x4,
-            (BoneIndices, 0x18) => Sint16x4,
-            (Tangent, 0x10 | 0x11 | 0x13 | 0x2F) => Snorm8x4,
+            (BoneIndices, 0x11 | 0x24 | 0x2F) => Uint8x4,
+            (BoneIndices, 0x18) => Uint16x4,
+            (VertexColor, 0x03) => Float32x4,
+            (VertexColor, 0x10 | 0x13) => Unorm8x4,
             _ => return None,
         };
 
         Some(format)
     }
+
+    /// The number of UV sets stored in this attribute. Some formats pack two UV sets into one
+    /// attribute, one after the other.
+    pub fn uv_count(&self) -> usize {
+        match (self.semantic(), self.format_id.get()) {
+            (VertexAttributeSemantic::UV, 0x03 | 0x16) => 2,
+            (VertexAttributeSemantic::UV, _) => 1,
+            _ => 0,
+        }
+    }
+}
+
+#[repr(u32)]
+#[derive(Debug, PartialEq, Eq)]
+// TODO: these come from soulsformats and probably have documented
+// names in dx12
+pub enum VertexAttributeFormat {
+    Float2 = 0x1,
+    Float3 = 0x2,
+    Float4 = 0x3,
+    Byte4A = 0x10,
+    Byte4B = 0x11,
+    Short2ToFloat2 = 0x12,
+
+    // int to float 127
+    Byte4C = 0x13,
+    UV = 0x15,
+
+    // int to float
+    UVPair = 0x16,
+    ShortBoneIndices = 0x18,
+    Short4ToFloat4A = 0x1A,
+    Short4ToFloat4B = 0x2E,
+    Byte4E = 0x2F,
+    EdgeCompressed = 0xF0,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum VertexAttributeSemantic {
+    Position,
+    BoneWeights,
+    BoneIndices,
+    Normal,
+    UV,
+    Tangent,
+    Bitangent,
+    VertexColor,
+    Unknown(u32),
+}
+
+impl From<u32> for VertexAttributeSemantic {
+    fn from(value: u32) -> Self {
+        match value {
+            0x0 => Self::Position,
+            0x1 => Self::BoneWeights,
+            0x2 => Self::BoneIndices,
+            0x3 => Self::Normal,
+            0x5 => Self::UV,
+            0x6 => Self::Tangent,
+            0x7 => Self::Bitangent,
+            0xA => Self::VertexColor,
+            _ => Self::Unknown(value),
+        }
+    }
+}
+
+impl From<VertexAttributeSemantic> for u32 {
+    fn from(value: VertexAttributeSemantic) -> Self {
+        match value {
+            VertexAttributeSemantic::Position => 0x0,
+            VertexAttributeSemantic::BoneWeights => 0x1,
+            VertexAttributeSemantic::BoneIndices => 0x2,
+            VertexAttributeSemantic::Normal => 0x3,
+            VertexAttributeSemantic::UV => 0x5,
+            VertexAttributeSemantic::Tangent => 0x6,
+            VertexAttributeSemantic::Bitangent => 0x7,
+            VertexAttributeSemantic::VertexColor => 0xA,
+            VertexAttributeSemantic::Unknown(value) => value,
+        }
+    }
 }
 
 pub enum VertexFormat {
     Float32x2,
     Float32x3,
     Float32x4,
+    /// Unsigned bytes divided by 255.
     Unorm8x4,
+    /// Signed bytes divided by 127.
     Snorm8x4,
+    /// Signed shorts divided by 32767.
     Snorm16x4,
+    /// Unsigned bytes with 127 subtracted, divided by 127.
+    Biased8x4,
+    /// Unsigned shorts with 32767 subtracted, divided by 32767.
+    Biased16x4,
     Uint8x4,
-    Sint16x4,
+    Uint16x4,
     Sscale16x2,
     Sscale16x4,
 }
 
+impl VertexFormat {
+    /// Size of an attribute of this format in bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Float32x3 => 12,
+            Self::Float32x4 => 16,
+            Self::Unorm8x4
+            | Self::Snorm8x4
+            | Self::Biased8x4
+            | Self::Uint8x4
+            | Self::Sscale16x2 => 4,
+            Self::Float32x2
+            | Self::Snorm16x4
+            | Self::Biased16x4
+            | Self::Uint16x4
+            | Self::Sscale16x4 => 8,
+        }
+    }
+}
+
 // UVs:
 //
 // 0x01: 2 floats
 // 0x02: 3 floats (only 2 components used)
 // 0x03: 2 floats, 2 floats
-// 0x10, 0x11, 0x12, 0x13, 0x15: 2 signed shorts -> float (cast to float, take UV factor into
-// account) 0x16: 4 signed shorts -> float (cast to float, take UV factor into account)
-// 0x1A, 0x2E: 4 signed shorts -> float (cast to float, take UV factor into account) (only 2
-// components used)
+// 0x10, 0x11, 0x12, 0x13, 0x15: 2 signed shorts divided by the UV factor
+// 0x16: 4 signed shorts divided by the UV factor, holding 2 UVs
+// 0x1A, 0x2E: 4 signed shorts divided by the UV factor (only 2 components used)
+//
+// The UV factor is 2048 since version 0x2000F and 1024 before it.
 impl<O: ByteOrder> FlverHeaderPart for VertexBufferAttribute<O> {}
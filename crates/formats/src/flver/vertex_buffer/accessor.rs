@@ -1,32 +1,68 @@
 use std::{array, marker::PhantomData, mem::size_of};
 
-use bytemuck::Pod;
+use byteorder::ByteOrder;
 
 use crate::flver::vertex_buffer::normalization::{
-    NoNormalization, SNorm, UNorm, VertexAttributeNormalization,
+    BiasedNorm, NoNormalization, SNorm, UNorm, VertexAttributeNormalization,
 };
 
-pub enum VertexAttributeAccessor<'a> {
-    Float2(VertexAttributeIter<'a, f32, 2>),
-    Float3(VertexAttributeIter<'a, f32, 3>),
-    Float4(VertexAttributeIter<'a, f32, 4>),
-    UNorm8x4(VertexAttributeIter<'a, u8, 4, UNorm<u8, 255>>),
-    UNorm4x4(VertexAttributeIter<'a, u8, 4, UNorm<u8, 127>>),
-    UNorm16x2(VertexAttributeIter<'a, u16, 2, UNorm<u16, 32767>>),
-    UNorm16x4(VertexAttributeIter<'a, u16, 4, UNorm<u16, 32767>>),
-    SNorm8x4(VertexAttributeIter<'a, u8, 4, SNorm<u8, 127>>),
-    SNorm16x4(VertexAttributeIter<'a, u16, 4, SNorm<u16, 32767>>),
-    SNorm16x2(VertexAttributeIter<'a, u16, 2, SNorm<u16, 32767>>),
-    UV(VertexAttributeIter<'a, f32, 2>),
-    // TODO: get the last 2 components of this
-    UVPair(VertexAttributeIter<'a, f32, 2>),
-    Short4ToFloat4A(VertexAttributeIter<'a, u16, 4>),
-    Short4ToFloat4B(VertexAttributeIter<'a, u16, 4>),
+pub enum VertexAttributeAccessor<'a, O: ByteOrder> {
+    Float2(VertexAttributeIter<'a, O, f32, 2>),
+    Float3(VertexAttributeIter<'a, O, f32, 3>),
+    Float4(VertexAttributeIter<'a, O, f32, 4>),
+    UNorm8x4(VertexAttributeIter<'a, O, u8, 4, UNorm<u8, 255>>),
+    SNorm8x4(VertexAttributeIter<'a, O, i8, 4, SNorm<i8, 127>>),
+    SNorm16x4(VertexAttributeIter<'a, O, i16, 4, SNorm<i16, 32767>>),
+    /// Unsigned bytes biased by 127, as used by most normals and tangents.
+    BiasedNorm8x4(VertexAttributeIter<'a, O, u8, 4, BiasedNorm<u8, 127>>),
+    BiasedNorm16x4(VertexAttributeIter<'a, O, u16, 4, BiasedNorm<u16, 32767>>),
+    /// Raw integers, such as bone indices.
+    UInt8x4(VertexAttributeIter<'a, O, u8, 4>),
+    UInt16x4(VertexAttributeIter<'a, O, u16, 4>),
+    UV(VertexAttributeScaledIter<'a, O, 2>),
+    /// Two UVs per vertex, as `[u0, v0, u1, v1]`.
+    UVPair(VertexAttributeScaledIter<'a, O, 4>),
+}
+
+/// A component of a vertex attribute, read in the byte order of the FLVER it's part of.
+pub trait VertexAttributeComponent: Copy {
+    fn read<O: ByteOrder>(bytes: &[u8]) -> Self;
+}
+
+impl VertexAttributeComponent for u8 {
+    fn read<O: ByteOrder>(bytes: &[u8]) -> Self {
+        bytes[0]
+    }
+}
+
+impl VertexAttributeComponent for i8 {
+    fn read<O: ByteOrder>(bytes: &[u8]) -> Self {
+        bytes[0] as i8
+    }
+}
+
+impl VertexAttributeComponent for u16 {
+    fn read<O: ByteOrder>(bytes: &[u8]) -> Self {
+        O::read_u16(bytes)
+    }
+}
+
+impl VertexAttributeComponent for i16 {
+    fn read<O: ByteOrder>(bytes: &[u8]) -> Self {
+        O::read_i16(bytes)
+    }
+}
+
+impl VertexAttributeComponent for f32 {
+    fn read<O: ByteOrder>(bytes: &[u8]) -> Self {
+        O::read_f32(bytes)
+    }
 }
 
 pub struct VertexAttributeIter<
     'a,
-    T: Pod,
+    O: ByteOrder,
+    T: VertexAttributeComponent,
     const L: usize,
     N: VertexAttributeNormalization = NoNormalization<T>,
 > {
@@ -34,17 +70,24 @@ pub struct VertexAttributeIter<
     attribute_data_offset: usize,
     attribute_data_end: usize,
     vertex_size: usize,
+    _order: PhantomData<O>,
     _value: PhantomData<T>,
     _normalization: PhantomData<N>,
 }
 
-// TODO: this doesn't support endian sensitive reading like the rest of the FLVER parser.
-impl<'a, T: Pod, const L: usize, N: VertexAttributeNormalization> VertexAttributeIter<'a, T, L, N> {
+impl<
+        'a,
+        O: ByteOrder,
+        T: VertexAttributeComponent,
+        const L: usize,
+        N: VertexAttributeNormalization,
+    > VertexAttributeIter<'a, O, T, L, N>
+{
     pub fn new(
         buffer: &'a [u8],
         vertex_size: usize,
         vertex_offset: usize,
-    ) -> VertexAttributeIter<'a, T, L, N> {
+    ) -> VertexAttributeIter<'a, O, T, L, N> {
         let attribute_data_offset = vertex_offset;
         let attribute_data_end = attribute_data_offset + size_of::<T>() * L;
 
@@ -53,12 +96,13 @@ impl<'a, T: Pod, const L: usize, N: VertexAttributeNormalization> VertexAttribut
             attribute_data_offset,
             attribute_data_end,
             vertex_size,
+            _order: PhantomData,
             _value: PhantomData,
             _normalization: PhantomData,
         }
     }
 
-    pub fn no_norm(self) -> VertexAttributeIter<'a, T, L, NoNormalization<T>> {
+    pub fn no_norm(self) -> VertexAttributeIter<'a, O, T, L, NoNormalization<T>> {
         let Self {
             buffer,
             vertex_size,
@@ -70,25 +114,35 @@ impl<'a, T: Pod, const L: usize, N: VertexAttributeNormalization> VertexAttribut
     }
 }
 
-impl<'a, T: Pod, const L: usize, N: VertexAttributeNormalization<Input = T>> ExactSizeIterator
-    for VertexAttributeIter<'a, T, L, N>
+impl<
+        'a,
+        O: ByteOrder,
+        T: VertexAttributeComponent,
+        const L: usize,
+        N: VertexAttributeNormalization<Input = T>,
+    > ExactSizeIterator for VertexAttributeIter<'a, O, T, L, N>
 {
 }
-impl<'a, T: Pod, const L: usize, N: VertexAttributeNormalization<Input = T>> Iterator
-    for VertexAttributeIter<'a, T, L, N>
+impl<
+        'a,
+        O: ByteOrder,
+        T: VertexAttributeComponent,
+        const L: usize,
+        N: VertexAttributeNormalization<Input = T>,
+    > Iterator for VertexAttributeIter<'a, O, T, L, N>
 {
     type Item = [N::Output; L];
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.buffer.is_empty() {
-            return None;
-        }
-
-        let attribute_byte_data = &self.buffer[self.attribute_data_offset..self.attribute_data_end];
-        let data: &[T] = bytemuck::cast_slice(attribute_byte_data);
-        let output: [N::Output; L] = array::from_fn(|index| N::normalize(&data[index]));
+        let attribute_byte_data = self
+            .buffer
+            .get(self.attribute_data_offset..self.attribute_data_end)?;
+        let output: [N::Output; L] = array::from_fn(|index| {
+            let component = &attribute_byte_data[index * size_of::<T>()..];
+            N::normalize(&T::read::<O>(component))
+        });
 
-        self.buffer = &self.buffer[self.vertex_size..];
+        self.buffer = self.buffer.get(self.vertex_size..).unwrap_or_default();
 
         Some(output)
     }
@@ -98,3 +152,90 @@ impl<'a, T: Pod, const L: usize, N: VertexAttributeNormalization<Input = T>> Ite
         (remaining, Some(remaining))
     }
 }
+
+/// Iterates over signed 16-bit vectors divided by a factor that's only known when parsing, such
+/// as the UV factor of a FLVER version.
+pub struct VertexAttributeScaledIter<'a, O: ByteOrder, const L: usize> {
+    inner: VertexAttributeIter<'a, O, i16, L>,
+    factor: f32,
+}
+
+impl<'a, O: ByteOrder, const L: usize> VertexAttributeScaledIter<'a, O, L> {
+    pub fn new(
+        buffer: &'a [u8],
+        vertex_size: usize,
+        vertex_offset: usize,
+        factor: f32,
+    ) -> VertexAttributeScaledIter<'a, O, L> {
+        Self {
+            inner: VertexAttributeIter::new(buffer, vertex_size, vertex_offset),
+            factor,
+        }
+    }
+}
+
+impl<'a, O: ByteOrder, const L: usize> ExactSizeIterator for VertexAttributeScaledIter<'a, O, L> {}
+impl<'a, O: ByteOrder, const L: usize> Iterator for VertexAttributeScaledIter<'a, O, L> {
+    type Item = [f32; L];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.inner.next()?;
+        Some(value.map(|component| component as f32 / self.factor))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use byteorder::{BE, LE};
+
+    use crate::flver::vertex_buffer::{
+        accessor::VertexAttributeIter,
+        normalization::{BiasedNorm, SNorm, UNorm},
+    };
+
+    #[test]
+    pub fn decodes_normalized_components() {
+        // Two 8 byte vertices with a 4 byte attribute after 4 bytes of padding
+        let bytes = [0, 0, 0, 0, 0, 127, 254, 255, 0, 0, 0, 0, 51, 102, 204, 255];
+
+        let unorm = VertexAttributeIter::<LE, u8, 4, UNorm<u8, 255>>::new(&bytes, 8, 4);
+        assert_eq!(
+            unorm.collect::<Vec<_>>(),
+            [
+                [0.0, 127.0 / 255.0, 254.0 / 255.0, 1.0],
+                [0.2, 0.4, 0.8, 1.0]
+            ]
+        );
+
+        let snorm = VertexAttributeIter::<LE, i8, 4, SNorm<i8, 127>>::new(&bytes, 8, 4);
+        assert_eq!(
+            snorm.collect::<Vec<_>>()[0],
+            [0.0, 1.0, -2.0 / 127.0, -1.0 / 127.0]
+        );
+
+        let biased = VertexAttributeIter::<LE, u8, 4, BiasedNorm<u8, 127>>::new(&bytes, 8, 4);
+        assert_eq!(biased.collect::<Vec<_>>()[0], [-1.0, 0.0, 1.0, 1.0]);
+
+        let shorts = [0x7F, 0xFF, 0x80, 0x01, 0x00, 0x00, 0xC0, 0x00];
+        let snorm = VertexAttributeIter::<BE, i16, 4, SNorm<i16, 32767>>::new(&shorts, 8, 0);
+        assert_eq!(
+            snorm.collect::<Vec<_>>(),
+            [[1.0, -1.0, 0.0, -16384.0 / 32767.0]]
+        );
+    }
+
+    #[test]
+    pub fn decodes_integer_bone_indices() {
+        let bytes = [3, 0, 255, 17];
+        let indices = VertexAttributeIter::<LE, u8, 4>::new(&bytes, 4, 0);
+        assert_eq!(indices.collect::<Vec<_>>(), [[3, 0, 255, 17]]);
+
+        let bytes = [0x2C, 0x01, 0x00, 0x00, 0x01, 0x00, 0xFF, 0xFF];
+        let indices = VertexAttributeIter::<LE, u16, 4>::new(&bytes, 8, 0);
+        assert_eq!(indices.collect::<Vec<_>>(), [[300, 0, 1, u16::MAX]]);
+    }
+}
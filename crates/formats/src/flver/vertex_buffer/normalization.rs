@@ -20,8 +20,8 @@ impl<T: Copy> VertexAttributeNormalization for NoNormalization<T> {
     }
 }
 
-/// Normalize an unsigned 4-bit value to a range of [0, 1] with 128 possible values.
-/// Normalize a signed value to a range of [0,1] with N possible values.
+/// Normalize a signed value to a range of [-1, 1], where N maps to 1. Both -N and the minimum
+/// value of the type map to -1.
 pub struct SNorm<T: Into<f32> + Copy, const N: usize> {
     _value: PhantomData<T>,
 }
@@ -31,11 +31,11 @@ impl<T: Into<f32> + Copy, const N: usize> VertexAttributeNormalization for SNorm
     type Output = f32;
 
     fn normalize(input: &Self::Input) -> Self::Output {
-        ((*input).into() - N as f32) / N as f32
+        ((*input).into() / N as f32).max(-1.0)
     }
 }
 
-/// Normalize an unsigned value to a range of [0,1] with N possible values.
+/// Normalize an unsigned value to a range of [0, 1], where N maps to 1.
 pub struct UNorm<T: Into<f32> + Copy, const N: usize> {
     _value: PhantomData<T>,
 }
@@ -48,3 +48,44 @@ impl<T: Into<f32> + Copy, const N: usize> VertexAttributeNormalization for UNorm
         (*input).into() / N as f32
     }
 }
+
+/// Normalize an unsigned value biased by N to a range of [-1, 1], so that N maps to 0. This is
+/// how FLVERs store most byte normals and tangents.
+pub struct BiasedNorm<T: Into<f32> + Copy, const N: usize> {
+    _value: PhantomData<T>,
+}
+
+impl<T: Into<f32> + Copy, const N: usize> VertexAttributeNormalization for BiasedNorm<T, N> {
+    type Input = T;
+    type Output = f32;
+
+    fn normalize(input: &Self::Input) -> Self::Output {
+        (((*input).into() - N as f32) / N as f32).min(1.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::flver::vertex_buffer::normalization::{
+        BiasedNorm, SNorm, UNorm, VertexAttributeNormalization,
+    };
+
+    #[test]
+    pub fn normalizes_known_encodings() {
+        assert_eq!(SNorm::<i8, 127>::normalize(&127), 1.0);
+        assert_eq!(SNorm::<i8, 127>::normalize(&-127), -1.0);
+        assert_eq!(SNorm::<i8, 127>::normalize(&-128), -1.0);
+        assert_eq!(SNorm::<i16, 32767>::normalize(&0), 0.0);
+        assert_eq!(SNorm::<i16, 32767>::normalize(&-32767), -1.0);
+
+        assert_eq!(UNorm::<u8, 255>::normalize(&255), 1.0);
+        assert_eq!(UNorm::<u8, 255>::normalize(&51), 0.2);
+        assert_eq!(UNorm::<u16, 65535>::normalize(&0), 0.0);
+
+        assert_eq!(BiasedNorm::<u8, 127>::normalize(&127), 0.0);
+        assert_eq!(BiasedNorm::<u8, 127>::normalize(&254), 1.0);
+        assert_eq!(BiasedNorm::<u8, 127>::normalize(&255), 1.0);
+        assert_eq!(BiasedNorm::<u8, 127>::normalize(&0), -1.0);
+        assert_eq!(BiasedNorm::<u16, 32767>::normalize(&65534), 1.0);
+    }
+}
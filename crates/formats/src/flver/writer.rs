@@ -0,0 +1,601 @@
+use std::{
+    io::{self, Write},
+    marker::PhantomData,
+};
+
+use byteorder::{ByteOrder, BE, LE};
+use encoding_rs::SHIFT_JIS;
+
+use crate::flver::{endianness_marker, owned::Flver, ALLOWED_VERSIONS};
+
+/// Serializes FLVERs in the layout used by `SoulsFormats`: the header parts in the order they're
+/// parsed in, followed by mesh bounding boxes, mesh index lists, GX lists, vertex attribute
+/// descriptions, strings and finally the data region holding face set indices and vertex
+/// buffers.
+struct FlverWriter<O: ByteOrder> {
+    out: Vec<u8>,
+    unicode: bool,
+    _order: PhantomData<O>,
+}
+
+impl<O: ByteOrder> FlverWriter<O> {
+    fn position(&self) -> u32 {
+        self.out.len() as u32
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.out.push(value);
+    }
+
+    fn bool(&mut self, value: bool) {
+        self.out.push(value as u8);
+    }
+
+    fn u16(&mut self, value: u16) {
+        let mut bytes = [0; 2];
+        O::write_u16(&mut bytes, value);
+        self.out.extend_from_slice(&bytes);
+    }
+
+    fn u32(&mut self, value: u32) {
+        let mut bytes = [0; 4];
+        O::write_u32(&mut bytes, value);
+        self.out.extend_from_slice(&bytes);
+    }
+
+    fn f32(&mut self, value: f32) {
+        let mut bytes = [0; 4];
+        O::write_f32(&mut bytes, value);
+        self.out.extend_from_slice(&bytes);
+    }
+
+    fn vec3(&mut self, value: [f32; 3]) {
+        value.into_iter().for_each(|component| self.f32(component));
+    }
+
+    fn bone_index(&mut self, index: Option<usize>) {
+        self.u16(index.map_or(u16::MAX, |index| index as u16));
+    }
+
+    fn zeroes(&mut self, count: usize) {
+        self.out.resize(self.out.len() + count, 0);
+    }
+
+    fn pad(&mut self, alignment: usize) {
+        self.zeroes((alignment - self.out.len() % alignment) % alignment);
+    }
+
+    /// Writes a placeholder for an offset and returns its position, to be filled in with
+    /// [`Self::fill`].
+    fn reserve(&mut self) -> usize {
+        self.u32(0);
+        self.out.len() - 4
+    }
+
+    fn fill(&mut self, reservation: usize, value: u32) {
+        O::write_u32(&mut self.out[reservation..reservation + 4], value);
+    }
+
+    fn string(&mut self, reservation: usize, value: &str) {
+        self.fill(reservation, self.position());
+        if self.unicode {
+            value.encode_utf16().for_each(|unit| self.u16(unit));
+            self.u16(0);
+        } else {
+            let (bytes, _, _) = SHIFT_JIS.encode(value);
+            self.out.extend_from_slice(&bytes);
+            self.u8(0);
+        }
+    }
+}
+
+struct MaterialReservations {
+    name: usize,
+    mtd: usize,
+    gx_offset: usize,
+}
+
+struct MeshReservations {
+    bounding_box: usize,
+    bones: usize,
+    face_sets: usize,
+    vertex_buffers: usize,
+}
+
+impl Flver {
+    /// Serializes this FLVER.
+    ///
+    /// Header fields such as the bounding box and face counts are written as they are stored
+    /// and not recomputed from the meshes.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, io::Error> {
+        if !ALLOWED_VERSIONS.contains(&self.version) {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "writing FLVER version 0x{:X} is not supported",
+                    self.version
+                ),
+            ));
+        }
+
+        Ok(if self.big_endian {
+            self.to_bytes_inner::<BE>()
+        } else {
+            self.to_bytes_inner::<LE>()
+        })
+    }
+
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<(), io::Error> {
+        writer.write_all(&self.to_bytes()?)
+    }
+
+    fn to_bytes_inner<O: ByteOrder>(&self) -> Vec<u8> {
+        let mut w = FlverWriter::<O> {
+            out: Vec::new(),
+            unicode: self.unicode,
+            _order: PhantomData,
+        };
+
+        let face_set_count: usize = self.meshes.iter().map(|mesh| mesh.face_sets.len()).sum();
+        let vertex_buffer_count: usize = self
+            .meshes
+            .iter()
+            .map(|mesh| mesh.vertex_buffers.len())
+            .sum();
+        let texture_count: usize = self
+            .materials
+            .iter()
+            .map(|material| material.textures.len())
+            .sum();
+
+        w.out.extend_from_slice(b"FLVER\0");
+        w.out.extend_from_slice(&endianness_marker::<O>());
+        w.u32(self.version);
+        let data_offset = w.reserve();
+        let data_length = w.reserve();
+        w.u32(self.dummies.len() as u32);
+        w.u32(self.materials.len() as u32);
+        w.u32(self.bones.len() as u32);
+        w.u32(self.meshes.len() as u32);
+        w.u32(vertex_buffer_count as u32);
+        w.vec3(self.bounding_box_min);
+        w.vec3(self.bounding_box_max);
+        w.u32(self.face_count);
+        w.u32(self.total_face_count);
+        w.u8(self.vertex_index_size);
+        w.bool(self.unicode);
+        w.u8(self.unk4a);
+        w.u8(self.unk4b);
+        w.u32(self.unk4c);
+        w.u32(face_set_count as u32);
+        w.u32(self.buffer_layouts.len() as u32);
+        w.u32(texture_count as u32);
+        w.u8(self.unk5c);
+        w.u8(self.unk5d);
+        w.zeroes(10);
+        w.u32(self.unk68);
+        w.zeroes(20);
+
+        for dummy in &self.dummies {
+            w.vec3(dummy.position);
+            w.out.extend_from_slice(&dummy.color);
+            w.vec3(dummy.forward);
+            w.u16(dummy.reference_id);
+            w.bone_index(dummy.parent_bone_index);
+            w.vec3(dummy.up_vector);
+            w.bone_index(dummy.attached_bone_index);
+            w.bool(dummy.flag_1);
+            w.bool(dummy.use_up_vector);
+            w.u32(dummy.unk30);
+            w.u32(dummy.unk34);
+            w.zeroes(8);
+        }
+
+        let mut texture_index = 0;
+        let mut material_reservations = Vec::with_capacity(self.materials.len());
+        for material in &self.materials {
+            let name = w.reserve();
+            let mtd = w.reserve();
+            w.u32(material.textures.len() as u32);
+            w.u32(texture_index);
+            w.u32(material.flags);
+            let gx_offset = w.reserve();
+            w.u32(material.unk18);
+            w.u32(0);
+
+            texture_index += material.textures.len() as u32;
+            material_reservations.push(MaterialReservations {
+                name,
+                mtd,
+                gx_offset,
+            });
+        }
+
+        let mut bone_names = Vec::with_capacity(self.bones.len());
+        for bone in &self.bones {
+            w.vec3(bone.translation);
+            bone_names.push(w.reserve());
+            w.vec3(bone.rotation);
+            w.bone_index(bone.parent_index);
+            w.bone_index(bone.child_index);
+            w.vec3(bone.scale);
+            w.bone_index(bone.next_sibling_index);
+            w.bone_index(bone.previous_sibling_index);
+            w.vec3(bone.bounding_box_min);
+            w.u32(bone.unk3c);
+            w.vec3(bone.bounding_box_max);
+            w.zeroes(0x34);
+        }
+
+        let mut mesh_reservations = Vec::with_capacity(self.meshes.len());
+        for mesh in &self.meshes {
+            w.bool(mesh.dynamic);
+            w.zeroes(3);
+            w.u32(mesh.material_index as u32);
+            w.zeroes(8);
+            w.u32(
+                mesh.default_bone_index
+                    .map_or(u32::MAX, |index| index as u32),
+            );
+            w.u32(mesh.bone_indices.len() as u32);
+            let bounding_box = w.reserve();
+            let bones = w.reserve();
+            w.u32(mesh.face_sets.len() as u32);
+            let face_sets = w.reserve();
+            w.u32(mesh.vertex_buffers.len() as u32);
+            let vertex_buffers = w.reserve();
+
+            mesh_reservations.push(MeshReservations {
+                bounding_box,
+                bones,
+                face_sets,
+                vertex_buffers,
+            });
+        }
+
+        let mut face_set_reservations = Vec::with_capacity(face_set_count);
+        for face_set in self.meshes.iter().flat_map(|mesh| &mesh.face_sets) {
+            let index_size = self.effective_index_size(face_set.index_size);
+
+            w.u32(face_set.flags);
+            w.bool(face_set.triangle_strip);
+            w.bool(face_set.cull_back_faces);
+            w.u16(face_set.unk06);
+            w.u32(face_set.indices.len() as u32);
+            face_set_reservations.push(w.reserve());
+            w.u32(face_set.indices.len() as u32 * index_size / 8);
+            w.u32(0);
+            w.u32(face_set.index_size);
+            w.u32(0);
+        }
+
+        let mut vertex_buffer_reservations = Vec::with_capacity(vertex_buffer_count);
+        for buffer in self.meshes.iter().flat_map(|mesh| &mesh.vertex_buffers) {
+            w.u32(buffer.buffer_index);
+            w.u32(buffer.layout_index);
+            w.u32(buffer.vertex_size);
+            w.u32(buffer.vertex_count);
+            w.zeroes(8);
+            w.u32(buffer.data.len() as u32);
+            vertex_buffer_reservations.push(w.reserve());
+        }
+
+        let mut layout_reservations = Vec::with_capacity(self.buffer_layouts.len());
+        for layout in &self.buffer_layouts {
+            w.u32(layout.attributes.len() as u32);
+            w.zeroes(8);
+            layout_reservations.push(w.reserve());
+        }
+
+        let mut texture_reservations = Vec::with_capacity(texture_count);
+        for texture in self
+            .materials
+            .iter()
+            .flat_map(|material| &material.textures)
+        {
+            let path = w.reserve();
+            let r#type = w.reserve();
+            w.f32(texture.scale[0]);
+            w.f32(texture.scale[1]);
+            w.u8(texture.unk10);
+            w.bool(texture.unk11);
+            w.zeroes(2);
+            w.f32(texture.unk14);
+            w.f32(texture.unk18);
+            w.f32(texture.unk1c);
+            texture_reservations.push((path, r#type));
+        }
+
+        w.pad(0x10);
+        for (mesh, reservations) in self.meshes.iter().zip(&mesh_reservations) {
+            if let Some(bounding_box) = &mesh.bounding_box {
+                w.fill(reservations.bounding_box, w.position());
+                w.vec3(bounding_box.min);
+                w.vec3(bounding_box.max);
+                if let Some(unk) = bounding_box.unk {
+                    w.vec3(unk);
+                }
+            }
+        }
+
+        // Meshes without bones point at the start of the bone index lists
+        w.pad(0x10);
+        let bone_indices_start = w.position();
+        for (mesh, reservations) in self.meshes.iter().zip(&mesh_reservations) {
+            if mesh.bone_indices.is_empty() {
+                w.fill(reservations.bones, bone_indices_start);
+            } else {
+                w.fill(reservations.bones, w.position());
+                mesh.bone_indices.iter().for_each(|index| w.u32(*index));
+            }
+        }
+
+        w.pad(0x10);
+        let mut face_set_index = 0;
+        for (mesh, reservations) in self.meshes.iter().zip(&mesh_reservations) {
+            w.fill(reservations.face_sets, w.position());
+            for _ in &mesh.face_sets {
+                w.u32(face_set_index);
+                face_set_index += 1;
+            }
+        }
+
+        w.pad(0x10);
+        let mut vertex_buffer_index = 0;
+        for (mesh, reservations) in self.meshes.iter().zip(&mesh_reservations) {
+            w.fill(reservations.vertex_buffers, w.position());
+            for _ in &mesh.vertex_buffers {
+                w.u32(vertex_buffer_index);
+                vertex_buffer_index += 1;
+            }
+        }
+
+        w.pad(0x10);
+        let mut gx_offsets = Vec::with_capacity(self.gx_lists.len());
+        for gx_list in &self.gx_lists {
+            gx_offsets.push(w.position());
+            w.out.extend_from_slice(gx_list);
+        }
+        for (material, reservations) in self.materials.iter().zip(&material_reservations) {
+            let gx_offset = material
+                .gx_index
+                .and_then(|index| gx_offsets.get(index).copied())
+                .unwrap_or_default();
+
+            w.fill(reservations.gx_offset, gx_offset);
+        }
+
+        w.pad(0x10);
+        for (layout, reservation) in self.buffer_layouts.iter().zip(layout_reservations) {
+            w.fill(reservation, w.position());
+            for attribute in &layout.attributes {
+                w.u32(attribute.unk0);
+                w.u32(attribute.struct_offset);
+                w.u32(attribute.format_id);
+                w.u32(attribute.semantic.into());
+                w.u32(attribute.index);
+            }
+        }
+
+        w.pad(0x10);
+        for (material, reservations) in self.materials.iter().zip(&material_reservations) {
+            w.string(reservations.name, &material.name);
+            w.string(reservations.mtd, &material.mtd);
+        }
+        let textures = self
+            .materials
+            .iter()
+            .flat_map(|material| &material.textures);
+        for (texture, (path, r#type)) in textures.zip(texture_reservations) {
+            w.string(path, &texture.path);
+            w.string(r#type, &texture.r#type);
+        }
+        for (bone, reservation) in self.bones.iter().zip(bone_names) {
+            w.string(reservation, &bone.name);
+        }
+
+        w.pad(0x10);
+        let data_start = w.position();
+        w.fill(data_offset, data_start);
+
+        let mut face_set_reservations = face_set_reservations.into_iter();
+        let mut vertex_buffer_reservations = vertex_buffer_reservations.into_iter();
+        for mesh in &self.meshes {
+            for (face_set, reservation) in mesh.face_sets.iter().zip(&mut face_set_reservations) {
+                w.pad(0x10);
+                w.fill(reservation, w.position() - data_start);
+                match self.effective_index_size(face_set.index_size) {
+                    8 => face_set.indices.iter().for_each(|i| w.u8(*i as u8)),
+                    16 => face_set.indices.iter().for_each(|i| w.u16(*i as u16)),
+                    _ => face_set.indices.iter().for_each(|i| w.u32(*i)),
+                }
+            }
+
+            for (buffer, reservation) in mesh
+                .vertex_buffers
+                .iter()
+                .zip(&mut vertex_buffer_reservations)
+            {
+                w.pad(0x10);
+                w.fill(reservation, w.position() - data_start);
+                w.out.extend_from_slice(&buffer.data);
+            }
+        }
+
+        w.pad(0x10);
+        w.fill(data_length, w.position() - data_start);
+
+        w.out
+    }
+
+    /// The size in bits of the indices of a face set with the given index size field.
+    fn effective_index_size(&self, index_size: u32) -> u32 {
+        match index_size {
+            0 => self.vertex_index_size as u32,
+            size => size,
+        }
+    }
+}
+
+#[cfg(test)]
+pub(super) mod test {
+    use crate::flver::{
+        mesh::MeshBoundingBox,
+        owned::{
+            Bone, Dummy, FaceSet, Flver, Material, Mesh, Texture, VertexBuffer,
+            VertexBufferAttribute, VertexBufferLayout,
+        },
+        vertex_buffer::VertexAttributeSemantic,
+    };
+
+    pub fn test_flver() -> Flver {
+        let texture = |path: &str, r#type: &str| Texture {
+            path: path.to_string(),
+            r#type: r#type.to_string(),
+            scale: [1.0, 1.0],
+            unk10: 1,
+            unk11: true,
+            unk14: 0.0,
+            unk18: 0.0,
+            unk1c: 0.0,
+        };
+        let bone = |name: &str, parent_index, child_index| Bone {
+            name: name.to_string(),
+            translation: [0.0, 1.0, 0.0],
+            rotation: [0.0, 0.5, 0.0],
+            scale: [1.0; 3],
+            parent_index,
+            child_index,
+            next_sibling_index: None,
+            previous_sibling_index: None,
+            bounding_box_min: [-1.0; 3],
+            bounding_box_max: [1.0; 3],
+            unk3c: 0,
+        };
+        let mesh = |material_index, index_size, indices: Vec<u32>| Mesh {
+            dynamic: index_size == 0,
+            material_index,
+            default_bone_index: Some(0),
+            bone_indices: if index_size == 0 { vec![0, 1] } else { vec![] },
+            bounding_box: Some(MeshBoundingBox {
+                min: [-1.0; 3],
+                max: [1.0; 3],
+                unk: Some([0.0; 3]),
+            }),
+            face_sets: vec![FaceSet {
+                flags: 0,
+                triangle_strip: false,
+                cull_back_faces: true,
+                unk06: 0,
+                index_size,
+                indices,
+            }],
+            vertex_buffers: vec![VertexBuffer {
+                buffer_index: 0,
+                layout_index: 0,
+                vertex_size: 12,
+                vertex_count: 3,
+                data: (0..36).collect(),
+            }],
+        };
+
+        // A GX list with one item followed by the terminator
+        let mut gx_list = Vec::new();
+        for value in [
+            u32::from_le_bytes(*b"GXMD"),
+            100,
+            16,
+            7,
+            i32::MAX as u32,
+            100,
+            12,
+        ] {
+            gx_list.extend_from_slice(&value.to_le_bytes());
+        }
+
+        Flver {
+            big_endian: false,
+            version: 0x2001A,
+            bounding_box_min: [-1.0; 3],
+            bounding_box_max: [1.0; 3],
+            face_count: 2,
+            total_face_count: 2,
+            vertex_index_size: 16,
+            unicode: true,
+            unk4a: 0,
+            unk4b: 0,
+            unk4c: 0,
+            unk5c: 0,
+            unk5d: 0,
+            unk68: 4,
+            dummies: vec![Dummy {
+                position: [0.0, 2.0, 0.0],
+                color: [255, 0, 0, 255],
+                forward: [0.0, 0.0, 1.0],
+                up_vector: [0.0, 1.0, 0.0],
+                reference_id: 100,
+                parent_bone_index: Some(0),
+                attached_bone_index: Some(1),
+                flag_1: true,
+                use_up_vector: false,
+                unk30: 0,
+                unk34: 0,
+            }],
+            materials: vec![
+                Material {
+                    name: "body".to_string(),
+                    mtd: "N:\\mtd\\body.matxml".to_string(),
+                    flags: 0,
+                    gx_index: Some(0),
+                    unk18: 1,
+                    textures: vec![
+                        texture("body_a.tif", "g_DiffuseTexture"),
+                        texture("body_n.tif", "g_BumpmapTexture"),
+                    ],
+                },
+                Material {
+                    name: "eyes".to_string(),
+                    mtd: "N:\\mtd\\eyes.matxml".to_string(),
+                    flags: 0,
+                    gx_index: Some(0),
+                    unk18: 2,
+                    textures: vec![texture("eyes_a.tif", "g_DiffuseTexture")],
+                },
+            ],
+            bones: vec![bone("root", None, Some(1)), bone("spine", Some(0), None)],
+            meshes: vec![mesh(0, 0, vec![0, 1, 2]), mesh(1, 32, vec![2, 1, 0])],
+            buffer_layouts: vec![VertexBufferLayout {
+                attributes: vec![VertexBufferAttribute {
+                    unk0: 0,
+                    struct_offset: 0,
+                    format_id: 0x2,
+                    semantic: VertexAttributeSemantic::Position,
+                    index: 0,
+                }],
+            }],
+            gx_lists: vec![gx_list],
+        }
+    }
+
+    #[test]
+    pub fn round_trips_owned_flver() {
+        let bytes = test_flver().to_bytes().expect("supported version");
+
+        let parsed = Flver::parse(&bytes).expect("valid FLVER");
+        assert_eq!(parsed.bones[1].name, "spine");
+        assert_eq!(parsed.bones[1].parent_index, Some(0));
+        assert_eq!(parsed.materials[1].textures[0].path, "eyes_a.tif");
+        assert_eq!(parsed.materials[1].gx_index, Some(0));
+        assert_eq!(parsed.meshes[0].bone_indices, [0, 1]);
+        assert_eq!(parsed.meshes[1].face_sets[0].indices, [2, 1, 0]);
+        assert_eq!(parsed.meshes[1].vertex_buffers[0].data.len(), 36);
+        assert_eq!(parsed.dummies[0].attached_bone_index, Some(1));
+
+        assert_eq!(parsed.to_bytes().expect("supported version"), bytes);
+
+        // Versions newer than the writable ones are rejected when parsing, not only when writing
+        let mut newer = bytes.clone();
+        newer[8..12].copy_from_slice(&0x20022u32.to_le_bytes());
+        assert!(Flver::parse(&newer).is_err());
+    }
+}
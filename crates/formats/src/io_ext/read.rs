@@ -1,11 +1,13 @@
 use std::io::{ErrorKind, Read};
 
 use byteorder::{ByteOrder, ReadBytesExt};
+use encoding_rs::SHIFT_JIS;
 
 pub trait ReadFormatsExt {
     fn read_bool(&mut self) -> std::io::Result<bool>;
     fn read_magic<const LENGTH: usize>(&mut self, expected: &[u8; LENGTH]) -> std::io::Result<()>;
     fn read_utf16<BO: ByteOrder>(&mut self) -> std::io::Result<String>;
+    fn read_shift_jis(&mut self) -> std::io::Result<String>;
 
     fn read_padding(&mut self, length: usize) -> std::io::Result<()>;
 }
@@ -52,6 +54,19 @@ impl<R: Read> ReadFormatsExt for R {
             .map_err(|e| std::io::Error::new(ErrorKind::InvalidInput, e.to_string()))
     }
 
+    fn read_shift_jis(&mut self) -> std::io::Result<String> {
+        let mut bytes = Vec::new();
+        loop {
+            match self.read_u8()? {
+                0 => break,
+                byte => bytes.push(byte),
+            }
+        }
+
+        let (string, _) = SHIFT_JIS.decode_without_bom_handling(&bytes);
+        Ok(string.into_owned())
+    }
+
     #[cfg(not(feature = "strict-padding"))]
     fn read_padding(&mut self, length: usize) -> std::io::Result<()> {
         let mut taken = self.take(length as u64);
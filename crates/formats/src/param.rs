@@ -1,9 +1,23 @@
 use core::str;
-use std::{borrow::Cow, ffi::CStr, marker::PhantomData};
+use std::{borrow::Cow, collections::HashMap, ffi::CStr, marker::PhantomData, sync::OnceLock};
 
 use thiserror::Error;
 use zerocopy::{ByteOrder, FromBytes, FromZeroes, Unaligned, BE, LE, U16, U32, U64};
 
+mod builder;
+pub mod diff;
+pub mod paramdef;
+mod text;
+mod typed_row;
+mod validate;
+
+pub use builder::{ParamBuilder, ParamRow};
+pub use text::ParamTextError;
+pub use typed_row::{write_field, FieldValue, TypedRow};
+pub use validate::ParamValidationReport;
+
+use self::paramdef::ParamDef;
+
 /// Traits used to represent the varying endiannes, offset size and string encoding
 /// used it param files at compile time.
 pub mod traits {
@@ -179,7 +193,7 @@ impl<T: traits::ParamFileLayout> RowDescriptor<T> {
     pub fn data<'a>(&self, file: &'a Param<'a, T>) -> Option<&'a [u8]> {
         let offset: usize = self.data_offset.into() as usize;
         match file.detected_row_size {
-            Some(size) => file.data.get(offset..offset + size as usize),
+            Some(size) => file.data.get(offset..offset.checked_add(size as usize)?),
             None => file.data.get(offset..),
         }
     }
@@ -262,6 +276,31 @@ impl<BO: ByteOrder> ParamHeader<BO> {
     pub fn has_flag_2d_01(&self) -> bool {
         (self.format_flags_2d & 1) != 0
     }
+
+    /// Unknown 16-bit value stored at offset 0x06 of the header.
+    pub fn unk006(&self) -> u16 {
+        self.unk006.get()
+    }
+
+    /// Version of the paramdef the rows of this param file conform to.
+    pub fn paramdef_data_version(&self) -> u16 {
+        self.paramdef_data_version.get()
+    }
+
+    /// Raw format flags stored at offset 0x2D of the header.
+    pub fn format_flags_2d(&self) -> u8 {
+        self.format_flags_2d
+    }
+
+    /// Raw format flags stored at offset 0x2E of the header.
+    pub fn format_flags_2e(&self) -> u8 {
+        self.format_flags_2e
+    }
+
+    /// Format version of the paramdef the rows of this param file conform to.
+    pub fn paramdef_format_version(&self) -> u8 {
+        self.paramdef_format_version
+    }
 }
 
 pub struct Param<'a, T: traits::ParamFileLayout = ParamFileLayout> {
@@ -271,6 +310,8 @@ pub struct Param<'a, T: traits::ParamFileLayout = ParamFileLayout> {
     row_descriptors: &'a [RowDescriptor<T>],
     detected_strings_offset: Option<u64>,
     detected_row_size: Option<u64>,
+    /// Row indices by ID, built on first lookup if the row descriptors are not sorted.
+    id_lookup: OnceLock<Option<HashMap<u32, usize>>>,
     phantom: PhantomData<fn() -> T>,
 }
 
@@ -302,7 +343,7 @@ impl<'a, T: traits::ParamFileLayout> Param<'a, T> {
     /// finding the null-terminator to certain strings makes the worst-case linear in the size
     /// of the data.
     pub fn parse(data: &'a [u8]) -> Result<Self, ParamParseError> {
-        let header = ParamHeader::ref_from(data).ok_or(ParamParseError::InvalidData)?;
+        let header = ParamHeader::ref_from_prefix(data).ok_or(ParamParseError::InvalidData)?;
 
         // Check if this param file is compatible with our traits
         if T::is_64_bit() != header.is_64_bit()
@@ -321,15 +362,6 @@ impl<'a, T: traits::ParamFileLayout> Param<'a, T> {
 
         // Parse the param type string (expecting ASCII, so failing if not utf8 is fine)
         let param_type = if header.is_long_param_type() {
-            // SAFETY: union access is always safe for FromBytes
-            let inlined = unsafe { &header.param_type_block.param_type_buf };
-            let inlined_nt = &inlined[..inlined
-                .iter()
-                .position(|b| *b == 0)
-                .unwrap_or(inlined.len())];
-
-            str::from_utf8(inlined_nt).map_err(|_| ParamParseError::InvalidData)?
-        } else {
             // SAFETY: union access is always safe for FromBytes
             let offset: u64 = unsafe { header.param_type_block.offset.param_type_offset }.into();
             let strings = data
@@ -343,6 +375,18 @@ impl<'a, T: traits::ParamFileLayout> Param<'a, T> {
                 .ok()
                 .and_then(|cstr| cstr.to_str().ok())
                 .ok_or(ParamParseError::InvalidData)?
+        } else {
+            // SAFETY: union access is always safe for FromBytes
+            let inlined = unsafe { &header.param_type_block.param_type_buf };
+            let inlined_nt = &inlined[..inlined
+                .iter()
+                .position(|b| *b == 0)
+                .unwrap_or(inlined.len())];
+
+            // Some files pad the inline param type with spaces instead of nulls
+            str::from_utf8(inlined_nt)
+                .map_err(|_| ParamParseError::InvalidData)?
+                .trim_end_matches(' ')
         };
 
         // Parse the row descriptors
@@ -390,6 +434,7 @@ impl<'a, T: traits::ParamFileLayout> Param<'a, T> {
             row_descriptors,
             detected_row_size,
             detected_strings_offset,
+            id_lookup: OnceLock::new(),
             phantom: PhantomData,
         })
     }
@@ -436,21 +481,16 @@ pub trait ParamCommon<'a> {
     /// Returns the row size of this param, if known.
     fn row_size(&self) -> Option<usize>;
 
-    /// Checks that the row descriptors of this param are sorted.
-    /// If they are not, the following functions will not produce correct results:
-    /// - [`ParamCommon::index_of`]
-    /// - [`ParamCommon::data_by_id`]
-    /// - [`ParamCommon::name_by_id`]
+    /// Checks that the row descriptors of this param are sorted by strictly increasing ID.
+    /// If they are not, lookups by ID fall back to a hash map built on first use.
     fn are_rows_sorted(&self) -> bool;
 
     /// Attempts to find the index of a row given its ID.
     ///
-    /// # Constaints
-    /// This performs binary search on the row descriptors. As such, if they are not
-    /// sorted this will almost certainly lead to bogus results.
-    ///
-    /// Note that parsing a param file does *NOT* validate that row descriptors are sorted.
-    /// You can check this using [`ParamCommon::are_rows_sorted`].
+    /// # Complexity
+    /// If the row descriptors are sorted, this performs binary search on them. Otherwise, the
+    /// first lookup builds a hash map of row indices by ID in linear time, and rows sharing an
+    /// ID resolve to the first of them.
     fn index_of(&self, row_id: u32) -> Option<usize>;
 
     /// Returns the data for a param row given its index.
@@ -473,34 +513,45 @@ pub trait ParamCommon<'a> {
     /// [`ParamCommon::row_count`].
     fn name_by_index(&self, index: usize) -> Option<Cow<'_, str>>;
 
-    /// Returns the data of a row given its ID.
-    ///
-    /// # Constaints
-    /// This function relies on binary search on the row descriptors. As such, if they are not
-    /// sorted this will almost certainly lead to bogus results.
-    ///
-    /// Note that parsing a param file does *NOT* validate that row descriptors are sorted.
-    /// You can check this using [`ParamCommon::are_rows_sorted`].
+    /// Returns the data of a row given its ID. See [`ParamCommon::index_of`].
     fn data_by_id(&self, id: u32) -> Option<&[u8]> {
         self.data_by_index(self.index_of(id)?)
     }
 
-    /// Returns the name of a row given its ID.
-    ///
-    /// # Constaints
-    /// This function relies on binary search on the row descriptors. As such, if they are not
-    /// sorted this will almost certainly lead to bogus results.
-    ///
-    /// Note that parsing a param file does *NOT* validate that row descriptors are sorted.
-    /// You can check this using [`ParamCommon::are_rows_sorted`].
+    /// Returns the name of a row given its ID. See [`ParamCommon::index_of`].
     fn name_by_id(&self, id: u32) -> Option<Cow<'_, str>> {
         self.name_by_index(self.index_of(id)?)
     }
 
+    /// Returns a [`TypedRow`] view over the data of a row given its ID, which reads the fields
+    /// described by `paramdef`.
+    ///
+    /// Returns [`None`] if the row is not found or its data is smaller than the row size of the
+    /// paramdef.
+    fn typed_row_by_id<'s>(&'s self, paramdef: &'s ParamDef, id: u32) -> Option<TypedRow<'s>> {
+        TypedRow::new(paramdef, self.data_by_id(id)?, self.is_big_endian())
+    }
+
     /// Returns a boxed Iterator impl yielding structured information about each param row.
     ///
     /// If working with a concrete type, prefer [`Param::row_descriptors`] to this.
     fn dyn_rows(&self) -> Box<dyn Iterator<Item = UntypedRowInfo<'_>> + '_>;
+
+    /// Copies the header and rows of this param into an owned [`ParamBuilder`] which can be
+    /// modified and written back.
+    ///
+    /// # Errors
+    /// Returns [`ParamParseError::InvalidData`] if the data of a row is out-of-bounds.
+    fn to_builder(&self) -> Result<ParamBuilder, ParamParseError>;
+
+    /// Checks the row layout of this param: whether rows are sorted, have unique IDs, and have
+    /// in-bounds, non-overlapping data of a consistent size matching the row size of
+    /// `paramdef`, if given.
+    ///
+    /// Parsing a param file does *NOT* perform these checks.
+    fn validate(&self, paramdef: Option<&ParamDef>) -> ParamValidationReport {
+        validate::validate(self, paramdef)
+    }
 }
 
 impl<'a, T: traits::ParamFileLayout> ParamCommon<'a> for Param<'a, T> {
@@ -554,9 +605,23 @@ impl<'a, T: traits::ParamFileLayout> ParamCommon<'a> for Param<'a, T> {
     }
 
     fn index_of(&self, row_id: u32) -> Option<usize> {
-        self.row_descriptors
-            .binary_search_by_key(&row_id, |rd| rd.id.into())
-            .ok()
+        let lookup = self.id_lookup.get_or_init(|| {
+            (!self.are_rows_sorted()).then(|| {
+                let mut lookup = HashMap::with_capacity(self.row_descriptors.len());
+                for (index, rd) in self.row_descriptors.iter().enumerate() {
+                    lookup.entry(rd.id.get()).or_insert(index);
+                }
+                lookup
+            })
+        });
+
+        match lookup {
+            Some(lookup) => lookup.get(&row_id).copied(),
+            None => self
+                .row_descriptors
+                .binary_search_by_key(&row_id, |rd| rd.id.into())
+                .ok(),
+        }
     }
 
     fn dyn_rows(&self) -> Box<dyn Iterator<Item = UntypedRowInfo<'_>> + '_> {
@@ -568,6 +633,10 @@ impl<'a, T: traits::ParamFileLayout> ParamCommon<'a> for Param<'a, T> {
             name: row_desc.name(self).map(|s| s.to_rust_str()),
         }))
     }
+
+    fn to_builder(&self) -> Result<ParamBuilder, ParamParseError> {
+        ParamBuilder::from_param(self)
+    }
 }
 
 /// Contains untyped param row information.
@@ -605,7 +674,7 @@ impl<'a> RowDescriptorsDyn for dyn ParamCommon<'a> + 'a {
 /// finding the null-terminator to certain strings makes the worst-case linear in the size
 /// of the data.
 pub fn parse_dyn<'a>(data: &'a [u8]) -> Result<Box<dyn ParamCommon<'a> + 'a>, ParamParseError> {
-    let header = ParamHeader::<LE>::ref_from(data).ok_or(ParamParseError::InvalidData)?;
+    let header = ParamHeader::<LE>::ref_from_prefix(data).ok_or(ParamParseError::InvalidData)?;
     Ok(
         match (
             header.is_big_endian(),
@@ -0,0 +1,406 @@
+use std::io::{self, Write};
+
+use byteorder::{ByteOrder, BE, LE};
+
+use crate::param::{traits, Param, ParamCommon, ParamParseError};
+
+// Format flags as named by SoulsFormats
+const FLAG_2D_01: u8 = 0x01;
+const FLAG_2D_INT_DATA_OFFSET: u8 = 0x02;
+const FLAG_2D_LONG_DATA_OFFSET: u8 = 0x04;
+const FLAG_2D_OFFSET_PARAM_TYPE: u8 = 0x80;
+const FLAG_2E_UNICODE_ROW_NAMES: u8 = 0x01;
+
+/// An owned param row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParamRow {
+    pub id: u32,
+    /// Name of the row. Rows without a name are written with a zero name offset, while empty
+    /// names are written as an empty string.
+    pub name: Option<String>,
+    pub data: Vec<u8>,
+}
+
+/// An owned, modifiable param file which can be serialized back to the binary param format.
+///
+/// Rows are kept in insertion order while editing and are stably sorted by ID when written, so
+/// that [`ParamCommon::index_of`] and the lookups relying on it are valid for the output.
+#[derive(Clone, Debug)]
+pub struct ParamBuilder {
+    pub param_type: String,
+    pub big_endian: bool,
+    pub unk006: u16,
+    pub paramdef_data_version: u16,
+    /// Raw format flags. These determine the offset size, header size and how the param type
+    /// is stored.
+    pub format_flags_2d: u8,
+    /// Raw format flags. Bit 0 determines whether row names are UTF-16 encoded.
+    pub format_flags_2e: u8,
+    pub paramdef_format_version: u8,
+    rows: Vec<ParamRow>,
+}
+
+impl ParamBuilder {
+    /// Create an empty param with format flags matching the given file layout.
+    ///
+    /// 64-bit params store their param type in the strings section, like the params of
+    /// Elden Ring. 32-bit params store it inline in the header.
+    pub fn new(
+        param_type: impl Into<String>,
+        big_endian: bool,
+        is_64_bit: bool,
+        is_unicode: bool,
+    ) -> Self {
+        Self {
+            param_type: param_type.into(),
+            big_endian,
+            unk006: 0,
+            paramdef_data_version: 0,
+            format_flags_2d: if is_64_bit {
+                FLAG_2D_OFFSET_PARAM_TYPE | FLAG_2D_LONG_DATA_OFFSET
+            } else {
+                0
+            },
+            format_flags_2e: if is_unicode {
+                FLAG_2E_UNICODE_ROW_NAMES
+            } else {
+                0
+            },
+            paramdef_format_version: 0,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Copy the header and rows of a parsed param file.
+    ///
+    /// # Errors
+    /// Returns [`ParamParseError::InvalidData`] if the data of a row is out-of-bounds.
+    pub fn from_param<T: traits::ParamFileLayout>(
+        param: &Param<'_, T>,
+    ) -> Result<Self, ParamParseError> {
+        let header = param.header();
+        let rows = (0..param.row_count())
+            .map(|index| {
+                Ok(ParamRow {
+                    id: param.row_descriptors()[index].id.get(),
+                    name: param.name_by_index(index).map(|name| name.into_owned()),
+                    data: param
+                        .data_by_index(index)
+                        .ok_or(ParamParseError::InvalidData)?
+                        .to_vec(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            param_type: param.param_type().to_string(),
+            big_endian: header.is_big_endian(),
+            unk006: header.unk006(),
+            paramdef_data_version: header.paramdef_data_version(),
+            format_flags_2d: header.format_flags_2d(),
+            format_flags_2e: header.format_flags_2e(),
+            paramdef_format_version: header.paramdef_format_version(),
+            rows,
+        })
+    }
+
+    /// Whether the param will be written with 64-bit offsets.
+    pub fn is_64_bit(&self) -> bool {
+        (self.format_flags_2d & FLAG_2D_LONG_DATA_OFFSET) != 0
+    }
+
+    /// Whether row names will be written as UTF-16 strings.
+    pub fn is_unicode(&self) -> bool {
+        (self.format_flags_2e & FLAG_2E_UNICODE_ROW_NAMES) != 0
+    }
+
+    /// Whether the param type will be written to the strings section instead of the header.
+    pub fn is_long_param_type(&self) -> bool {
+        (self.format_flags_2d & FLAG_2D_OFFSET_PARAM_TYPE) != 0
+    }
+
+    /// The rows of this param, in their current order.
+    pub fn rows(&self) -> &[ParamRow] {
+        &self.rows
+    }
+
+    /// Mutable access to the rows of this param, allowing arbitrary reordering.
+    pub fn rows_mut(&mut self) -> &mut Vec<ParamRow> {
+        &mut self.rows
+    }
+
+    /// Returns the first row with the given ID.
+    pub fn row(&self, id: u32) -> Option<&ParamRow> {
+        self.rows.iter().find(|row| row.id == id)
+    }
+
+    /// Returns the first row with the given ID.
+    pub fn row_mut(&mut self, id: u32) -> Option<&mut ParamRow> {
+        self.rows.iter_mut().find(|row| row.id == id)
+    }
+
+    /// Appends a row. Rows with duplicate IDs are allowed and keep their relative order
+    /// when written.
+    pub fn add_row(&mut self, row: ParamRow) {
+        self.rows.push(row);
+    }
+
+    /// Replaces the first row with the same ID as `row`, or appends it if there is none.
+    /// Returns the replaced row.
+    pub fn insert_row(&mut self, row: ParamRow) -> Option<ParamRow> {
+        match self.row_mut(row.id) {
+            Some(existing) => Some(std::mem::replace(existing, row)),
+            None => {
+                self.rows.push(row);
+                None
+            }
+        }
+    }
+
+    /// Removes the first row with the given ID.
+    pub fn remove_row(&mut self, id: u32) -> Option<ParamRow> {
+        let index = self.rows.iter().position(|row| row.id == id)?;
+        Some(self.rows.remove(index))
+    }
+
+    /// Sets the name of the first row with the given ID. Returns false if there is no such row.
+    pub fn rename_row(&mut self, id: u32, name: Option<String>) -> bool {
+        match self.row_mut(id) {
+            Some(row) => {
+                row.name = name;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves the row at index `from` to index `to`, shifting the rows in between.
+    ///
+    /// # Panics
+    /// Panics if either index is out of bounds.
+    pub fn move_row(&mut self, from: usize, to: usize) {
+        let row = self.rows.remove(from);
+        self.rows.insert(to, row);
+    }
+
+    /// Stably sorts the rows by ID.
+    pub fn sort_rows(&mut self) {
+        self.rows.sort_by_key(|row| row.id);
+    }
+
+    /// Serializes the param, with rows stably sorted by ID.
+    ///
+    /// # Errors
+    /// Returns an [`io::ErrorKind::InvalidInput`] error if the param has more rows than the
+    /// 16-bit row count can hold, or if its row data starts past the 16-bit data offset of
+    /// params without a 32 or 64-bit one.
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        if self.big_endian {
+            self.to_bytes_inner::<BE>()
+        } else {
+            self.to_bytes_inner::<LE>()
+        }
+    }
+
+    /// Serializes the param to a writer. See [`ParamBuilder::to_bytes`].
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(&self.to_bytes()?)
+    }
+
+    // Follows the layout written by SoulsFormats' PARAM.Write
+    fn to_bytes_inner<BO: ByteOrder>(&self) -> io::Result<Vec<u8>> {
+        let flags = self.format_flags_2d;
+        let is_64_bit = self.is_64_bit();
+        let int_data_offset = (flags & FLAG_2D_01) != 0 && (flags & FLAG_2D_INT_DATA_OFFSET) != 0;
+
+        let mut rows: Vec<&ParamRow> = self.rows.iter().collect();
+        rows.sort_by_key(|row| row.id);
+
+        let mut out = Vec::new();
+        push_u32::<BO>(&mut out, 0);
+        let short_data_offset_pos = out.len();
+        push_u16::<BO>(&mut out, 0);
+        push_u16::<BO>(&mut out, self.unk006);
+        push_u16::<BO>(&mut out, self.paramdef_data_version);
+        let row_count = u16::try_from(rows.len()).map_err(|_| {
+            invalid_input(format!(
+                "param has {} rows, at most {} can be written",
+                rows.len(),
+                u16::MAX
+            ))
+        })?;
+        push_u16::<BO>(&mut out, row_count);
+
+        let mut param_type_offset_pos = None;
+        if self.is_long_param_type() {
+            push_u32::<BO>(&mut out, 0);
+            param_type_offset_pos = Some(out.len());
+            out.extend_from_slice(&[0; 28]);
+        } else {
+            let padding = if (flags & FLAG_2D_01) != 0 { b' ' } else { 0 };
+            let mut param_type = [padding; 0x20];
+            let len = self.param_type.len().min(0x20);
+            param_type[..len].copy_from_slice(&self.param_type.as_bytes()[..len]);
+            out.extend_from_slice(&param_type);
+        }
+
+        out.push(if self.big_endian { 0xFF } else { 0 });
+        out.push(flags);
+        out.push(self.format_flags_2e);
+        out.push(self.paramdef_format_version);
+
+        let data_offset_pos = out.len();
+        if int_data_offset || is_64_bit {
+            out.extend_from_slice(&[0; 16]);
+        }
+
+        let descriptors_pos = out.len();
+        let descriptor_size = if is_64_bit { 24 } else { 12 };
+        for row in &rows {
+            push_u32::<BO>(&mut out, row.id);
+            out.resize(out.len() + descriptor_size - 4, 0);
+        }
+
+        if flags == FLAG_2D_01 {
+            out.extend_from_slice(&[0; 0x20]);
+        }
+
+        let data_start = out.len() as u64;
+        if int_data_offset {
+            BO::write_u32(&mut out[data_offset_pos..], data_start as u32);
+        } else if is_64_bit {
+            BO::write_u64(&mut out[data_offset_pos..], data_start);
+        } else {
+            let data_start = u16::try_from(data_start).map_err(|_| {
+                invalid_input(format!(
+                    "param row data starts at {data_start:#x}, past the 16-bit data offset"
+                ))
+            })?;
+            BO::write_u16(&mut out[short_data_offset_pos..], data_start);
+        }
+
+        // Offsets of the data and name offset fields of a row descriptor
+        let field_pos = |index: usize, field: usize| {
+            let offset_size = if is_64_bit { 8 } else { 4 };
+            descriptors_pos
+                + index * descriptor_size
+                + (descriptor_size - 2 * offset_size)
+                + field * offset_size
+        };
+        let write_offset = |out: &mut Vec<u8>, pos: usize, value: u64| {
+            if is_64_bit {
+                BO::write_u64(&mut out[pos..], value);
+            } else {
+                BO::write_u32(&mut out[pos..], value as u32);
+            }
+        };
+
+        for (index, row) in rows.iter().enumerate() {
+            let offset = out.len() as u64;
+            write_offset(&mut out, field_pos(index, 0), offset);
+            out.extend_from_slice(&row.data);
+        }
+
+        let strings_offset = out.len() as u32;
+        BO::write_u32(&mut out[0..], strings_offset);
+
+        if let Some(pos) = param_type_offset_pos {
+            let offset = out.len() as u64;
+            BO::write_u64(&mut out[pos..], offset);
+            out.extend_from_slice(self.param_type.as_bytes());
+            out.push(0);
+        }
+
+        for (index, row) in rows.iter().enumerate() {
+            let Some(name) = &row.name else {
+                continue;
+            };
+
+            let offset = out.len() as u64;
+            write_offset(&mut out, field_pos(index, 1), offset);
+            if self.is_unicode() {
+                for unit in name.encode_utf16().chain([0]) {
+                    push_u16::<BO>(&mut out, unit);
+                }
+            } else {
+                out.extend_from_slice(name.as_bytes());
+                out.push(0);
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn push_u16<BO: ByteOrder>(out: &mut Vec<u8>, value: u16) {
+    let mut bytes = [0; 2];
+    BO::write_u16(&mut bytes, value);
+    out.extend_from_slice(&bytes);
+}
+
+fn push_u32<BO: ByteOrder>(out: &mut Vec<u8>, value: u32) {
+    let mut bytes = [0; 4];
+    BO::write_u32(&mut bytes, value);
+    out.extend_from_slice(&bytes);
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ParamBuilder, ParamRow};
+    use crate::param::parse_dyn;
+
+    #[test]
+    pub fn round_trips_all_layouts() {
+        for layout in 0..8 {
+            let (big_endian, is_64_bit, is_unicode) =
+                (layout & 1 != 0, layout & 2 != 0, layout & 4 != 0);
+
+            let mut builder = ParamBuilder::new("TEST_PARAM_ST", big_endian, is_64_bit, is_unicode);
+            builder.paramdef_data_version = 3;
+            for (id, name) in [(20, Some("twenty")), (5, None), (10, Some(""))] {
+                builder.add_row(ParamRow {
+                    id,
+                    name: name.map(str::to_string),
+                    data: vec![id as u8; 8],
+                });
+            }
+            assert!(builder.rename_row(5, Some("five".to_string())));
+
+            let bytes = builder.to_bytes().expect("writable param");
+            let param = parse_dyn(&bytes).expect("written param parses");
+            assert_eq!(param.is_big_endian(), big_endian);
+            assert_eq!(param.is_64_bit(), is_64_bit);
+            assert_eq!(param.is_unicode(), is_unicode);
+            assert_eq!(param.param_type(), "TEST_PARAM_ST");
+            assert_eq!(param.row_count(), 3);
+            assert!(param.are_rows_sorted());
+            assert_eq!(param.row_size(), Some(8));
+            assert_eq!(param.data_by_id(10), Some(&[10; 8][..]));
+            assert_eq!(param.name_by_id(5).as_deref(), Some("five"));
+            assert_eq!(param.name_by_id(10).as_deref(), Some(""));
+
+            let rebuilt = param.to_builder().expect("rows in bounds");
+            assert_eq!(rebuilt.paramdef_data_version, 3);
+            assert_eq!(rebuilt.format_flags_2d, builder.format_flags_2d);
+            assert_eq!(rebuilt.to_bytes().expect("writable param"), bytes);
+        }
+    }
+
+    #[test]
+    pub fn rejects_too_many_rows() {
+        let mut builder = ParamBuilder::new("TEST_PARAM_ST", false, true, true);
+        for id in 0..=u16::MAX as u32 {
+            builder.add_row(ParamRow {
+                id,
+                name: None,
+                data: Vec::new(),
+            });
+        }
+
+        assert!(builder.to_bytes().is_err());
+    }
+}
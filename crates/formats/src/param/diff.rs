@@ -0,0 +1,589 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use byteorder::{ByteOrder, BE, LE};
+
+use crate::param::{
+    paramdef::{ParamDef, ParamDefField},
+    typed_row::write_field,
+    ParamBuilder, ParamCommon, ParamParseError, ParamRow, TypedRow,
+};
+
+/// Rows are identified by their ID and by their position among rows sharing that ID, so that
+/// params with duplicate IDs can still be compared.
+type RowKey = (u32, usize);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct RowValue<'a> {
+    name: Option<String>,
+    data: &'a [u8],
+}
+
+/// Differences between two params. See [`diff`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParamDiff {
+    /// IDs of rows only present in the new param.
+    pub added: Vec<u32>,
+    /// IDs of rows only present in the old param.
+    pub removed: Vec<u32>,
+    /// Rows present in both params whose name or data differ.
+    pub changed: Vec<RowDiff>,
+}
+
+impl ParamDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Differences between two versions of a row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RowDiff {
+    pub id: u32,
+    /// Old and new names of the row, if it was renamed.
+    pub name: Option<(Option<String>, Option<String>)>,
+    pub changes: Vec<DataChange>,
+}
+
+/// A single difference in the data of a row.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DataChange {
+    /// A run of differing bytes, reported when no paramdef is available or the row is smaller
+    /// than the row size of the paramdef.
+    Bytes {
+        offset: usize,
+        old: Vec<u8>,
+        new: Vec<u8>,
+    },
+    /// A field whose value differs, formatted as text.
+    Field {
+        name: String,
+        old: String,
+        new: String,
+    },
+}
+
+/// Computes the row-level differences between `old` and `new`. If a paramdef is given, changed
+/// data is reported per field by comparing the raw bytes of each field, otherwise as runs of
+/// differing bytes.
+///
+/// This does not rely on the rows being sorted.
+///
+/// # Errors
+/// Returns [`ParamParseError::InvalidData`] if the data of a row of either param is
+/// out-of-bounds.
+pub fn diff(
+    old: &dyn ParamCommon<'_>,
+    new: &dyn ParamCommon<'_>,
+    paramdef: Option<&ParamDef>,
+) -> Result<ParamDiff, ParamParseError> {
+    let old_rows = rows(old)?;
+    let new_rows = rows(new)?;
+    let mut result = ParamDiff::default();
+
+    let keys: BTreeSet<&RowKey> = old_rows.keys().chain(new_rows.keys()).collect();
+    for key in keys {
+        match (old_rows.get(key), new_rows.get(key)) {
+            (Some(_), None) => result.removed.push(key.0),
+            (None, Some(_)) => result.added.push(key.0),
+            (Some(old_row), Some(new_row)) if old_row != new_row => {
+                result.changed.push(RowDiff {
+                    id: key.0,
+                    name: (old_row.name != new_row.name)
+                        .then(|| (old_row.name.clone(), new_row.name.clone())),
+                    changes: data_changes(
+                        old_row.data,
+                        new_row.data,
+                        old.is_big_endian(),
+                        paramdef,
+                    ),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    Ok(result)
+}
+
+/// A conflict found while merging two params. See [`merge`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MergeConflict {
+    pub id: u32,
+    pub kind: ConflictKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConflictKind {
+    /// Both sides added a row with this ID with different contents.
+    BothAdded,
+    /// One side removed the row while the other modified it.
+    RemovedAndModified,
+    /// Both sides renamed the row differently.
+    Name,
+    /// Both sides changed the same field differently.
+    Field(String),
+    /// Both sides changed the row data differently, and no paramdef was available to merge it
+    /// field by field (or the rows are smaller than the row size of the paramdef).
+    Data,
+}
+
+/// The result of a three-way merge. Conflicting changes are resolved in favour of `ours`.
+pub struct MergeResult {
+    pub param: ParamBuilder,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// Performs a three-way merge of the changes made to `base` by `ours` and `theirs`.
+///
+/// Changes made by only one side are applied. With a paramdef, rows changed by both sides are
+/// merged field by field, otherwise they conflict unless both made the same change. Every
+/// conflict is reported and resolved by keeping the version in `ours`. The merged param keeps
+/// the header of `ours`.
+///
+/// # Errors
+/// Returns [`ParamParseError::InvalidData`] if the data of a row of any of the params is
+/// out-of-bounds.
+pub fn merge(
+    base: &dyn ParamCommon<'_>,
+    ours: &dyn ParamCommon<'_>,
+    theirs: &dyn ParamCommon<'_>,
+    paramdef: Option<&ParamDef>,
+) -> Result<MergeResult, ParamParseError> {
+    let big_endian = ours.is_big_endian();
+    let base_rows = rows(base)?;
+    let our_rows = rows(ours)?;
+    let their_rows = rows(theirs)?;
+
+    let mut param = ours.to_builder()?;
+    let mut merged = Vec::new();
+    let mut conflicts = Vec::new();
+    let mut conflict = |id, kind| conflicts.push(MergeConflict { id, kind });
+
+    let keys: BTreeSet<&RowKey> = base_rows
+        .keys()
+        .chain(our_rows.keys())
+        .chain(their_rows.keys())
+        .collect();
+
+    for key in keys {
+        let id = key.0;
+        let row = match (base_rows.get(key), our_rows.get(key), their_rows.get(key)) {
+            (_, None, None) => None,
+            (None, Some(row), None) | (None, None, Some(row)) => Some(owned_row(id, row)),
+            (None, Some(ours), Some(theirs)) => {
+                if ours != theirs {
+                    conflict(id, ConflictKind::BothAdded);
+                }
+                Some(owned_row(id, ours))
+            }
+            (Some(base), Some(row), None) | (Some(base), None, Some(row)) => {
+                if base == row {
+                    None
+                } else {
+                    conflict(id, ConflictKind::RemovedAndModified);
+                    our_rows.get(key).map(|row| owned_row(id, row))
+                }
+            }
+            (Some(base), Some(ours), Some(theirs)) => {
+                let name = if theirs.name == base.name {
+                    ours.name.clone()
+                } else if ours.name == base.name || ours.name == theirs.name {
+                    theirs.name.clone()
+                } else {
+                    conflict(id, ConflictKind::Name);
+                    ours.name.clone()
+                };
+
+                let data = if theirs.data == base.data || ours.data == theirs.data {
+                    ours.data.to_vec()
+                } else if ours.data == base.data {
+                    theirs.data.to_vec()
+                } else {
+                    let (data, data_conflicts) =
+                        merge_data(base.data, ours.data, theirs.data, big_endian, paramdef);
+                    for kind in data_conflicts {
+                        conflict(id, kind);
+                    }
+                    data
+                };
+
+                Some(ParamRow { id, name, data })
+            }
+        };
+
+        merged.extend(row);
+    }
+
+    *param.rows_mut() = merged;
+    Ok(MergeResult { param, conflicts })
+}
+
+/// Collects the rows of a param by key. The data of a row spans the detected row size of the
+/// param, or failing that runs up to the data of the next row, or to the strings for the last
+/// one. Bytes past the row size of a paramdef are kept so they survive a merge.
+fn rows<'a>(
+    param: &'a dyn ParamCommon<'_>,
+) -> Result<BTreeMap<RowKey, RowValue<'a>>, ParamParseError> {
+    let file = param.file_bytes();
+    let row_size = param.row_size();
+
+    let mut data_offsets: Vec<usize> = param
+        .dyn_rows()
+        .map(|row| row.data_offset as usize)
+        .collect();
+    data_offsets.sort_unstable();
+    data_offsets.dedup();
+    let strings_offset = param
+        .strings()
+        .map_or(file.len(), |strings| file.len() - strings.len());
+
+    let mut occurrences: BTreeMap<u32, usize> = BTreeMap::new();
+    param
+        .dyn_rows()
+        .map(|row| {
+            let occurrence = occurrences.entry(row.id).or_default();
+            let key = (row.id, *occurrence);
+            *occurrence += 1;
+
+            let start = row.data_offset as usize;
+            let end = match row_size {
+                Some(size) => start.saturating_add(size),
+                None => {
+                    let next = data_offsets.partition_point(|offset| *offset <= start);
+                    data_offsets
+                        .get(next)
+                        .copied()
+                        .unwrap_or(strings_offset)
+                        .max(start)
+                }
+            };
+
+            let data = file.get(start..end).ok_or(ParamParseError::InvalidData)?;
+            Ok((
+                key,
+                RowValue {
+                    name: row.name.map(|name| name.into_owned()),
+                    data,
+                },
+            ))
+        })
+        .collect()
+}
+
+fn owned_row(id: u32, row: &RowValue<'_>) -> ParamRow {
+    ParamRow {
+        id,
+        name: row.name.clone(),
+        data: row.data.to_vec(),
+    }
+}
+
+fn data_changes(
+    old: &[u8],
+    new: &[u8],
+    big_endian: bool,
+    paramdef: Option<&ParamDef>,
+) -> Vec<DataChange> {
+    let mut changes = Vec::new();
+    let mut bytes_from = 0;
+
+    if let Some(paramdef) = paramdef {
+        if let (Some(old_row), Some(new_row)) = (
+            TypedRow::new(paramdef, old, big_endian),
+            TypedRow::new(paramdef, new, big_endian),
+        ) {
+            for field in paramdef.fields() {
+                if !same_field(field, old, new, big_endian) {
+                    changes.push(DataChange::Field {
+                        name: field.internal_name.clone(),
+                        old: old_row.read_field(field).to_string(),
+                        new: new_row.read_field(field).to_string(),
+                    });
+                }
+            }
+            bytes_from = paramdef.row_size();
+        }
+    }
+
+    // Report the remaining differences as runs of differing bytes
+    let len = old.len().max(new.len());
+    let mut offset = bytes_from;
+    while offset < len {
+        if old.get(offset) == new.get(offset) {
+            offset += 1;
+            continue;
+        }
+
+        let start = offset;
+        while offset < len && old.get(offset) != new.get(offset) {
+            offset += 1;
+        }
+        changes.push(DataChange::Bytes {
+            offset: start,
+            old: old
+                .get(start..offset.min(old.len()))
+                .unwrap_or_default()
+                .to_vec(),
+            new: new
+                .get(start..offset.min(new.len()))
+                .unwrap_or_default()
+                .to_vec(),
+        });
+    }
+
+    changes
+}
+
+/// Merges rows changed by both sides field by field, applying every change that doesn't
+/// conflict and returning the conflicts.
+fn merge_data(
+    base: &[u8],
+    ours: &[u8],
+    theirs: &[u8],
+    big_endian: bool,
+    paramdef: Option<&ParamDef>,
+) -> (Vec<u8>, Vec<ConflictKind>) {
+    let Some(paramdef) = paramdef else {
+        return (ours.to_vec(), vec![ConflictKind::Data]);
+    };
+
+    let row_size = paramdef.row_size();
+    let (Some(_), Some(_), Some(their_row)) = (
+        TypedRow::new(paramdef, base, big_endian),
+        TypedRow::new(paramdef, ours, big_endian),
+        TypedRow::new(paramdef, theirs, big_endian),
+    ) else {
+        return (ours.to_vec(), vec![ConflictKind::Data]);
+    };
+
+    let mut merged = ours.to_vec();
+    let mut conflicts = Vec::new();
+    if base[row_size..] != theirs[row_size..] && ours[row_size..] != theirs[row_size..] {
+        if base[row_size..] == ours[row_size..] {
+            merged.truncate(row_size);
+            merged.extend_from_slice(&theirs[row_size..]);
+        } else {
+            conflicts.push(ConflictKind::Data);
+        }
+    }
+
+    for field in paramdef.fields() {
+        if same_field(field, theirs, base, big_endian)
+            || same_field(field, theirs, ours, big_endian)
+        {
+            continue;
+        }
+
+        if same_field(field, ours, base, big_endian) {
+            write_field(field, &mut merged, big_endian, &their_row.read_field(field));
+        } else {
+            conflicts.push(ConflictKind::Field(field.internal_name.clone()));
+        }
+    }
+
+    (merged, conflicts)
+}
+
+/// Compares the raw bytes of a field in two rows, or only its bits for bitfields.
+fn same_field(field: &ParamDefField, a: &[u8], b: &[u8], big_endian: bool) -> bool {
+    let range = field.byte_offset()..field.byte_offset() + field.size();
+    let (Some(a), Some(b)) = (a.get(range.clone()), b.get(range)) else {
+        return false;
+    };
+
+    match (field.bit_offset(), field.bit_size) {
+        (Some(bit_offset), Some(bit_size)) => {
+            let read = |bytes: &[u8]| {
+                let unit = if big_endian {
+                    BE::read_uint(bytes, bytes.len())
+                } else {
+                    LE::read_uint(bytes, bytes.len())
+                };
+                (unit >> bit_offset) & ((1 << bit_size) - 1)
+            };
+            read(a) == read(b)
+        }
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{diff, merge, ConflictKind, DataChange, MergeConflict};
+    use crate::param::{paramdef::ParamDef, parse_dyn, ParamBuilder, ParamParseError, ParamRow};
+
+    const PARAMDEF: &str = r#"<PARAMDEF XmlVersion="3">
+  <ParamType>TEST_PARAM_ST</ParamType>
+  <Fields>
+    <Field Def="s32 a" />
+    <Field Def="s32 b" />
+  </Fields>
+</PARAMDEF>"#;
+
+    fn param(rows: &[(u32, i32, i32)]) -> Vec<u8> {
+        let mut param = ParamBuilder::new("TEST_PARAM_ST", false, true, true);
+        for &(id, a, b) in rows {
+            let mut data = a.to_le_bytes().to_vec();
+            data.extend(b.to_le_bytes());
+            param.add_row(ParamRow {
+                id,
+                name: None,
+                data,
+            });
+        }
+        param.to_bytes().expect("writable param")
+    }
+
+    #[test]
+    pub fn merges_fields_and_reports_conflicts() {
+        let paramdef = ParamDef::from_xml(PARAMDEF).expect("valid paramdef");
+        let base = param(&[(1, 1, 1), (2, 1, 1), (4, 0, 0)]);
+        let ours = param(&[(1, 2, 1), (2, 5, 1)]);
+        let theirs = param(&[(1, 1, 3), (2, 6, 1), (3, 7, 7), (4, 0, 0)]);
+        let (base, ours, theirs) = (
+            parse_dyn(&base).expect("valid param"),
+            parse_dyn(&ours).expect("valid param"),
+            parse_dyn(&theirs).expect("valid param"),
+        );
+
+        let changes = diff(&*base, &*ours, Some(&paramdef)).expect("valid rows");
+        assert_eq!(changes.removed, vec![4]);
+        assert_eq!(
+            changes.changed[0].changes,
+            vec![DataChange::Field {
+                name: "a".to_string(),
+                old: "1".to_string(),
+                new: "2".to_string(),
+            }]
+        );
+
+        let result = merge(&*base, &*ours, &*theirs, Some(&paramdef)).expect("valid rows");
+        assert_eq!(
+            result.conflicts,
+            vec![MergeConflict {
+                id: 2,
+                kind: ConflictKind::Field("a".to_string()),
+            }]
+        );
+
+        let merged = result.param;
+        let ids: Vec<u32> = merged.rows().iter().map(|row| row.id).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(merged.rows()[0].data, [2, 0, 0, 0, 3, 0, 0, 0]);
+        assert_eq!(merged.rows()[1].data, [5, 0, 0, 0, 1, 0, 0, 0]);
+    }
+
+    #[test]
+    pub fn bounds_rows_and_compares_raw_fields() {
+        let single_row = |name: &str, data: &[u8]| {
+            let mut param = ParamBuilder::new("TEST_PARAM_ST", false, true, true);
+            param.add_row(ParamRow {
+                id: 1,
+                name: Some(name.to_string()),
+                data: data.to_vec(),
+            });
+            param.to_bytes().expect("writable param")
+        };
+
+        // Without a detected row size, row data must stop before the names
+        let (old, new) = (single_row("old", b"ab\0\0"), single_row("new", b"ac\0x"));
+        let (old, new) = (
+            parse_dyn(&old).expect("valid param"),
+            parse_dyn(&new).expect("valid param"),
+        );
+        let changes = diff(&*old, &*new, None).expect("valid rows");
+        assert_eq!(
+            changes.changed[0].changes,
+            vec![
+                DataChange::Bytes {
+                    offset: 1,
+                    old: vec![b'b'],
+                    new: vec![b'c'],
+                },
+                DataChange::Bytes {
+                    offset: 3,
+                    old: vec![0],
+                    new: vec![b'x'],
+                },
+            ]
+        );
+
+        // Fields are compared by their raw bytes, including those after a string terminator
+        let paramdef = ParamDef::from_xml(
+            r#"<PARAMDEF XmlVersion="3">
+  <ParamType>TEST_PARAM_ST</ParamType>
+  <Fields>
+    <Field Def="fixstr label[4]" />
+  </Fields>
+</PARAMDEF>"#,
+        )
+        .expect("valid paramdef");
+        let changes = diff(&*old, &*new, Some(&paramdef)).expect("valid rows");
+        assert_eq!(
+            changes.changed[0].changes,
+            vec![DataChange::Field {
+                name: "label".to_string(),
+                old: "ab".to_string(),
+                new: "ac".to_string(),
+            }]
+        );
+        let (same, trailing) = (single_row("old", b"ab\0\0"), single_row("new", b"ab\0x"));
+        let (same, trailing) = (
+            parse_dyn(&same).expect("valid param"),
+            parse_dyn(&trailing).expect("valid param"),
+        );
+        let changes = diff(&*same, &*trailing, Some(&paramdef)).expect("valid rows");
+        assert!(matches!(
+            &changes.changed[0].changes[..],
+            [DataChange::Field { name, .. }] if name == "label"
+        ));
+
+        // Rows whose data lies outside of the file are an error, not an empty row
+        let valid = param(&[(1, 1, 1), (2, 1, 1)]);
+        let mut corrupt = valid.clone();
+        corrupt[0x40 + 24 + 8..0x40 + 24 + 16].copy_from_slice(&0x1000u64.to_le_bytes());
+        let (valid, corrupt) = (
+            parse_dyn(&valid).expect("valid param"),
+            parse_dyn(&corrupt).expect("valid param"),
+        );
+        assert!(matches!(
+            diff(&*valid, &*corrupt, None),
+            Err(ParamParseError::InvalidData)
+        ));
+    }
+
+    #[test]
+    pub fn merges_bytes_past_the_paramdef() {
+        let paramdef = ParamDef::from_xml(
+            r#"<PARAMDEF XmlVersion="3">
+  <ParamType>TEST_PARAM_ST</ParamType>
+  <Fields>
+    <Field Def="s32 a" />
+  </Fields>
+</PARAMDEF>"#,
+        )
+        .expect("valid paramdef");
+        let base = param(&[(1, 1, 1), (2, 1, 1)]);
+        let ours = param(&[(1, 2, 1), (2, 1, 1)]);
+        let theirs = param(&[(1, 1, 3), (2, 1, 1)]);
+        let (base, ours, theirs) = (
+            parse_dyn(&base).expect("valid param"),
+            parse_dyn(&ours).expect("valid param"),
+            parse_dyn(&theirs).expect("valid param"),
+        );
+
+        let changes = diff(&*base, &*theirs, Some(&paramdef)).expect("valid rows");
+        assert_eq!(
+            changes.changed[0].changes,
+            vec![DataChange::Bytes {
+                offset: 4,
+                old: vec![1],
+                new: vec![3],
+            }]
+        );
+
+        let result = merge(&*base, &*ours, &*theirs, Some(&paramdef)).expect("valid rows");
+        assert!(result.conflicts.is_empty());
+        assert_eq!(result.param.rows()[0].data, [2, 0, 0, 0, 3, 0, 0, 0]);
+    }
+}
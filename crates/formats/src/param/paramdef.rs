@@ -0,0 +1,628 @@
+use std::{collections::HashMap, fmt::Display, io, str::FromStr};
+
+use byteorder::{ByteOrder, ReadBytesExt, BE, LE};
+use encoding_rs::SHIFT_JIS;
+use thiserror::Error;
+
+mod xml;
+
+#[derive(Debug, Error)]
+pub enum ParamDefError {
+    #[error("Could not read paramdef data {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Unsupported paramdef format version {0}")]
+    UnsupportedFormatVersion(u16),
+
+    #[error("Unknown field type {0:?}")]
+    UnknownFieldType(String),
+
+    #[error("Invalid field definition {0:?}")]
+    InvalidFieldDefinition(String),
+
+    #[error("Could not parse paramdef XML: {0}")]
+    Xml(String),
+
+    #[error("Invalid data")]
+    InvalidData,
+}
+
+/// Value type of a single field in a param row.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FieldType {
+    Dummy8,
+    U8,
+    S8,
+    U16,
+    S16,
+    U32,
+    S32,
+    B32,
+    F32,
+    Angle32,
+    F64,
+    FixStr,
+    FixStrW,
+}
+
+impl FieldType {
+    /// Size in bytes of a single element of this type.
+    pub fn value_size(self) -> usize {
+        match self {
+            FieldType::Dummy8 | FieldType::U8 | FieldType::S8 | FieldType::FixStr => 1,
+            FieldType::U16 | FieldType::S16 | FieldType::FixStrW => 2,
+            FieldType::U32
+            | FieldType::S32
+            | FieldType::B32
+            | FieldType::F32
+            | FieldType::Angle32 => 4,
+            FieldType::F64 => 8,
+        }
+    }
+
+    /// Whether fields of this type may declare an array length.
+    pub fn is_array_type(self) -> bool {
+        matches!(
+            self,
+            FieldType::Dummy8 | FieldType::FixStr | FieldType::FixStrW
+        )
+    }
+
+    /// The integer type bitfields of this type are packed into, if bitfields are allowed.
+    pub fn bit_unit(self) -> Option<FieldType> {
+        match self {
+            FieldType::Dummy8 | FieldType::U8 => Some(FieldType::U8),
+            FieldType::U16 => Some(FieldType::U16),
+            FieldType::U32 => Some(FieldType::U32),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FieldType::Dummy8 => "dummy8",
+            FieldType::U8 => "u8",
+            FieldType::S8 => "s8",
+            FieldType::U16 => "u16",
+            FieldType::S16 => "s16",
+            FieldType::U32 => "u32",
+            FieldType::S32 => "s32",
+            FieldType::B32 => "b32",
+            FieldType::F32 => "f32",
+            FieldType::Angle32 => "angle32",
+            FieldType::F64 => "f64",
+            FieldType::FixStr => "fixstr",
+            FieldType::FixStrW => "fixstrW",
+        }
+    }
+}
+
+impl FromStr for FieldType {
+    type Err = ParamDefError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "dummy8" => FieldType::Dummy8,
+            "u8" => FieldType::U8,
+            "s8" => FieldType::S8,
+            "u16" => FieldType::U16,
+            "s16" => FieldType::S16,
+            "u32" => FieldType::U32,
+            "s32" => FieldType::S32,
+            "b32" => FieldType::B32,
+            "f32" => FieldType::F32,
+            "angle32" => FieldType::Angle32,
+            "f64" => FieldType::F64,
+            "fixstr" => FieldType::FixStr,
+            "fixstrW" => FieldType::FixStrW,
+            _ => return Err(ParamDefError::UnknownFieldType(s.to_string())),
+        })
+    }
+}
+
+impl Display for FieldType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Describes a single field of a param row.
+#[derive(Clone, Debug)]
+pub struct ParamDefField {
+    pub display_name: String,
+    pub display_type: FieldType,
+    pub display_format: String,
+    pub default: f32,
+    pub minimum: f32,
+    pub maximum: f32,
+    pub increment: f32,
+    pub edit_flags: u32,
+    pub description: Option<String>,
+    /// Name of the enum or underlying type of this field, e.g. `ATK_PARAM_HIT_SOURCE`.
+    pub internal_type: String,
+    /// Name of this field without any bitfield or array suffix.
+    pub internal_name: String,
+    /// Number of bits used by this field if it is a bitfield.
+    pub bit_size: Option<u8>,
+    /// Number of elements for `dummy8`, `fixstr` and `fixstrW` fields. 1 otherwise.
+    pub array_length: usize,
+    pub sort_id: i32,
+    byte_offset: usize,
+    bit_offset: u8,
+}
+
+impl ParamDefField {
+    pub(crate) fn new(display_type: FieldType, internal_name: String) -> Self {
+        let is_float = matches!(
+            display_type,
+            FieldType::F32 | FieldType::Angle32 | FieldType::F64
+        );
+        let (minimum, maximum) = match display_type {
+            FieldType::S8 => (i8::MIN as f32, i8::MAX as f32),
+            FieldType::U8 => (0.0, u8::MAX as f32),
+            FieldType::S16 => (i16::MIN as f32, i16::MAX as f32),
+            FieldType::U16 => (0.0, u16::MAX as f32),
+            FieldType::S32 => (i32::MIN as f32, i32::MAX as f32),
+            FieldType::U32 => (0.0, u32::MAX as f32),
+            FieldType::B32 => (0.0, 1.0),
+            FieldType::F32 | FieldType::Angle32 | FieldType::F64 => (f32::MIN, f32::MAX),
+            FieldType::Dummy8 | FieldType::FixStr | FieldType::FixStrW => (0.0, 0.0),
+        };
+        let display_format = match display_type {
+            FieldType::Dummy8 => "",
+            FieldType::FixStr | FieldType::FixStrW => "%s",
+            _ if is_float => "%f",
+            _ => "%d",
+        };
+
+        Self {
+            display_name: internal_name.clone(),
+            display_type,
+            display_format: display_format.to_string(),
+            default: 0.0,
+            minimum,
+            maximum,
+            increment: if is_float { 0.1 } else { 1.0 },
+            edit_flags: if display_type.is_array_type() { 0 } else { 1 },
+            description: None,
+            internal_type: display_type.to_string(),
+            internal_name,
+            bit_size: None,
+            array_length: 1,
+            sort_id: 0,
+            byte_offset: 0,
+            bit_offset: 0,
+        }
+    }
+
+    /// Applies a `name:bits` or `name[length]` suffix from an internal name to this field.
+    pub(crate) fn apply_internal_name(&mut self, internal_name: &str) -> Result<(), ParamDefError> {
+        let invalid = || ParamDefError::InvalidFieldDefinition(internal_name.to_string());
+        let internal_name = internal_name.trim();
+
+        if let Some((name, bits)) = internal_name.split_once(':') {
+            let bits: u8 = bits.trim().parse().map_err(|_| invalid())?;
+            let unit = self.display_type.bit_unit().ok_or_else(invalid)?;
+            if bits == 0 || bits as usize > unit.value_size() * 8 {
+                return Err(invalid());
+            }
+
+            self.internal_name = name.trim().to_string();
+            self.bit_size = Some(bits);
+        } else if let Some((name, length)) = internal_name
+            .strip_suffix(']')
+            .and_then(|s| s.split_once('['))
+        {
+            if !self.display_type.is_array_type() {
+                return Err(invalid());
+            }
+
+            self.internal_name = name.trim().to_string();
+            self.array_length = length.trim().parse().map_err(|_| invalid())?;
+        } else {
+            self.internal_name = internal_name.to_string();
+        }
+
+        Ok(())
+    }
+
+    /// Size in bytes of this field, or of the integer it is packed into for bitfields.
+    pub fn size(&self) -> usize {
+        match self.bit_size {
+            Some(_) => self.display_type.value_size(),
+            None => self.display_type.value_size() * self.array_length,
+        }
+    }
+
+    /// Offset of this field from the start of the row data.
+    pub fn byte_offset(&self) -> usize {
+        self.byte_offset
+    }
+
+    /// Offset of the least significant bit of this field within the integer at
+    /// [`ParamDefField::byte_offset`], if this field is a bitfield.
+    pub fn bit_offset(&self) -> Option<u8> {
+        self.bit_size.map(|_| self.bit_offset)
+    }
+
+    /// Whether this field only exists to pad the row and carries no data.
+    pub fn is_padding(&self) -> bool {
+        self.display_type == FieldType::Dummy8
+    }
+}
+
+/// Describes the layout of the rows in a param file, as found in `.paramdef` files or the XML
+/// definitions distributed with Paramdex.
+#[derive(Clone, Debug)]
+pub struct ParamDef {
+    pub param_type: String,
+    pub data_version: u16,
+    pub big_endian: bool,
+    pub unicode: bool,
+    pub format_version: u16,
+    fields: Vec<ParamDefField>,
+    field_indices: HashMap<String, usize>,
+    row_size: usize,
+}
+
+impl ParamDef {
+    /// Creates a paramdef from a list of fields, computing the offset of each field in a row.
+    pub fn new(
+        param_type: String,
+        data_version: u16,
+        big_endian: bool,
+        unicode: bool,
+        format_version: u16,
+        mut fields: Vec<ParamDefField>,
+    ) -> Self {
+        let mut offset = 0;
+        // Integer type and next free bit of the bitfield unit currently being filled.
+        let mut bit_unit: Option<(FieldType, u8)> = None;
+
+        for field in fields.iter_mut() {
+            let Some(bit_size) = field.bit_size else {
+                bit_unit = None;
+                field.byte_offset = offset;
+                field.bit_offset = 0;
+                offset += field.size();
+                continue;
+            };
+
+            // Bitfield sizes are validated on construction, so the unit type is always present
+            let unit = field.display_type.bit_unit().unwrap_or(FieldType::U8);
+            let unit_bits = unit.value_size() as u8 * 8;
+
+            match &mut bit_unit {
+                Some((ty, next_bit)) if *ty == unit && *next_bit + bit_size <= unit_bits => {
+                    field.byte_offset = offset - unit.value_size();
+                    field.bit_offset = *next_bit;
+                    *next_bit += bit_size;
+                }
+                _ => {
+                    field.byte_offset = offset;
+                    field.bit_offset = 0;
+                    offset += unit.value_size();
+                    bit_unit = Some((unit, bit_size));
+                }
+            }
+        }
+
+        // Padding fields often share a name, in which case the first one is kept
+        let mut field_indices = HashMap::new();
+        for (index, field) in fields.iter().enumerate() {
+            field_indices
+                .entry(field.internal_name.clone())
+                .or_insert(index);
+        }
+
+        Self {
+            param_type,
+            data_version,
+            big_endian,
+            unicode,
+            format_version,
+            fields,
+            field_indices,
+            row_size: offset,
+        }
+    }
+
+    /// Parse a binary `.paramdef` file.
+    ///
+    /// # Errors
+    /// Returns a [`ParamDefError`] if the data is truncated, uses an unknown field type or a
+    /// format version that is not supported.
+    pub fn parse(bytes: &[u8]) -> Result<Self, ParamDefError> {
+        match bytes.get(0x2C) {
+            Some(0xFF) => Self::parse_inner::<BE>(bytes),
+            Some(_) => Self::parse_inner::<LE>(bytes),
+            None => Err(ParamDefError::InvalidData),
+        }
+    }
+
+    /// Load a paramdef from its Paramdex XML representation.
+    ///
+    /// # Errors
+    /// Returns a [`ParamDefError`] if the XML is malformed or contains invalid field
+    /// definitions.
+    pub fn from_xml(xml: &str) -> Result<Self, ParamDefError> {
+        xml::from_xml(xml)
+    }
+
+    fn parse_inner<BO: ByteOrder>(bytes: &[u8]) -> Result<Self, ParamDefError> {
+        let format_version = BO::read_u16(bytes.get(0x2E..0x30).ok_or(ParamDefError::InvalidData)?);
+        if !matches!(format_version, 101..=104 | 106 | 201..=203) {
+            return Err(ParamDefError::UnsupportedFormatVersion(format_version));
+        }
+
+        let unicode = bytes[0x2D] != 0;
+        let mut header = &bytes[4..];
+        let header_size = header.read_u16::<BO>()?;
+        let data_version = header.read_u16::<BO>()?;
+        let field_count = header.read_u16::<BO>()? as usize;
+        let field_size = header.read_u16::<BO>()? as usize;
+
+        let param_type = if format_version >= 202 {
+            let offset = BO::read_u64(&bytes[0x10..0x18]) as usize;
+            read_ascii(bytes, offset)?
+        } else {
+            fixed_str(&bytes[0x0C..0x2C], false)
+        };
+
+        let fields_offset = if format_version >= 200 {
+            BO::read_u64(bytes.get(0x30..0x38).ok_or(ParamDefError::InvalidData)?) as usize
+        } else {
+            header_size as usize
+        };
+
+        let fields = (0..field_count)
+            .map(|index| {
+                let start = fields_offset + index * field_size;
+                let data = bytes
+                    .get(start..start + field_size)
+                    .ok_or(ParamDefError::InvalidData)?;
+
+                Self::parse_field::<BO>(bytes, data, format_version, unicode)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self::new(
+            param_type,
+            data_version,
+            BO::read_u16(&[0, 1]) == 1,
+            unicode,
+            format_version,
+            fields,
+        ))
+    }
+
+    fn parse_field<BO: ByteOrder>(
+        file: &[u8],
+        mut r: &[u8],
+        format_version: u16,
+        unicode: bool,
+    ) -> Result<ParamDefField, ParamDefError> {
+        let display_name = if format_version >= 202 {
+            let offset = r.read_u64::<BO>()? as usize;
+            read_string::<BO>(
+                file.get(offset..).ok_or(ParamDefError::InvalidData)?,
+                unicode,
+            )
+        } else {
+            let (name, rest) = r.split_at(0x40.min(r.len()));
+            r = rest;
+            read_string::<BO>(name, unicode)
+        };
+
+        let (display_type, rest) = r.split_at(8.min(r.len()));
+        let display_type: FieldType = fixed_str(display_type, false).parse()?;
+        let (display_format, rest) = rest.split_at(8.min(rest.len()));
+        let display_format = fixed_str(display_format, false);
+        r = rest;
+
+        let default = r.read_f32::<BO>()?;
+        let minimum = r.read_f32::<BO>()?;
+        let maximum = r.read_f32::<BO>()?;
+        let increment = r.read_f32::<BO>()?;
+        let edit_flags = r.read_u32::<BO>()?;
+        let _byte_count = r.read_u32::<BO>()?;
+
+        let description_offset = if format_version >= 200 {
+            r.read_u64::<BO>()? as usize
+        } else {
+            r.read_u32::<BO>()? as usize
+        };
+        let description = match description_offset {
+            0 => None,
+            offset => Some(read_string::<BO>(
+                file.get(offset..).ok_or(ParamDefError::InvalidData)?,
+                unicode,
+            )),
+        };
+
+        let internal_type = if format_version >= 202 {
+            read_ascii(file, r.read_u64::<BO>()? as usize)?
+        } else {
+            let (internal_type, rest) = r.split_at(0x20.min(r.len()));
+            r = rest;
+            fixed_str(internal_type, false)
+        };
+
+        let internal_name = if format_version >= 202 {
+            Some(read_ascii(file, r.read_u64::<BO>()? as usize)?)
+        } else if format_version >= 102 {
+            let (internal_name, rest) = r.split_at(0x20.min(r.len()));
+            r = rest;
+            Some(fixed_str(internal_name, false))
+        } else {
+            None
+        };
+
+        let sort_id = if format_version >= 104 {
+            r.read_i32::<BO>()?
+        } else {
+            0
+        };
+
+        let mut field = ParamDefField::new(display_type, display_name.clone());
+        if let Some(internal_name) = internal_name {
+            field.apply_internal_name(&internal_name)?;
+        }
+
+        field.display_name = display_name;
+        field.display_format = display_format;
+        field.default = default;
+        field.minimum = minimum;
+        field.maximum = maximum;
+        field.increment = increment;
+        field.edit_flags = edit_flags;
+        field.description = description;
+        field.internal_type = internal_type.trim().to_string();
+        field.sort_id = sort_id;
+
+        Ok(field)
+    }
+
+    /// The fields of this paramdef, in the order they are laid out in a row.
+    pub fn fields(&self) -> &[ParamDefField] {
+        &self.fields
+    }
+
+    /// Finds a field by its internal name.
+    pub fn field(&self, name: &str) -> Option<&ParamDefField> {
+        self.field_indices
+            .get(name)
+            .map(|index| &self.fields[*index])
+    }
+
+    /// Size in bytes of a single row described by this paramdef.
+    pub fn row_size(&self) -> usize {
+        self.row_size
+    }
+}
+
+/// Reads a null-terminated single byte string from a fixed size buffer.
+pub(crate) fn fixed_str(bytes: &[u8], shift_jis: bool) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    if shift_jis {
+        SHIFT_JIS
+            .decode_without_bom_handling(&bytes[..end])
+            .0
+            .into_owned()
+    } else {
+        String::from_utf8_lossy(&bytes[..end]).into_owned()
+    }
+}
+
+/// Reads a null-terminated UTF-16 string from a fixed size buffer.
+pub(crate) fn fixed_wstr<BO: ByteOrder>(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(BO::read_u16)
+        .take_while(|unit| *unit != 0)
+        .collect();
+
+    String::from_utf16_lossy(&units)
+}
+
+fn read_string<BO: ByteOrder>(bytes: &[u8], unicode: bool) -> String {
+    if unicode {
+        fixed_wstr::<BO>(bytes)
+    } else {
+        fixed_str(bytes, true)
+    }
+}
+
+fn read_ascii(file: &[u8], offset: usize) -> Result<String, ParamDefError> {
+    Ok(fixed_str(
+        file.get(offset..).ok_or(ParamDefError::InvalidData)?,
+        false,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FieldType, ParamDef};
+
+    fn fixed(bytes: &mut Vec<u8>, value: &str, size: usize) {
+        let start = bytes.len();
+        bytes.extend_from_slice(value.as_bytes());
+        bytes.resize(start + size, 0);
+    }
+
+    /// Writes a little endian, single byte string paramdef with format version 104.
+    fn binary_paramdef(fields: &[(&str, &str)], description: &str) -> Vec<u8> {
+        const HEADER_SIZE: usize = 0x30;
+        const FIELD_SIZE: usize = 0xB0;
+        let description_offset = HEADER_SIZE + fields.len() * FIELD_SIZE;
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        bytes.extend_from_slice(&(HEADER_SIZE as u16).to_le_bytes());
+        bytes.extend_from_slice(&3u16.to_le_bytes());
+        bytes.extend_from_slice(&(fields.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&(FIELD_SIZE as u16).to_le_bytes());
+        fixed(&mut bytes, "TEST_PARAM_ST", 0x20);
+        bytes.extend_from_slice(&[0, 0]);
+        bytes.extend_from_slice(&104u16.to_le_bytes());
+
+        for (index, (display_type, internal_name)) in fields.iter().enumerate() {
+            fixed(&mut bytes, internal_name, 0x40);
+            fixed(&mut bytes, display_type, 8);
+            fixed(&mut bytes, "%d", 8);
+            for value in [index as f32, 0.0, 100.0, 1.0] {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+            bytes.extend_from_slice(&1u32.to_le_bytes());
+            bytes.extend_from_slice(&0u32.to_le_bytes());
+            bytes.extend_from_slice(&(description_offset as u32).to_le_bytes());
+            fixed(&mut bytes, display_type, 0x20);
+            fixed(&mut bytes, internal_name, 0x20);
+            bytes.extend_from_slice(&(index as i32 * 100).to_le_bytes());
+        }
+
+        fixed(&mut bytes, description, description.len() + 1);
+        let file_size = bytes.len() as u32;
+        bytes[..4].copy_from_slice(&file_size.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    pub fn parses_binary_paramdefs() {
+        let bytes = binary_paramdef(
+            &[
+                ("s32", "id"),
+                ("u8", "flagA:1"),
+                ("u8", "flagB:3"),
+                ("dummy8", "padBits:4"),
+                ("u16", "value"),
+                ("dummy8", "pad[2]"),
+                ("f32", "rate"),
+            ],
+            "Description",
+        );
+
+        let paramdef = ParamDef::parse(&bytes).expect("valid paramdef");
+        assert_eq!(paramdef.param_type, "TEST_PARAM_ST");
+        assert_eq!(paramdef.data_version, 3);
+        assert_eq!(paramdef.format_version, 104);
+        assert!(!paramdef.big_endian && !paramdef.unicode);
+        assert_eq!(paramdef.fields().len(), 7);
+        assert_eq!(paramdef.row_size(), 13);
+
+        let flag_b = paramdef.field("flagB").expect("bitfield");
+        assert_eq!(flag_b.display_type, FieldType::U8);
+        assert_eq!(flag_b.bit_size, Some(3));
+        assert_eq!((flag_b.byte_offset(), flag_b.bit_offset()), (4, Some(1)));
+        assert_eq!(flag_b.default, 2.0);
+        assert_eq!(flag_b.sort_id, 200);
+        assert_eq!(flag_b.description.as_deref(), Some("Description"));
+
+        let pad = paramdef.field("pad").expect("array");
+        assert_eq!(pad.array_length, 2);
+        assert_eq!((pad.byte_offset(), pad.size()), (7, 2));
+        assert_eq!(paramdef.field("rate").map(|f| f.byte_offset()), Some(9));
+
+        assert!(ParamDef::parse(&bytes[..0x100]).is_err());
+    }
+}
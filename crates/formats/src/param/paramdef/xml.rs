@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use quick_xml::{events::Event, Reader};
+
+use crate::param::paramdef::{FieldType, ParamDef, ParamDefError, ParamDefField};
+
+impl From<quick_xml::Error> for ParamDefError {
+    fn from(value: quick_xml::Error) -> Self {
+        ParamDefError::Xml(value.to_string())
+    }
+}
+
+/// Parses a Paramdex-style XML paramdef:
+///
+/// ```xml
+/// <PARAMDEF XmlVersion="3">
+///   <ParamType>EQUIP_PARAM_WEAPON_ST</ParamType>
+///   <DataVersion>2</DataVersion>
+///   <BigEndian>False</BigEndian>
+///   <Unicode>True</Unicode>
+///   <FormatVersion>203</FormatVersion>
+///   <Fields>
+///     <Field Def="s32 behaviorVariationId">
+///       <DisplayName>行動バリエーションID</DisplayName>
+///     </Field>
+///     <Field Def="u8 isNormalAttackType:1" />
+///     <Field Def="dummy8 pad[3]" />
+///   </Fields>
+/// </PARAMDEF>
+/// ```
+pub(super) fn from_xml(xml: &str) -> Result<ParamDef, ParamDefError> {
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+
+    let mut path: Vec<String> = Vec::new();
+    let mut properties: HashMap<String, String> = HashMap::new();
+    let mut field: Option<(String, HashMap<String, String>)> = None;
+    let mut fields = Vec::new();
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(element) => {
+                let name = String::from_utf8_lossy(element.name().as_ref()).into_owned();
+                if name == "Field" {
+                    field = Some((field_def(&element)?, HashMap::new()));
+                }
+
+                path.push(name);
+            }
+            Event::Empty(element) if element.name().as_ref() == b"Field" => {
+                fields.push(parse_field(&field_def(&element)?, &HashMap::new())?);
+            }
+            Event::Text(text) => {
+                let Some(name) = path.last() else {
+                    continue;
+                };
+
+                let value = text.unescape()?.into_owned();
+                match &mut field {
+                    Some((_, field_properties)) => field_properties.insert(name.clone(), value),
+                    None => properties.insert(name.clone(), value),
+                };
+            }
+            Event::End(element) => {
+                path.pop();
+                if element.name().as_ref() == b"Field" {
+                    if let Some((def, field_properties)) = field.take() {
+                        fields.push(parse_field(&def, &field_properties)?);
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    let param_type = properties
+        .get("ParamType")
+        .cloned()
+        .ok_or_else(|| ParamDefError::Xml("missing ParamType".to_string()))?;
+
+    Ok(ParamDef::new(
+        param_type,
+        parse_or(&properties, "DataVersion", 0)?,
+        parse_bool(&properties, "BigEndian")?,
+        parse_bool(&properties, "Unicode")?,
+        parse_or(&properties, "FormatVersion", 0)?,
+        fields,
+    ))
+}
+
+fn field_def(element: &quick_xml::events::BytesStart<'_>) -> Result<String, ParamDefError> {
+    let def = element
+        .try_get_attribute("Def")?
+        .ok_or_else(|| ParamDefError::Xml("Field is missing a Def attribute".to_string()))?;
+
+    Ok(def.unescape_value()?.into_owned())
+}
+
+/// Builds a field from a `Def` attribute of the form `type name[:bits|[length]] [= default]`
+/// and the child elements of the field.
+fn parse_field(
+    def: &str,
+    properties: &HashMap<String, String>,
+) -> Result<ParamDefField, ParamDefError> {
+    let invalid = || ParamDefError::InvalidFieldDefinition(def.to_string());
+
+    let (display_type, rest) = def
+        .trim()
+        .split_once(char::is_whitespace)
+        .ok_or_else(invalid)?;
+    let (internal_name, default) = match rest.split_once('=') {
+        Some((name, default)) => (name, Some(default.trim())),
+        None => (rest, None),
+    };
+
+    let display_type: FieldType = display_type.parse()?;
+    let mut field = ParamDefField::new(display_type, String::new());
+    field.apply_internal_name(internal_name)?;
+
+    if let Some(default) = default {
+        field.default = default.parse().map_err(|_| invalid())?;
+    }
+
+    field.display_name = properties
+        .get("DisplayName")
+        .cloned()
+        .unwrap_or_else(|| field.internal_name.clone());
+    if let Some(internal_type) = properties.get("Enum") {
+        field.internal_type.clone_from(internal_type);
+    }
+    if let Some(display_format) = properties.get("DisplayFormat") {
+        field.display_format.clone_from(display_format);
+    }
+    if let Some(edit_flags) = properties.get("EditFlags") {
+        field.edit_flags = parse_edit_flags(edit_flags).ok_or_else(invalid)?;
+    }
+
+    field.description = properties.get("Description").cloned();
+    field.default = parse_or(properties, "Default", field.default)?;
+    field.minimum = parse_or(properties, "Minimum", field.minimum)?;
+    field.maximum = parse_or(properties, "Maximum", field.maximum)?;
+    field.increment = parse_or(properties, "Increment", field.increment)?;
+    field.sort_id = parse_or(properties, "SortID", field.sort_id)?;
+
+    Ok(field)
+}
+
+/// Parses either a numeric value or a comma separated list of flag names.
+fn parse_edit_flags(value: &str) -> Option<u32> {
+    if let Ok(flags) = value.trim().parse() {
+        return Some(flags);
+    }
+
+    value.split(',').try_fold(0, |flags, name| {
+        Some(
+            flags
+                | match name.trim() {
+                    "None" => 0,
+                    "Wrap" => 1,
+                    "Lock" => 4,
+                    _ => return None,
+                },
+        )
+    })
+}
+
+fn parse_or<T: std::str::FromStr>(
+    properties: &HashMap<String, String>,
+    name: &str,
+    default: T,
+) -> Result<T, ParamDefError> {
+    match properties.get(name) {
+        Some(value) => value
+            .trim()
+            .parse()
+            .map_err(|_| ParamDefError::Xml(format!("invalid value {value:?} for {name}"))),
+        None => Ok(default),
+    }
+}
+
+fn parse_bool(properties: &HashMap<String, String>, name: &str) -> Result<bool, ParamDefError> {
+    match properties.get(name).map(|value| value.trim()) {
+        Some(value) if value.eq_ignore_ascii_case("true") => Ok(true),
+        Some(value) if value.eq_ignore_ascii_case("false") => Ok(false),
+        None => Ok(false),
+        Some(value) => Err(ParamDefError::Xml(format!(
+            "invalid value {value:?} for {name}"
+        ))),
+    }
+}
@@ -0,0 +1,586 @@
+use std::{
+    borrow::Cow,
+    io::{Read, Write},
+};
+
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+
+use crate::param::{
+    paramdef::{FieldType, ParamDef, ParamDefField},
+    typed_row::write_field,
+    FieldValue, ParamBuilder, ParamRow, TypedRow,
+};
+
+const ID_COLUMN: &str = "ID";
+const NAME_COLUMN: &str = "Name";
+const DATA_COLUMN: &str = "Data";
+
+#[derive(Debug, Error)]
+pub enum ParamTextError {
+    #[error("Could not read or write CSV {0}")]
+    Csv(#[from] csv::Error),
+
+    #[error("Could not read or write JSON {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Missing column or property {0:?}")]
+    Missing(&'static str),
+
+    #[error("Unknown field {0:?}")]
+    UnknownField(String),
+
+    #[error("Invalid value {value:?} for {column} in row {row}")]
+    InvalidValue {
+        row: usize,
+        column: String,
+        value: String,
+    },
+
+    #[error("Row {0} is smaller than the row size of the paramdef")]
+    RowTooSmall(u32),
+}
+
+/// Exports params to CSV and JSON and imports them back.
+///
+/// With a [`ParamDef`], each field of a row gets its own column (or JSON property) named after
+/// the internal name of the field. Fields sharing the name of an earlier field are named
+/// `name#index` instead, with the index of the field in the paramdef. Without a paramdef, the
+/// row data is exported as a single hex string. Bytes past the row size of the paramdef are not
+/// exported.
+impl ParamBuilder {
+    /// Writes the rows of this param as CSV, with an `ID` and `Name` column followed by either
+    /// one column per field of `paramdef` or a `Data` column.
+    pub fn write_csv<W: Write>(
+        &self,
+        paramdef: Option<&ParamDef>,
+        writer: W,
+    ) -> Result<(), ParamTextError> {
+        let mut csv = csv::Writer::from_writer(writer);
+
+        let mut header = vec![ID_COLUMN.to_string(), NAME_COLUMN.to_string()];
+        match paramdef {
+            Some(paramdef) => header.extend(
+                paramdef
+                    .fields()
+                    .iter()
+                    .enumerate()
+                    .map(|(index, field)| field_key(paramdef, index, field)),
+            ),
+            None => header.push(DATA_COLUMN.to_string()),
+        }
+        csv.write_record(&header)?;
+
+        for row in self.rows() {
+            let mut record = vec![row.id.to_string(), row.name.clone().unwrap_or_default()];
+            match paramdef {
+                Some(paramdef) => record.extend(
+                    self.typed_row(paramdef, row)?
+                        .fields()
+                        .map(|(_, value)| value.to_string()),
+                ),
+                None => record.push(FieldValue::Dummy8(&row.data).to_string()),
+            }
+            csv.write_record(&record)?;
+        }
+
+        csv.flush().map_err(csv::Error::from)?;
+        Ok(())
+    }
+
+    /// Replaces the rows of this param with the ones in a CSV file in the format written by
+    /// [`ParamBuilder::write_csv`].
+    ///
+    /// Columns are matched to fields by name, in any order. Fields without a column are set to
+    /// their default value, and columns that don't name a field are an error. Empty names are
+    /// imported as rows without a name.
+    pub fn read_csv<R: Read>(
+        &mut self,
+        paramdef: Option<&ParamDef>,
+        reader: R,
+    ) -> Result<(), ParamTextError> {
+        let mut csv = csv::Reader::from_reader(reader);
+        let header = csv.headers()?.clone();
+        let columns: Vec<&str> = header.iter().collect();
+
+        let id_column = column_index(&columns, ID_COLUMN)?;
+        let name_column = column_index(&columns, NAME_COLUMN)?;
+
+        let field_columns = columns
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index != id_column && *index != name_column)
+            .filter_map(|(index, column)| match paramdef {
+                Some(paramdef) => Some(
+                    key_field(paramdef, column)
+                        .map(|field| (index, field))
+                        .ok_or_else(|| ParamTextError::UnknownField(column.to_string())),
+                ),
+                None if *column == DATA_COLUMN => None,
+                None => Some(Err(ParamTextError::UnknownField(column.to_string()))),
+            })
+            .collect::<Result<Vec<_>, ParamTextError>>()?;
+
+        let mut rows = Vec::new();
+        for (row_index, record) in csv.records().enumerate() {
+            let record = record?;
+            let cell = |index: usize| record.get(index).unwrap_or_default();
+
+            let id = parse_id(row_index, cell(id_column))?;
+            let name = Some(cell(name_column))
+                .filter(|name| !name.is_empty())
+                .map(str::to_string);
+
+            let data = match paramdef {
+                Some(paramdef) => {
+                    let mut data = default_row(paramdef, self.big_endian);
+                    for (index, field) in &field_columns {
+                        write_text(field, &mut data, self.big_endian, cell(*index)).ok_or_else(
+                            || invalid_value(row_index, columns[*index], cell(*index)),
+                        )?;
+                    }
+                    data
+                }
+                None => {
+                    let index = column_index(&columns, DATA_COLUMN)?;
+                    from_hex(cell(index))
+                        .ok_or_else(|| invalid_value(row_index, DATA_COLUMN, cell(index)))?
+                }
+            };
+
+            rows.push(ParamRow { id, name, data });
+        }
+
+        *self.rows_mut() = rows;
+        Ok(())
+    }
+
+    /// Converts this param to a JSON value of the form
+    /// `{ "param_type": ..., "rows": [{ "id": ..., "name": ..., "fields": { ... } }] }`,
+    /// where `fields` is replaced by a hex `data` string if no paramdef is given.
+    ///
+    /// Infinite and NaN floats are written as the strings `inf`, `-inf` and `NaN`.
+    pub fn to_json(&self, paramdef: Option<&ParamDef>) -> Result<Value, ParamTextError> {
+        let rows = self
+            .rows()
+            .iter()
+            .map(|row| {
+                let mut object = Map::new();
+                object.insert("id".to_string(), json!(row.id));
+                object.insert("name".to_string(), json!(row.name));
+                match paramdef {
+                    Some(paramdef) => {
+                        let fields = self
+                            .typed_row(paramdef, row)?
+                            .fields()
+                            .enumerate()
+                            .map(|(index, (field, value))| {
+                                (field_key(paramdef, index, field), json_value(&value))
+                            })
+                            .collect();
+                        object.insert("fields".to_string(), Value::Object(fields));
+                    }
+                    None => {
+                        let data = FieldValue::Dummy8(&row.data).to_string();
+                        object.insert("data".to_string(), Value::String(data));
+                    }
+                }
+                Ok(Value::Object(object))
+            })
+            .collect::<Result<Vec<_>, ParamTextError>>()?;
+
+        Ok(json!({
+            "param_type": self.param_type,
+            "rows": rows,
+        }))
+    }
+
+    /// Writes this param as pretty-printed JSON. See [`ParamBuilder::to_json`].
+    pub fn write_json<W: Write>(
+        &self,
+        paramdef: Option<&ParamDef>,
+        writer: W,
+    ) -> Result<(), ParamTextError> {
+        serde_json::to_writer_pretty(writer, &self.to_json(paramdef)?)?;
+        Ok(())
+    }
+
+    /// Replaces the rows of this param with the ones in a JSON value in the format produced by
+    /// [`ParamBuilder::to_json`].
+    ///
+    /// Fields without a property are set to their default value.
+    pub fn import_json(
+        &mut self,
+        paramdef: Option<&ParamDef>,
+        value: &Value,
+    ) -> Result<(), ParamTextError> {
+        if let Some(param_type) = value.get("param_type").and_then(Value::as_str) {
+            self.param_type = param_type.to_string();
+        }
+
+        let rows = value
+            .get("rows")
+            .and_then(Value::as_array)
+            .ok_or(ParamTextError::Missing("rows"))?;
+
+        *self.rows_mut() = rows
+            .iter()
+            .enumerate()
+            .map(|(row_index, row)| {
+                let id = row
+                    .get("id")
+                    .and_then(Value::as_u64)
+                    .and_then(|id| u32::try_from(id).ok())
+                    .ok_or_else(|| {
+                        invalid_value(
+                            row_index,
+                            "id",
+                            &json_text(row.get("id")).unwrap_or_default(),
+                        )
+                    })?;
+                let name = row.get("name").and_then(Value::as_str).map(str::to_string);
+
+                let data = match paramdef {
+                    Some(paramdef) => {
+                        let mut data = default_row(paramdef, self.big_endian);
+                        let fields = row
+                            .get("fields")
+                            .and_then(Value::as_object)
+                            .ok_or(ParamTextError::Missing("fields"))?;
+
+                        for (name, value) in fields {
+                            let field = key_field(paramdef, name)
+                                .ok_or_else(|| ParamTextError::UnknownField(name.clone()))?;
+                            let text = json_text(Some(value)).unwrap_or_default();
+                            write_text(field, &mut data, self.big_endian, &text)
+                                .ok_or_else(|| invalid_value(row_index, name, &text))?;
+                        }
+                        data
+                    }
+                    None => {
+                        let text = row
+                            .get("data")
+                            .and_then(Value::as_str)
+                            .ok_or(ParamTextError::Missing("data"))?;
+                        from_hex(text).ok_or_else(|| invalid_value(row_index, "data", text))?
+                    }
+                };
+
+                Ok(ParamRow { id, name, data })
+            })
+            .collect::<Result<_, ParamTextError>>()?;
+
+        Ok(())
+    }
+
+    /// Replaces the rows of this param with the ones in a JSON file. See
+    /// [`ParamBuilder::import_json`].
+    pub fn read_json<R: Read>(
+        &mut self,
+        paramdef: Option<&ParamDef>,
+        reader: R,
+    ) -> Result<(), ParamTextError> {
+        let value: Value = serde_json::from_reader(reader)?;
+        self.import_json(paramdef, &value)
+    }
+
+    fn typed_row<'a>(
+        &self,
+        paramdef: &'a ParamDef,
+        row: &'a ParamRow,
+    ) -> Result<TypedRow<'a>, ParamTextError> {
+        TypedRow::new(paramdef, &row.data, self.big_endian)
+            .ok_or(ParamTextError::RowTooSmall(row.id))
+    }
+}
+
+fn column_index(columns: &[&str], name: &'static str) -> Result<usize, ParamTextError> {
+    columns
+        .iter()
+        .position(|column| *column == name)
+        .ok_or(ParamTextError::Missing(name))
+}
+
+fn invalid_value(row: usize, column: &str, value: &str) -> ParamTextError {
+    ParamTextError::InvalidValue {
+        row,
+        column: column.to_string(),
+        value: value.to_string(),
+    }
+}
+
+fn parse_id(row: usize, text: &str) -> Result<u32, ParamTextError> {
+    text.trim()
+        .parse()
+        .map_err(|_| invalid_value(row, ID_COLUMN, text))
+}
+
+/// Creates the data of a row with every field set to its default value.
+fn default_row(paramdef: &ParamDef, big_endian: bool) -> Vec<u8> {
+    let mut data = vec![0; paramdef.row_size()];
+    for field in paramdef.fields() {
+        let default = match field.display_type {
+            FieldType::F32 | FieldType::Angle32 | FieldType::F64 => field.default.to_string(),
+            _ => (field.default as i64).to_string(),
+        };
+
+        // Defaults that don't fit the field (e.g. on padding) are left zeroed
+        let _ = write_text(field, &mut data, big_endian, &default);
+    }
+
+    data
+}
+
+/// Parses a textual field value and writes it to the row data.
+fn write_text(field: &ParamDefField, data: &mut [u8], big_endian: bool, text: &str) -> Option<()> {
+    let trimmed = text.trim();
+    let bytes;
+    let value = if field.bit_size.is_some() {
+        FieldValue::U32(trimmed.parse().ok()?)
+    } else {
+        match field.display_type {
+            FieldType::Dummy8 => {
+                bytes = from_hex(trimmed)?;
+                FieldValue::Dummy8(&bytes)
+            }
+            FieldType::U8 => FieldValue::U8(trimmed.parse().ok()?),
+            FieldType::S8 => FieldValue::S8(trimmed.parse().ok()?),
+            FieldType::U16 => FieldValue::U16(trimmed.parse().ok()?),
+            FieldType::S16 => FieldValue::S16(trimmed.parse().ok()?),
+            FieldType::U32 => FieldValue::U32(trimmed.parse().ok()?),
+            FieldType::S32 => FieldValue::S32(trimmed.parse().ok()?),
+            FieldType::B32 => FieldValue::B32(match trimmed.to_ascii_lowercase().as_str() {
+                "1" | "true" => true,
+                "0" | "false" => false,
+                _ => return None,
+            }),
+            FieldType::F32 => FieldValue::F32(trimmed.parse().ok()?),
+            FieldType::Angle32 => FieldValue::Angle32(trimmed.parse().ok()?),
+            FieldType::F64 => FieldValue::F64(trimmed.parse().ok()?),
+            FieldType::FixStr => FieldValue::FixStr(Cow::Borrowed(text)),
+            FieldType::FixStrW => FieldValue::FixStrW(Cow::Borrowed(text)),
+        }
+    };
+
+    write_field(field, data, big_endian, &value).then_some(())
+}
+
+/// Column or JSON property name of the field at `index`, unique even if fields share a name.
+fn field_key(paramdef: &ParamDef, index: usize, field: &ParamDefField) -> String {
+    let first = paramdef.field(&field.internal_name);
+    if first.is_some_and(|first| std::ptr::eq(first, field)) {
+        field.internal_name.clone()
+    } else {
+        format!("{}#{index}", field.internal_name)
+    }
+}
+
+/// Finds the field of a column or property name written by [`field_key`].
+fn key_field<'a>(paramdef: &'a ParamDef, key: &str) -> Option<&'a ParamDefField> {
+    key.rsplit_once('#')
+        .and_then(|(name, index)| {
+            let field = paramdef.fields().get(index.parse::<usize>().ok()?)?;
+            (field.internal_name == name).then_some(field)
+        })
+        .or_else(|| paramdef.field(key))
+}
+
+fn json_value(value: &FieldValue<'_>) -> Value {
+    match value {
+        FieldValue::B32(v) => Value::Bool(*v),
+        FieldValue::FixStr(v) | FieldValue::FixStrW(v) => Value::String(v.to_string()),
+        FieldValue::Dummy8(_) => Value::String(value.to_string()),
+        // Go through the shortest round-trip representation so that floats don't gain
+        // spurious digits when widened to f64. JSON has no infinite or NaN numbers, so those
+        // are kept as strings
+        _ => {
+            let text = value.to_string();
+            text.parse::<serde_json::Number>()
+                .map_or(Value::String(text), Value::Number)
+        }
+    }
+}
+
+fn json_text(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(text) => Some(text.clone()),
+        Value::Null => None,
+        value => Some(value.to_string()),
+    }
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim();
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::param::{paramdef::ParamDef, text::ParamTextError, ParamBuilder, ParamRow};
+
+    const PARAMDEF: &str = r#"<PARAMDEF XmlVersion="3">
+  <ParamType>TEST_PARAM_ST</ParamType>
+  <Fields>
+    <Field Def="s32 value = 7" />
+    <Field Def="u8 flag:1" />
+    <Field Def="dummy8 pad:7" />
+    <Field Def="b32 enabled" />
+    <Field Def="f32 rate = 1" />
+    <Field Def="fixstr label[4]" />
+  </Fields>
+</PARAMDEF>"#;
+
+    #[test]
+    pub fn round_trips_csv_and_json() {
+        let paramdef = ParamDef::from_xml(PARAMDEF).expect("valid paramdef");
+        let mut param = ParamBuilder::new("TEST_PARAM_ST", false, true, true);
+        param
+            .read_csv(
+                Some(&paramdef),
+                "ID,Name,value,flag,enabled,label\n10,Ten,-3,1,true,ab\n20,,5,0,0,\n".as_bytes(),
+            )
+            .expect("valid csv");
+
+        assert_eq!(param.rows().len(), 2);
+        assert_eq!(param.rows()[1].name, None);
+        let mut expected = (-3i32).to_le_bytes().to_vec();
+        expected.extend([1, 1, 0, 0, 0]);
+        expected.extend(1f32.to_le_bytes());
+        expected.extend(b"ab\0\0");
+        assert_eq!(param.rows()[0].data, expected);
+
+        let mut csv = Vec::new();
+        param
+            .write_csv(Some(&paramdef), &mut csv)
+            .expect("writable");
+        let csv = String::from_utf8(csv).expect("utf8 csv");
+        assert!(
+            csv.starts_with("ID,Name,value,flag,pad,enabled,rate,label\n10,Ten,-3,1,0,1,1,ab\n")
+        );
+
+        let mut from_csv = param.clone();
+        from_csv
+            .read_csv(Some(&paramdef), csv.as_bytes())
+            .expect("valid csv");
+        assert_eq!(from_csv.rows(), param.rows());
+
+        let json = param.to_json(Some(&paramdef)).expect("rows large enough");
+        assert_eq!(json["rows"][0]["fields"]["enabled"], true);
+        let mut from_json = param.clone();
+        from_json
+            .import_json(Some(&paramdef), &json)
+            .expect("valid json");
+        assert_eq!(from_json.rows(), param.rows());
+
+        let untyped = param.to_json(None).expect("untyped export");
+        let mut from_untyped = param.clone();
+        from_untyped
+            .import_json(None, &untyped)
+            .expect("valid json");
+        assert_eq!(
+            from_untyped.rows().first(),
+            Some(&ParamRow {
+                id: 10,
+                name: Some("Ten".to_string()),
+                data: expected,
+            })
+        );
+    }
+
+    #[test]
+    pub fn maps_csv_columns_by_name() {
+        let paramdef = ParamDef::from_xml(PARAMDEF).expect("valid paramdef");
+        let mut param = ParamBuilder::new("TEST_PARAM_ST", false, true, true);
+        param
+            .read_csv(
+                Some(&paramdef),
+                "label,Name,value,ID\nab,Ten,-3,10\n".as_bytes(),
+            )
+            .expect("valid csv");
+
+        let mut expected = (-3i32).to_le_bytes().to_vec();
+        expected.extend([0, 0, 0, 0, 0]);
+        expected.extend(1f32.to_le_bytes());
+        expected.extend(b"ab\0\0");
+        assert_eq!(
+            param.rows(),
+            [ParamRow {
+                id: 10,
+                name: Some("Ten".to_string()),
+                data: expected,
+            }]
+        );
+
+        for (paramdef, csv, unknown) in [
+            (
+                Some(&paramdef),
+                "ID,Name,value,speed\n10,Ten,-3,1\n",
+                "speed",
+            ),
+            (None, "ID,Name,Data,value\n10,Ten,00,1\n", "value"),
+        ] {
+            assert!(matches!(
+                param.read_csv(paramdef, csv.as_bytes()),
+                Err(ParamTextError::UnknownField(column)) if column == unknown
+            ));
+        }
+    }
+
+    #[test]
+    pub fn round_trips_duplicate_names() {
+        let paramdef = ParamDef::from_xml(
+            r#"<PARAMDEF XmlVersion="3">
+  <ParamType>TEST_PARAM_ST</ParamType>
+  <Fields>
+    <Field Def="u8 unk" />
+    <Field Def="u8 unk" />
+    <Field Def="f32 rate" />
+    <Field Def="f32 scale" />
+  </Fields>
+</PARAMDEF>"#,
+        )
+        .expect("valid paramdef");
+
+        let mut data = vec![1, 2];
+        data.extend(f32::NAN.to_le_bytes());
+        data.extend(f32::NEG_INFINITY.to_le_bytes());
+        let mut param = ParamBuilder::new("TEST_PARAM_ST", false, true, true);
+        param.add_row(ParamRow {
+            id: 1,
+            name: None,
+            data,
+        });
+
+        let json = param.to_json(Some(&paramdef)).expect("rows large enough");
+        let fields = &json["rows"][0]["fields"];
+        assert_eq!(fields["unk"], 1);
+        assert_eq!(fields["unk#1"], 2);
+        assert_eq!(fields["rate"], "NaN");
+        assert_eq!(fields["scale"], "-inf");
+
+        let text = serde_json::to_string(&json).expect("serializable");
+        let mut from_json = param.clone();
+        from_json
+            .read_json(Some(&paramdef), text.as_bytes())
+            .expect("valid json");
+        assert_eq!(from_json.rows(), param.rows());
+
+        let mut csv = Vec::new();
+        param
+            .write_csv(Some(&paramdef), &mut csv)
+            .expect("writable");
+        assert!(csv.starts_with(b"ID,Name,unk,unk#1,rate,scale\n"));
+        let mut from_csv = param.clone();
+        from_csv
+            .read_csv(Some(&paramdef), csv.as_slice())
+            .expect("valid csv");
+        assert_eq!(from_csv.rows()[0].data[..2], [1, 2]);
+    }
+}
@@ -0,0 +1,299 @@
+use std::{borrow::Cow, fmt};
+
+use byteorder::{ByteOrder, BE, LE};
+use encoding_rs::SHIFT_JIS;
+
+use crate::param::paramdef::{fixed_str, fixed_wstr, FieldType, ParamDef, ParamDefField};
+
+/// The value of a single field of a param row.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldValue<'a> {
+    U8(u8),
+    S8(i8),
+    U16(u16),
+    S16(i16),
+    U32(u32),
+    S32(i32),
+    B32(bool),
+    F32(f32),
+    Angle32(f32),
+    F64(f64),
+    FixStr(Cow<'a, str>),
+    FixStrW(Cow<'a, str>),
+    Dummy8(&'a [u8]),
+}
+
+impl<'a> FieldValue<'a> {
+    /// Returns this value as a 64-bit float, if it is numeric.
+    pub fn as_f64(&self) -> Option<f64> {
+        Some(match *self {
+            FieldValue::U8(v) => v as f64,
+            FieldValue::S8(v) => v as f64,
+            FieldValue::U16(v) => v as f64,
+            FieldValue::S16(v) => v as f64,
+            FieldValue::U32(v) => v as f64,
+            FieldValue::S32(v) => v as f64,
+            FieldValue::B32(v) => v as u8 as f64,
+            FieldValue::F32(v) | FieldValue::Angle32(v) => v as f64,
+            FieldValue::F64(v) => v,
+            FieldValue::FixStr(_) | FieldValue::FixStrW(_) | FieldValue::Dummy8(_) => return None,
+        })
+    }
+
+    /// Returns this value as a 64-bit signed integer, if it is an integer.
+    pub fn as_i64(&self) -> Option<i64> {
+        Some(match *self {
+            FieldValue::U8(v) => v as i64,
+            FieldValue::S8(v) => v as i64,
+            FieldValue::U16(v) => v as i64,
+            FieldValue::S16(v) => v as i64,
+            FieldValue::U32(v) => v as i64,
+            FieldValue::S32(v) => v as i64,
+            FieldValue::B32(v) => v as i64,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for FieldValue<'_> {
+    /// Formats the value the way it is exported to text: numbers in their shortest round-trip
+    /// form, booleans as `0` or `1` and padding bytes as hex.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::U8(v) => v.fmt(f),
+            FieldValue::S8(v) => v.fmt(f),
+            FieldValue::U16(v) => v.fmt(f),
+            FieldValue::S16(v) => v.fmt(f),
+            FieldValue::U32(v) => v.fmt(f),
+            FieldValue::S32(v) => v.fmt(f),
+            FieldValue::B32(v) => (*v as u8).fmt(f),
+            FieldValue::F32(v) | FieldValue::Angle32(v) => v.fmt(f),
+            FieldValue::F64(v) => v.fmt(f),
+            FieldValue::FixStr(v) | FieldValue::FixStrW(v) => v.fmt(f),
+            FieldValue::Dummy8(bytes) => bytes.iter().try_for_each(|b| write!(f, "{b:02x}")),
+        }
+    }
+}
+
+/// A view over the data of a single param row that reads fields by name using a [`ParamDef`].
+pub struct TypedRow<'a> {
+    paramdef: &'a ParamDef,
+    data: &'a [u8],
+    big_endian: bool,
+}
+
+impl<'a> TypedRow<'a> {
+    /// Create a typed view of the given row data.
+    ///
+    /// Returns [`None`] if the row data is smaller than the row size described by the paramdef.
+    pub fn new(paramdef: &'a ParamDef, data: &'a [u8], big_endian: bool) -> Option<Self> {
+        (data.len() >= paramdef.row_size()).then_some(Self {
+            paramdef,
+            data,
+            big_endian,
+        })
+    }
+
+    pub fn paramdef(&self) -> &'a ParamDef {
+        self.paramdef
+    }
+
+    /// The raw data of this row.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Reads the value of the field with the given internal name.
+    pub fn get(&self, name: &str) -> Option<FieldValue<'a>> {
+        self.paramdef
+            .field(name)
+            .map(|field| self.read_field(field))
+    }
+
+    /// Iterates over each field of the row and its value, in layout order.
+    pub fn fields(&self) -> impl Iterator<Item = (&'a ParamDefField, FieldValue<'a>)> + '_ {
+        self.paramdef
+            .fields()
+            .iter()
+            .map(|field| (field, self.read_field(field)))
+    }
+
+    /// Reads the value of a field. The field must belong to the paramdef of this row.
+    pub fn read_field(&self, field: &ParamDefField) -> FieldValue<'a> {
+        if self.big_endian {
+            read_field::<BE>(field, self.data)
+        } else {
+            read_field::<LE>(field, self.data)
+        }
+    }
+}
+
+/// Writes the value of a field to the data of a row. The field must belong to the paramdef the
+/// row data was created for.
+///
+/// Returns false if the value does not match the type of the field, does not fit in a bitfield,
+/// or if the row data is too small.
+pub fn write_field(
+    field: &ParamDefField,
+    data: &mut [u8],
+    big_endian: bool,
+    value: &FieldValue<'_>,
+) -> bool {
+    if big_endian {
+        write_field_inner::<BE>(field, data, value)
+    } else {
+        write_field_inner::<LE>(field, data, value)
+    }
+}
+
+fn read_field<'a, BO: ByteOrder>(field: &ParamDefField, data: &'a [u8]) -> FieldValue<'a> {
+    let bytes = &data[field.byte_offset()..field.byte_offset() + field.size()];
+
+    if let (Some(bit_size), Some(bit_offset)) = (field.bit_size, field.bit_offset()) {
+        let unit = match bytes.len() {
+            1 => bytes[0] as u32,
+            2 => BO::read_u16(bytes) as u32,
+            _ => BO::read_u32(bytes),
+        };
+        let value = (unit >> bit_offset) & (u32::MAX >> (32 - bit_size as u32));
+
+        return match field.display_type {
+            FieldType::U16 => FieldValue::U16(value as u16),
+            FieldType::U32 => FieldValue::U32(value),
+            _ => FieldValue::U8(value as u8),
+        };
+    }
+
+    match field.display_type {
+        FieldType::Dummy8 => FieldValue::Dummy8(bytes),
+        FieldType::U8 => FieldValue::U8(bytes[0]),
+        FieldType::S8 => FieldValue::S8(bytes[0] as i8),
+        FieldType::U16 => FieldValue::U16(BO::read_u16(bytes)),
+        FieldType::S16 => FieldValue::S16(BO::read_i16(bytes)),
+        FieldType::U32 => FieldValue::U32(BO::read_u32(bytes)),
+        FieldType::S32 => FieldValue::S32(BO::read_i32(bytes)),
+        FieldType::B32 => FieldValue::B32(BO::read_u32(bytes) != 0),
+        FieldType::F32 => FieldValue::F32(BO::read_f32(bytes)),
+        FieldType::Angle32 => FieldValue::Angle32(BO::read_f32(bytes)),
+        FieldType::F64 => FieldValue::F64(BO::read_f64(bytes)),
+        FieldType::FixStr => FieldValue::FixStr(Cow::Owned(fixed_str(bytes, true))),
+        FieldType::FixStrW => FieldValue::FixStrW(Cow::Owned(fixed_wstr::<BO>(bytes))),
+    }
+}
+
+fn write_field_inner<BO: ByteOrder>(
+    field: &ParamDefField,
+    data: &mut [u8],
+    value: &FieldValue<'_>,
+) -> bool {
+    let Some(bytes) = data.get_mut(field.byte_offset()..field.byte_offset() + field.size()) else {
+        return false;
+    };
+
+    if let (Some(bit_size), Some(bit_offset)) = (field.bit_size, field.bit_offset()) {
+        let Some(value) = value.as_i64().and_then(|v| u32::try_from(v).ok()) else {
+            return false;
+        };
+        let mask = u32::MAX >> (32 - bit_size as u32);
+        if value > mask {
+            return false;
+        }
+
+        let unit = match bytes.len() {
+            1 => bytes[0] as u32,
+            2 => BO::read_u16(bytes) as u32,
+            _ => BO::read_u32(bytes),
+        };
+        let unit = (unit & !(mask << bit_offset)) | (value << bit_offset);
+        match bytes.len() {
+            1 => bytes[0] = unit as u8,
+            2 => BO::write_u16(bytes, unit as u16),
+            _ => BO::write_u32(bytes, unit),
+        }
+
+        return true;
+    }
+
+    match (field.display_type, value) {
+        (FieldType::Dummy8, FieldValue::Dummy8(v)) => {
+            if v.len() > bytes.len() {
+                return false;
+            }
+            bytes.fill(0);
+            bytes[..v.len()].copy_from_slice(v);
+        }
+        (FieldType::U8, FieldValue::U8(v)) => bytes[0] = *v,
+        (FieldType::S8, FieldValue::S8(v)) => bytes[0] = *v as u8,
+        (FieldType::U16, FieldValue::U16(v)) => BO::write_u16(bytes, *v),
+        (FieldType::S16, FieldValue::S16(v)) => BO::write_i16(bytes, *v),
+        (FieldType::U32, FieldValue::U32(v)) => BO::write_u32(bytes, *v),
+        (FieldType::S32, FieldValue::S32(v)) => BO::write_i32(bytes, *v),
+        (FieldType::B32, FieldValue::B32(v)) => BO::write_u32(bytes, *v as u32),
+        (FieldType::F32, FieldValue::F32(v)) | (FieldType::Angle32, FieldValue::Angle32(v)) => {
+            BO::write_f32(bytes, *v);
+        }
+        (FieldType::F64, FieldValue::F64(v)) => BO::write_f64(bytes, *v),
+        (FieldType::FixStr, FieldValue::FixStr(v)) => {
+            let encoded = SHIFT_JIS.encode(v).0;
+            let len = encoded.len().min(bytes.len());
+            bytes.fill(0);
+            bytes[..len].copy_from_slice(&encoded[..len]);
+        }
+        (FieldType::FixStrW, FieldValue::FixStrW(v)) => {
+            bytes.fill(0);
+            for (unit, dst) in v.encode_utf16().zip(bytes.chunks_exact_mut(2)) {
+                BO::write_u16(dst, unit);
+            }
+        }
+        _ => return false,
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FieldValue, TypedRow};
+    use crate::param::paramdef::ParamDef;
+
+    const PARAMDEF: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<PARAMDEF XmlVersion="3">
+  <ParamType>TEST_PARAM_ST</ParamType>
+  <DataVersion>1</DataVersion>
+  <BigEndian>False</BigEndian>
+  <Unicode>True</Unicode>
+  <FormatVersion>203</FormatVersion>
+  <Fields>
+    <Field Def="s32 id = -1">
+      <DisplayName>ID</DisplayName>
+    </Field>
+    <Field Def="u8 flagA:1" />
+    <Field Def="u8 flagB:3" />
+    <Field Def="dummy8 padBits:4" />
+    <Field Def="u16 value" />
+    <Field Def="dummy8 pad[2]" />
+    <Field Def="fixstr name[4]" />
+    <Field Def="f32 rate" />
+  </Fields>
+</PARAMDEF>"#;
+
+    #[test]
+    pub fn reads_bitfields_and_arrays() {
+        let paramdef = ParamDef::from_xml(PARAMDEF).expect("valid paramdef");
+        assert_eq!(paramdef.row_size(), 17);
+        assert_eq!(paramdef.field("id").map(|f| f.default), Some(-1.0));
+
+        let mut data = vec![0xFE, 0xFF, 0xFF, 0xFF, 0b0000_1011, 0x34, 0x12, 0, 0];
+        data.extend_from_slice(b"ab\0\0");
+        data.extend_from_slice(&2.5f32.to_le_bytes());
+
+        let row = TypedRow::new(&paramdef, &data, false).expect("row large enough");
+        assert_eq!(row.get("id"), Some(FieldValue::S32(-2)));
+        assert_eq!(row.get("flagA"), Some(FieldValue::U8(1)));
+        assert_eq!(row.get("flagB"), Some(FieldValue::U8(5)));
+        assert_eq!(row.get("value"), Some(FieldValue::U16(0x1234)));
+        assert_eq!(row.get("pad"), Some(FieldValue::Dummy8(&[0, 0])));
+        assert_eq!(row.get("name"), Some(FieldValue::FixStr("ab".into())));
+        assert_eq!(row.get("rate"), Some(FieldValue::F32(2.5)));
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::BTreeSet;
+
+use crate::param::{paramdef::ParamDef, ParamCommon};
+
+/// Problems found in the row layout of a param file. See [`ParamCommon::validate`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParamValidationReport {
+    /// Whether the row descriptors are sorted by strictly increasing ID. If not, lookups by ID
+    /// fall back to a hash map built on first use.
+    pub sorted: bool,
+    /// IDs shared by more than one row.
+    pub duplicate_ids: Vec<u32>,
+    /// Indices of rows whose data is not contained in the file.
+    pub out_of_bounds_rows: Vec<usize>,
+    /// Pairs of row indices whose data overlaps.
+    pub overlapping_rows: Vec<(usize, usize)>,
+    /// Whether the distance between the data of consecutive rows differs from the detected row
+    /// size somewhere in the file.
+    pub inconsistent_row_stride: bool,
+    /// The row size described by the paramdef and the detected row size of the file, if they
+    /// differ.
+    pub paramdef_row_size_mismatch: Option<(usize, Option<usize>)>,
+}
+
+impl ParamValidationReport {
+    /// Returns true if no problem was found.
+    pub fn is_valid(&self) -> bool {
+        self.sorted
+            && self.duplicate_ids.is_empty()
+            && self.out_of_bounds_rows.is_empty()
+            && self.overlapping_rows.is_empty()
+            && !self.inconsistent_row_stride
+            && self.paramdef_row_size_mismatch.is_none()
+    }
+}
+
+pub(super) fn validate<'a, P: ParamCommon<'a> + ?Sized>(
+    param: &P,
+    paramdef: Option<&ParamDef>,
+) -> ParamValidationReport {
+    let file_len = param.file_bytes().len() as u64;
+    let row_size = param.row_size();
+    let mut report = ParamValidationReport {
+        sorted: param.are_rows_sorted(),
+        ..Default::default()
+    };
+
+    let mut seen = BTreeSet::new();
+    let mut duplicates = BTreeSet::new();
+    let mut extents = Vec::with_capacity(param.row_count());
+
+    for (index, row) in param.dyn_rows().enumerate() {
+        if !seen.insert(row.id) {
+            duplicates.insert(row.id);
+        }
+
+        let end = row
+            .data_offset
+            .checked_add(row_size.unwrap_or_default() as u64);
+        if row.data.is_none() || end.is_none_or(|end| end > file_len) {
+            report.out_of_bounds_rows.push(index);
+        }
+        extents.push((row.data_offset, end.unwrap_or(u64::MAX), index));
+    }
+    report.duplicate_ids = duplicates.into_iter().collect();
+
+    extents.sort_unstable();
+    for pair in extents.windows(2) {
+        let ((start, end, index), (next_start, _, next_index)) = (pair[0], pair[1]);
+        if next_start < end || (next_start == start && row_size != Some(0)) {
+            report.overlapping_rows.push((index, next_index));
+        }
+        if let Some(row_size) = row_size {
+            if next_start - start != row_size as u64 {
+                report.inconsistent_row_stride = true;
+            }
+        }
+    }
+
+    if let Some(paramdef) = paramdef {
+        if row_size != Some(paramdef.row_size()) {
+            report.paramdef_row_size_mismatch = Some((paramdef.row_size(), row_size));
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use crate::param::{paramdef::ParamDef, parse_dyn, ParamBuilder, ParamRow};
+
+    /// Writes a param with rows of 4 bytes and the given IDs.
+    fn param(ids: &[u32]) -> Vec<u8> {
+        let mut param = ParamBuilder::new("TEST_PARAM_ST", false, true, true);
+        for &id in ids {
+            param.add_row(ParamRow {
+                id,
+                name: None,
+                data: vec![id as u8; 4],
+            });
+        }
+        param.to_bytes().expect("writable param")
+    }
+
+    /// Points the data of a row at another offset.
+    fn set_data_offset(bytes: &mut [u8], index: usize, offset: u64) {
+        let position = 0x40 + index * 24 + 8;
+        bytes[position..position + 8].copy_from_slice(&offset.to_le_bytes());
+    }
+
+    fn data_offset(bytes: &[u8], index: usize) -> u64 {
+        let position = 0x40 + index * 24 + 8;
+        u64::from_le_bytes(bytes[position..position + 8].try_into().expect("8 bytes"))
+    }
+
+    #[test]
+    pub fn looks_up_unsorted_rows() {
+        let mut bytes = param(&[1, 2, 3]);
+
+        // Swap the IDs of the first and last row descriptors
+        bytes[0x40] = 3;
+        bytes[0x40 + 2 * 24] = 1;
+
+        let param = parse_dyn(&bytes).expect("valid param");
+        let report = param.validate(None);
+        assert!(!report.sorted);
+        assert!(report.duplicate_ids.is_empty());
+        assert!(report.out_of_bounds_rows.is_empty());
+        assert!(report.overlapping_rows.is_empty());
+        assert!(!report.inconsistent_row_stride);
+
+        assert_eq!(param.index_of(3), Some(0));
+        assert_eq!(param.data_by_id(1), Some(&[3; 4][..]));
+        assert_eq!(param.data_by_id(2), Some(&[2; 4][..]));
+    }
+
+    #[test]
+    pub fn reports_duplicate_ids() {
+        let mut bytes = param(&[1, 2, 3]);
+        bytes[0x40 + 24] = 1;
+
+        let param = parse_dyn(&bytes).expect("valid param");
+        let report = param.validate(None);
+        assert!(!report.sorted);
+        assert_eq!(report.duplicate_ids, [1]);
+        assert!(report.overlapping_rows.is_empty());
+        assert!(!report.is_valid());
+    }
+
+    #[test]
+    pub fn reports_overlapping_and_out_of_bounds_rows() {
+        let mut bytes = param(&[1, 2, 3, 4]);
+        let first = data_offset(&bytes, 0);
+        set_data_offset(&mut bytes, 2, first + 6);
+        set_data_offset(&mut bytes, 3, u64::MAX - 1);
+
+        let param = parse_dyn(&bytes).expect("valid param");
+        let report = param.validate(None);
+        assert!(report.sorted);
+        assert!(report.duplicate_ids.is_empty());
+        assert_eq!(report.overlapping_rows, [(1, 2)]);
+        assert_eq!(report.out_of_bounds_rows, [3]);
+        assert!(report.inconsistent_row_stride);
+        assert_eq!(param.data_by_index(3), None);
+    }
+
+    #[test]
+    pub fn reports_paramdef_row_size_mismatch() {
+        let paramdef = ParamDef::from_xml(
+            r#"<PARAMDEF XmlVersion="3">
+  <ParamType>TEST_PARAM_ST</ParamType>
+  <Fields>
+    <Field Def="s32 a" />
+    <Field Def="s32 b" />
+  </Fields>
+</PARAMDEF>"#,
+        )
+        .expect("valid paramdef");
+        let bytes = param(&[1, 2, 3]);
+
+        let param = parse_dyn(&bytes).expect("valid param");
+        assert!(param.validate(None).is_valid());
+        let report = param.validate(Some(&paramdef));
+        assert_eq!(report.paramdef_row_size_mismatch, Some((8, Some(4))));
+        assert!(!report.is_valid());
+    }
+}
@@ -1,39 +1,371 @@
-use std::io::{self, SeekFrom};
+use std::{
+    borrow::Cow,
+    io::{self, Cursor, Seek, SeekFrom, Write},
+};
 
-use byteorder::{ReadBytesExt, LE};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt, BE, LE};
+use encoding_rs::SHIFT_JIS;
+use thiserror::Error;
 
-use crate::io_ext::ReadFormatsExt;
+use crate::{
+    io_ext::{ReadFormatsExt, ReadWidestringError},
+    tpf::{
+        dds::{build_dds, has_dds_header, DdsDescription, DdsError, DxgiFormat},
+        decode::{decode_rgba, RgbaImage},
+        swizzle::deswizzle_ps4,
+    },
+};
 
-#[derive(Debug)]
+pub mod dds;
+pub mod decode;
+pub mod swizzle;
+mod view;
+
+pub use view::Tpf;
+
+#[derive(Debug, Error)]
 pub enum TPFError {
-    IO(io::Error),
+    #[error("Could not read or write TPF {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Invalid DDS file {0}")]
+    Dds(#[from] DdsError),
+
+    #[error("No texture named {0}")]
+    TextureNotFound(String),
+
+    #[error("Texture {0} is from a console TPF and can't be replaced with a DDS file")]
+    ConsoleTexture(String),
+
+    #[error("Expected TPF magic, found {0:?}")]
+    InvalidMagic([u8; 4]),
+
+    #[error("Unsupported texture name encoding {0}")]
+    UnsupportedEncoding(u8),
+
+    #[error("Big endian TPFs of platform {0} can't be viewed, read them with TPF::from_reader")]
+    UnsupportedPlatform(u8),
+
+    #[error("Unknown TPF platform {0}")]
+    UnknownPlatform(u8),
+
+    #[error("TPF data is out of bounds")]
+    OutOfBounds,
+
+    #[error("Could not read texture name")]
+    String(#[from] ReadWidestringError),
+}
+
+/// The platform a TPF was made for, which decides its byte order, the layout of its texture
+/// headers and how texture data is stored. TPFs of any other platform, including PS5, are rejected
+/// when reading since neither their headers nor their tiling are known. Only PS4 texture data can
+/// be deswizzled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TPFPlatform {
+    Pc,
+    Xbox360,
+    Ps3,
+    Ps4,
+    XboxOne,
+}
+
+impl TPFPlatform {
+    pub fn from_u8(platform: u8) -> Option<Self> {
+        Some(match platform {
+            0 => Self::Pc,
+            1 => Self::Xbox360,
+            2 => Self::Ps3,
+            4 => Self::Ps4,
+            5 => Self::XboxOne,
+            _ => return None,
+        })
+    }
+
+    pub fn is_big_endian(self) -> bool {
+        matches!(self, Self::Xbox360 | Self::Ps3)
+    }
 }
 
 #[derive(Debug)]
 pub struct TPF {
+    pub platform: u8,
+    pub unk0d: u8,
+    /// Texture names are UTF-16 if this is 1 and Shift-JIS otherwise.
+    pub encoding: u8,
     pub textures: Vec<Texture>,
 }
 
 impl TPF {
-    pub fn from_reader(r: &mut (impl io::Read + io::Seek)) -> Result<Self, io::Error> {
-        r.read_magic(b"TPF\0")?;
-
-        let _data_size = r.read_u32::<LE>()?;
-        let texture_count = r.read_u32::<LE>()?;
-        let _platform = r.read_u8()?;
-        let _unk0d = r.read_u8()?;
-        assert!(r.read_u8()? == 0x1, "Encoding isn't 0x1");
+    pub fn from_reader(r: &mut (impl io::Read + io::Seek)) -> Result<Self, TPFError> {
+        let mut magic = [0u8; 4];
+        r.read_exact(&mut magic)?;
+        if &magic != b"TPF\0" {
+            return Err(TPFError::InvalidMagic(magic));
+        }
+
+        // The byte order depends on the platform that follows the sizes
+        let mut sizes = [0u8; 8];
+        r.read_exact(&mut sizes)?;
+        let platform = r.read_u8()?;
+        if TPFPlatform::from_u8(platform).is_none() {
+            return Err(TPFError::UnknownPlatform(platform));
+        }
+        let unk0d = r.read_u8()?;
+        let encoding = r.read_u8()?;
+        if encoding > 2 {
+            return Err(TPFError::UnsupportedEncoding(encoding));
+        }
         r.read_padding(1)?;
 
-        let mut textures = vec![];
-        for _ in 0..texture_count {
-            textures.push(Texture::from_reader(r)?);
+        let mut tpf = Self {
+            platform,
+            unk0d,
+            encoding,
+            textures: vec![],
+        };
+
+        if tpf.platform().is_some_and(TPFPlatform::is_big_endian) {
+            let texture_count = BE::read_u32(&sizes[4..]);
+            for _ in 0..texture_count {
+                tpf.textures.push(Texture::from_reader::<BE>(r, &tpf)?);
+            }
+        } else {
+            let texture_count = LE::read_u32(&sizes[4..]);
+            for _ in 0..texture_count {
+                tpf.textures.push(Texture::from_reader::<LE>(r, &tpf)?);
+            }
+        }
+
+        Ok(tpf)
+    }
+
+    pub fn platform(&self) -> Option<TPFPlatform> {
+        TPFPlatform::from_u8(self.platform)
+    }
+
+    /// Undoes the tiling of PS4 texture data so it is laid out like the data of a DDS file. Data
+    /// of other platforms is returned as is.
+    pub fn linear_data<'d>(
+        &self,
+        texture: &Texture,
+        data: &'d [u8],
+    ) -> Result<Cow<'d, [u8]>, DdsError> {
+        linear_texture_data(self.platform(), texture, data)
+    }
+
+    /// Copies the textures of this TPF so they can be modified and written with [`TPF::write`].
+    pub fn owned_textures(
+        &self,
+        r: &mut (impl io::Read + io::Seek),
+    ) -> Result<Vec<TPFTexture>, io::Error> {
+        self.textures
+            .iter()
+            .map(|texture| {
+                Ok(TPFTexture {
+                    name: texture.name.clone(),
+                    format: texture.format,
+                    cubemap: texture.cubemap,
+                    mipmaps: texture.mipmaps,
+                    unk0b: texture.unk0b,
+                    unk10: texture.unk10,
+                    header: texture.header.clone(),
+                    data: texture.bytes(r)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Writes a TPF containing `textures`, using the header fields of this TPF. Texture data is
+    /// aligned to 16 bytes.
+    ///
+    /// Textures of console TPFs need a [`TextureHeader`], which is written in the layout of the
+    /// platform.
+    pub fn write<W: Write>(&self, writer: W, textures: &[TPFTexture]) -> io::Result<()> {
+        if self.platform().is_some_and(TPFPlatform::is_big_endian) {
+            self.write_inner::<BE, W>(writer, textures)
+        } else {
+            self.write_inner::<LE, W>(writer, textures)
+        }
+    }
+
+    fn write_inner<O: ByteOrder, W: Write>(
+        &self,
+        mut writer: W,
+        textures: &[TPFTexture],
+    ) -> io::Result<()> {
+        let mut w = Cursor::new(Vec::new());
+        let platform = self.platform();
+
+        w.write_all(b"TPF\0")?;
+        // Data size is filled in below
+        w.write_u32::<O>(0)?;
+        w.write_u32::<O>(textures.len() as u32)?;
+        w.write_u8(self.platform)?;
+        w.write_u8(self.unk0d)?;
+        w.write_u8(self.encoding)?;
+        w.write_u8(0)?;
+
+        // Positions of the data and name offsets of every texture, filled in below
+        let mut offset_positions = Vec::with_capacity(textures.len());
+        for texture in textures {
+            let data_offset_pos = w.position();
+            w.write_u32::<O>(0)?;
+            w.write_u32::<O>(texture.data.len() as u32)?;
+            w.write_u8(texture.format)?;
+            w.write_u8(texture.cubemap)?;
+            w.write_u8(texture.mipmaps)?;
+            w.write_u8(texture.unk0b)?;
+
+            let header = match (platform, &texture.header) {
+                (None | Some(TPFPlatform::Pc), _) => None,
+                (Some(platform), Some(header)) => Some((platform, header)),
+                (Some(_), None) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("texture {} has no console texture header", texture.name),
+                    ))
+                }
+            };
+
+            if let Some((platform, header)) = header {
+                w.write_u16::<O>(header.width)?;
+                w.write_u16::<O>(header.height)?;
+                match platform {
+                    TPFPlatform::Xbox360 => w.write_u32::<O>(0)?,
+                    TPFPlatform::Ps3 => {
+                        w.write_u32::<O>(header.unk1)?;
+                        if self.unk0d != 0 {
+                            w.write_u32::<O>(header.unk2)?;
+                        }
+                    }
+                    TPFPlatform::Ps4 | TPFPlatform::XboxOne => {
+                        w.write_u32::<O>(header.array_size)?;
+                        w.write_u32::<O>(header.unk2)?;
+                    }
+                    TPFPlatform::Pc => {}
+                }
+            }
+
+            let name_offset_pos = w.position();
+            w.write_u32::<O>(0)?;
+            w.write_u32::<O>(texture.unk10)?;
+
+            if let Some((TPFPlatform::Ps4 | TPFPlatform::XboxOne, header)) = header {
+                w.write_u32::<O>(header.dxgi_format)?;
+            }
+
+            offset_positions.push((data_offset_pos, name_offset_pos));
+        }
+
+        let mut name_offsets = Vec::with_capacity(textures.len());
+        for texture in textures {
+            name_offsets.push(w.position() as u32);
+            if self.encoding == 1 {
+                for unit in texture.name.encode_utf16().chain([0]) {
+                    w.write_u16::<O>(unit)?;
+                }
+            } else {
+                let (bytes, _, _) = SHIFT_JIS.encode(&texture.name);
+                w.write_all(&bytes)?;
+                w.write_u8(0)?;
+            }
+        }
+
+        let data_start = w.position();
+        for (index, texture) in textures.iter().enumerate() {
+            if !texture.data.is_empty() {
+                let padding = (0x10 - w.position() % 0x10) % 0x10;
+                w.write_all(&vec![0; padding as usize])?;
+            }
+
+            let data_offset = w.position() as u32;
+            w.write_all(&texture.data)?;
+
+            let (data_offset_pos, name_offset_pos) = offset_positions[index];
+            let end = w.position();
+            w.seek(SeekFrom::Start(data_offset_pos))?;
+            w.write_u32::<O>(data_offset)?;
+            w.seek(SeekFrom::Start(name_offset_pos))?;
+            w.write_u32::<O>(name_offsets[index])?;
+            w.seek(SeekFrom::Start(end))?;
+        }
+
+        let data_size = w.position() - data_start;
+        w.seek(SeekFrom::Start(4))?;
+        w.write_u32::<O>(data_size as u32)?;
+
+        writer.write_all(&w.into_inner())
+    }
+}
+
+/// Texture properties that console TPFs store inline, since their texture data has no DDS header.
+/// Fields a platform doesn't store are zero.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TextureHeader {
+    pub width: u16,
+    pub height: u16,
+    /// The number of textures in the array, 6 for cubemaps. Stored by PS4 and Xbox One TPFs.
+    pub array_size: u32,
+    /// Stored by PS3 TPFs.
+    pub unk1: u32,
+    /// Stored by PS4 and Xbox One TPFs, and by PS3 TPFs with a non-zero `unk0d`.
+    pub unk2: u32,
+    /// Stored by PS4 and Xbox One TPFs.
+    pub dxgi_format: u32,
+}
+
+/// An owned texture to be written to a TPF with [`TPF::write`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TPFTexture {
+    pub name: String,
+    pub format: u8,
+    pub cubemap: u8,
+    pub mipmaps: u8,
+    pub unk0b: u8,
+    pub unk10: u32,
+    pub header: Option<TextureHeader>,
+    pub data: Vec<u8>,
+}
+
+impl TPFTexture {
+    /// Replaces the data of this texture with a DDS file, taking its format, mipmap count and
+    /// cubemap flag from the DDS header. The format code is kept if it already describes the
+    /// format of the DDS file, since several codes share a DXGI format.
+    ///
+    /// Only textures of PC TPFs can be replaced. Console TPFs store tiled data without a DDS
+    /// header, described by a [`TextureHeader`], and tiling isn't supported.
+    pub fn replace_data(&mut self, dds: Vec<u8>) -> Result<(), TPFError> {
+        if self.header.is_some() {
+            return Err(TPFError::ConsoleTexture(self.name.clone()));
         }
 
-        Ok(Self { textures })
+        let (description, _) = DdsDescription::parse(&dds)?;
+
+        if DxgiFormat::from_tpf_format(self.format) != Some(description.format) {
+            self.format = description.format.tpf_format();
+        }
+        self.mipmaps = description.mipmaps as u8;
+        self.cubemap = description.cubemap as u8;
+        self.data = dds;
+
+        Ok(())
     }
 }
 
+/// Replaces the texture called `name` with a DDS file, see [`TPFTexture::replace_data`].
+pub fn replace_texture(
+    textures: &mut [TPFTexture],
+    name: &str,
+    dds: Vec<u8>,
+) -> Result<(), TPFError> {
+    let texture = textures
+        .iter_mut()
+        .find(|texture| texture.name == name)
+        .ok_or_else(|| TPFError::TextureNotFound(name.to_string()))?;
+
+    texture.replace_data(dds)
+}
+
 #[derive(Debug)]
 pub struct Texture {
     pub data_offset: u32,
@@ -41,23 +373,70 @@ pub struct Texture {
     pub format: u8,
     pub cubemap: u8,
     pub mipmaps: u8,
+    pub unk0b: u8,
+    pub unk10: u32,
+    /// Present in console TPFs.
+    pub header: Option<TextureHeader>,
     pub name: String,
 }
 
 impl Texture {
-    pub fn from_reader(r: &mut (impl io::Read + io::Seek)) -> Result<Self, io::Error> {
-        let data_offset = r.read_u32::<LE>()?;
-        let data_size = r.read_u32::<LE>()?;
+    pub fn from_reader<O: ByteOrder>(
+        r: &mut (impl io::Read + io::Seek),
+        tpf: &TPF,
+    ) -> Result<Self, io::Error> {
+        let data_offset = r.read_u32::<O>()?;
+        let data_size = r.read_u32::<O>()?;
         let format = r.read_u8()?;
         let cubemap = r.read_u8()?;
         let mipmaps = r.read_u8()?;
-        let _unk0b = r.read_u8()?;
-        let name_offset = r.read_u32::<LE>()?;
-        let _unk10 = r.read_u32::<LE>()?;
+        let unk0b = r.read_u8()?;
+
+        let platform = tpf.platform();
+        let mut header = match platform {
+            None | Some(TPFPlatform::Pc) => None,
+            Some(platform) => {
+                let mut header = TextureHeader {
+                    width: r.read_u16::<O>()?,
+                    height: r.read_u16::<O>()?,
+                    ..Default::default()
+                };
+
+                match platform {
+                    TPFPlatform::Xbox360 => r.read_padding(4)?,
+                    TPFPlatform::Ps3 => {
+                        header.unk1 = r.read_u32::<O>()?;
+                        if tpf.unk0d != 0 {
+                            header.unk2 = r.read_u32::<O>()?;
+                        }
+                    }
+                    TPFPlatform::Ps4 | TPFPlatform::XboxOne => {
+                        header.array_size = r.read_u32::<O>()?;
+                        header.unk2 = r.read_u32::<O>()?;
+                    }
+                    TPFPlatform::Pc => {}
+                }
+
+                Some(header)
+            }
+        };
+
+        let name_offset = r.read_u32::<O>()?;
+        let unk10 = r.read_u32::<O>()?;
+
+        if let (Some(TPFPlatform::Ps4 | TPFPlatform::XboxOne), Some(header)) =
+            (platform, &mut header)
+        {
+            header.dxgi_format = r.read_u32::<O>()?;
+        }
 
         let current = r.stream_position()?;
         r.seek(SeekFrom::Start(name_offset as u64))?;
-        let name = r.read_utf16::<LE>()?;
+        let name = if tpf.encoding == 1 {
+            r.read_utf16::<O>()?
+        } else {
+            r.read_shift_jis()?
+        };
         r.seek(SeekFrom::Start(current))?;
 
         Ok(Self {
@@ -66,10 +445,81 @@ impl Texture {
             format,
             cubemap,
             mipmaps,
+            unk0b,
+            unk10,
+            header,
             name,
         })
     }
 
+    pub fn is_cubemap(&self) -> bool {
+        self.cubemap == 1
+            || self
+                .header
+                .as_ref()
+                .is_some_and(|header| header.array_size == 6)
+    }
+
+    /// Describes the data of this texture. PC textures embed a DDS file whose header is read,
+    /// other textures are described by the TPF metadata and the given dimensions, which default
+    /// to the ones of the console texture header. A mipmap count of zero stands for a full mip
+    /// chain.
+    ///
+    /// Returns the description and the texture data without any DDS header.
+    pub fn dds_description<'d>(
+        &self,
+        data: &'d [u8],
+        dimensions: Option<(u32, u32)>,
+    ) -> Result<(DdsDescription, &'d [u8]), DdsError> {
+        if has_dds_header(data) {
+            return DdsDescription::parse(data);
+        }
+
+        let header = self.header.as_ref();
+        let format = header
+            .and_then(|header| DxgiFormat::from_dxgi(header.dxgi_format))
+            .or_else(|| DxgiFormat::from_tpf_format(self.format))
+            .ok_or(DdsError::UnsupportedFormat(self.format))?;
+        let (width, height) = dimensions
+            .or_else(|| header.map(|header| (header.width as u32, header.height as u32)))
+            .ok_or(DdsError::MissingDimensions)?;
+        let mipmaps = match self.mipmaps {
+            0 => DdsDescription::full_mip_count(width, height),
+            mipmaps => mipmaps as u32,
+        };
+
+        let description = DdsDescription {
+            format,
+            width,
+            height,
+            mipmaps,
+            cubemap: self.is_cubemap(),
+        };
+
+        Ok((description, data))
+    }
+
+    /// Turns the data of this texture into a standalone DDS file, reconstructing the header when
+    /// the data has none.
+    pub fn to_dds(&self, data: &[u8], dimensions: Option<(u32, u32)>) -> Result<Vec<u8>, DdsError> {
+        if has_dds_header(data) {
+            return Ok(data.to_vec());
+        }
+
+        let (description, data) = self.dds_description(data, dimensions)?;
+        build_dds(&description, data)
+    }
+
+    /// Decodes the top mip of this texture to 8-bit RGBA.
+    pub fn to_rgba(
+        &self,
+        data: &[u8],
+        dimensions: Option<(u32, u32)>,
+    ) -> Result<RgbaImage, DdsError> {
+        let (description, data) = self.dds_description(data, dimensions)?;
+        decode_rgba(&description, data)
+    }
+
     pub fn bytes(&self, r: &mut (impl io::Read + io::Seek)) -> Result<Vec<u8>, io::Error> {
         let mut buffer = vec![0x0u8; self.data_size as usize];
         r.seek(SeekFrom::Start(self.data_offset as u64))?;
@@ -77,3 +527,293 @@ impl Texture {
         Ok(buffer)
     }
 }
+
+fn linear_texture_data<'d>(
+    platform: Option<TPFPlatform>,
+    texture: &Texture,
+    data: &'d [u8],
+) -> Result<Cow<'d, [u8]>, DdsError> {
+    if platform != Some(TPFPlatform::Ps4) || has_dds_header(data) {
+        return Ok(Cow::Borrowed(data));
+    }
+
+    let (description, data) = texture.dds_description(data, None)?;
+    Ok(Cow::Owned(deswizzle_ps4(&description, data)?))
+}
+
+#[cfg(test)]
+mod test {
+    use std::{io::Cursor, slice};
+
+    use crate::tpf::{
+        dds::{build_dds, DdsDescription, DxgiFormat},
+        replace_texture, TPFError, TPFTexture, TextureHeader, Tpf, TPF,
+    };
+
+    fn dds(format: DxgiFormat, mipmaps: u32, cubemap: bool) -> Vec<u8> {
+        let description = DdsDescription {
+            format,
+            width: 4,
+            height: 4,
+            mipmaps,
+            cubemap,
+        };
+
+        build_dds(&description, &vec![0x11; description.data_size()]).expect("enough data")
+    }
+
+    #[test]
+    pub fn writes_and_replaces_textures() {
+        let mut tpf = TPF {
+            platform: 0,
+            unk0d: 3,
+            encoding: 1,
+            textures: Vec::new(),
+        };
+        let mut textures = vec![
+            TPFTexture {
+                name: "c1000_a".to_string(),
+                format: 0,
+                cubemap: 0,
+                mipmaps: 1,
+                unk0b: 0,
+                unk10: 0,
+                header: None,
+                data: dds(DxgiFormat::BC1Unorm, 1, false),
+            },
+            TPFTexture {
+                name: "c1000_n".to_string(),
+                format: 107,
+                cubemap: 0,
+                mipmaps: 1,
+                unk0b: 0,
+                unk10: 0,
+                header: None,
+                data: dds(DxgiFormat::BC7Unorm, 1, false),
+            },
+        ];
+
+        replace_texture(
+            &mut textures,
+            "c1000_a",
+            dds(DxgiFormat::BC7UnormSrgb, 3, true),
+        )
+        .expect("valid DDS");
+        replace_texture(
+            &mut textures,
+            "c1000_n",
+            dds(DxgiFormat::BC7Unorm, 3, false),
+        )
+        .expect("valid DDS");
+        assert!(matches!(
+            replace_texture(
+                &mut textures,
+                "c1000_r",
+                dds(DxgiFormat::BC1Unorm, 1, false)
+            ),
+            Err(TPFError::TextureNotFound(_))
+        ));
+        assert_eq!(
+            (textures[0].format, textures[0].mipmaps, textures[0].cubemap),
+            (112, 3, 1)
+        );
+        assert_eq!(textures[1].format, 107);
+
+        for encoding in [1, 2] {
+            tpf.encoding = encoding;
+            let mut bytes = Vec::new();
+            tpf.write(&mut bytes, &textures).expect("written");
+
+            let mut cursor = Cursor::new(&bytes);
+            let parsed = TPF::from_reader(&mut cursor).expect("valid TPF");
+            assert_eq!(parsed.unk0d, 3);
+            assert_eq!(parsed.textures[1].data_offset % 0x10, 0);
+            assert_eq!(
+                parsed.owned_textures(&mut cursor).expect("readable data"),
+                textures
+            );
+        }
+    }
+
+    #[test]
+    pub fn reads_console_tpfs() {
+        let header = TextureHeader {
+            width: 4,
+            height: 4,
+            array_size: 1,
+            unk2: 0xD,
+            dxgi_format: DxgiFormat::A8Unorm as u32,
+            ..Default::default()
+        };
+        let texture = TPFTexture {
+            name: "m10_00_sky".to_string(),
+            format: 16,
+            cubemap: 0,
+            mipmaps: 1,
+            unk0b: 0,
+            unk10: 0,
+            header: Some(header.clone()),
+            data: (0..64).collect(),
+        };
+
+        let mut tpf = TPF {
+            platform: 4,
+            unk0d: 0,
+            encoding: 1,
+            textures: Vec::new(),
+        };
+        let mut bytes = Vec::new();
+        tpf.write(&mut bytes, slice::from_ref(&texture))
+            .expect("written");
+
+        let mut cursor = Cursor::new(&bytes);
+        let parsed = TPF::from_reader(&mut cursor).expect("valid TPF");
+        assert_eq!(parsed.textures[0].header, Some(header));
+        assert_eq!(parsed.textures[0].name, "m10_00_sky");
+
+        let data = parsed.textures[0]
+            .bytes(&mut cursor)
+            .expect("readable data");
+        let linear = parsed
+            .linear_data(&parsed.textures[0], &data)
+            .expect("deswizzled");
+        assert_eq!(linear[..4], [0, 1, 4, 5]);
+        let dds = parsed.textures[0]
+            .to_dds(&linear, None)
+            .expect("reconstructed header");
+        assert_eq!(dds.len(), 4 + 124 + 20 + 16);
+
+        // The header would go stale and the data would have to be tiled again
+        let mut owned = parsed.owned_textures(&mut cursor).expect("readable data");
+        assert!(matches!(
+            replace_texture(&mut owned, "m10_00_sky", dds),
+            Err(TPFError::ConsoleTexture(_))
+        ));
+
+        // PS3 TPFs are big endian and store more header fields when unk0d is set
+        tpf.platform = 2;
+        tpf.unk0d = 1;
+        let ps3_texture = TPFTexture {
+            header: Some(TextureHeader {
+                width: 4,
+                height: 4,
+                unk1: 0x2000,
+                unk2: 0xAAE4,
+                ..Default::default()
+            }),
+            ..texture
+        };
+        let mut bytes = Vec::new();
+        tpf.write(&mut bytes, slice::from_ref(&ps3_texture))
+            .expect("written");
+        assert_eq!(bytes[8..12], [0, 0, 0, 1]);
+
+        let mut cursor = Cursor::new(&bytes);
+        let parsed = TPF::from_reader(&mut cursor).expect("valid TPF");
+        assert_eq!(
+            parsed.owned_textures(&mut cursor).expect("readable data"),
+            [ps3_texture]
+        );
+
+        // Unknown platforms are rejected instead of read as PC
+        bytes[0xC] = 6;
+        assert!(matches!(
+            TPF::from_reader(&mut Cursor::new(&bytes)),
+            Err(TPFError::UnknownPlatform(6))
+        ));
+        assert!(matches!(
+            Tpf::parse(&bytes),
+            Err(TPFError::UnknownPlatform(6))
+        ));
+    }
+
+    #[test]
+    pub fn views_tpfs() {
+        let texture = TPFTexture {
+            name: "仮テクスチャ".to_string(),
+            format: 0,
+            cubemap: 0,
+            mipmaps: 1,
+            unk0b: 0,
+            unk10: 0,
+            header: None,
+            data: dds(DxgiFormat::BC1Unorm, 1, false),
+        };
+        let mut tpf = TPF {
+            platform: 0,
+            unk0d: 0,
+            encoding: 1,
+            textures: Vec::new(),
+        };
+
+        for encoding in [1, 2] {
+            tpf.encoding = encoding;
+            let mut bytes = Vec::new();
+            tpf.write(&mut bytes, slice::from_ref(&texture))
+                .expect("written");
+
+            let view = Tpf::parse(&bytes).expect("valid TPF");
+            assert_eq!(view.texture_count(), 1);
+            let parsed = view.texture(0).expect("valid texture");
+            assert_eq!(parsed.name, texture.name);
+            assert_eq!(view.texture_data(&parsed).expect("in bounds"), texture.data);
+
+            // Cutting into the texture entries fails up front, cutting into the data only once
+            // it's requested
+            assert!(matches!(
+                Tpf::parse(&bytes[..0x20]),
+                Err(TPFError::OutOfBounds)
+            ));
+            let truncated = Tpf::parse(&bytes[..bytes.len() - 1]).expect("valid entries");
+            let parsed = truncated.texture(0).expect("valid texture");
+            assert!(matches!(
+                truncated.texture_data(&parsed),
+                Err(TPFError::OutOfBounds)
+            ));
+
+            // Both readers reject the same headers
+            bytes[0xE] = 7;
+            assert!(matches!(
+                Tpf::parse(&bytes),
+                Err(TPFError::UnsupportedEncoding(7))
+            ));
+            assert!(matches!(
+                TPF::from_reader(&mut Cursor::new(&bytes)),
+                Err(TPFError::UnsupportedEncoding(7))
+            ));
+            bytes[0] = b'X';
+            assert!(matches!(Tpf::parse(&bytes), Err(TPFError::InvalidMagic(_))));
+            assert!(matches!(
+                TPF::from_reader(&mut Cursor::new(&bytes)),
+                Err(TPFError::InvalidMagic(_))
+            ));
+        }
+
+        tpf.platform = 4;
+        let header = TextureHeader {
+            width: 4,
+            height: 4,
+            array_size: 1,
+            unk2: 0xD,
+            dxgi_format: DxgiFormat::BC1Unorm as u32,
+            ..Default::default()
+        };
+        let ps4_texture = TPFTexture {
+            header: Some(header.clone()),
+            data: vec![0; 8 * 64],
+            ..texture
+        };
+        let mut bytes = Vec::new();
+        tpf.write(&mut bytes, slice::from_ref(&ps4_texture))
+            .expect("written");
+
+        let view = Tpf::parse(&bytes).expect("valid TPF");
+        let parsed = view.texture(0).expect("valid texture");
+        assert_eq!(parsed.header, Some(header));
+        let data = view.texture_data(&parsed).expect("in bounds");
+        assert_eq!(
+            view.linear_data(&parsed, data).expect("deswizzled").len(),
+            8
+        );
+    }
+}
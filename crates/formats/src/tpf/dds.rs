@@ -0,0 +1,499 @@
+//! Standalone DDS files of TPF textures.
+//!
+//! PC TPFs embed complete DDS files, but other platforms only store the texture data and describe
+//! it with the TPF metadata. The headers written here use a legacy `FourCC` where one exists for the
+//! format and a DX10 extension header otherwise.
+
+use std::io::{self, Write};
+
+use byteorder::{WriteBytesExt, LE};
+use thiserror::Error;
+use zerocopy::{FromBytes, FromZeroes, Ref, U32};
+
+pub const DDS_MAGIC: &[u8; 4] = b"DDS ";
+
+const HEADER_SIZE: u32 = 124;
+const PIXEL_FORMAT_SIZE: u32 = 32;
+
+const DDSD_CAPS: u32 = 0x1;
+const DDSD_HEIGHT: u32 = 0x2;
+const DDSD_WIDTH: u32 = 0x4;
+const DDSD_PITCH: u32 = 0x8;
+const DDSD_PIXELFORMAT: u32 = 0x1000;
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDSD_LINEARSIZE: u32 = 0x80000;
+
+const DDPF_ALPHA: u32 = 0x2;
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+
+const D3DFMT_A16B16G16R16F: u32 = 113;
+
+const DDSCAPS_COMPLEX: u32 = 0x8;
+const DDSCAPS_TEXTURE: u32 = 0x1000;
+const DDSCAPS_MIPMAP: u32 = 0x40_0000;
+
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+const DDSCAPS2_CUBEMAP_ALL_FACES: u32 = 0xFE00;
+
+const D3D10_RESOURCE_DIMENSION_TEXTURE2D: u32 = 3;
+const D3D10_RESOURCE_MISC_TEXTURECUBE: u32 = 0x4;
+
+#[derive(Debug, Error)]
+pub enum DdsError {
+    #[error("Unsupported TPF texture format {0}")]
+    UnsupportedFormat(u8),
+
+    #[error("Texture has no DDS header and no dimensions to reconstruct one from")]
+    MissingDimensions,
+
+    #[error("Texture has a width or height of zero")]
+    EmptyTexture,
+
+    #[error("Texture data is {actual} bytes long, but {expected} bytes are required")]
+    DataTooShort { expected: usize, actual: usize },
+
+    #[error("Data does not start with a DDS header")]
+    InvalidHeader,
+
+    #[error("Unsupported DXGI format {0}")]
+    UnsupportedDxgiFormat(u32),
+
+    #[error("Unsupported DDS pixel format")]
+    UnsupportedPixelFormat,
+
+    #[error("Could not write DDS header {0}")]
+    Io(#[from] io::Error),
+}
+
+/// The DXGI formats used by TPF textures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum DxgiFormat {
+    R16G16B16A16Float = 10,
+    R8G8B8A8Unorm = 28,
+    R8G8B8A8UnormSrgb = 29,
+    A8Unorm = 65,
+    BC1Unorm = 71,
+    BC1UnormSrgb = 72,
+    BC2Unorm = 74,
+    BC2UnormSrgb = 75,
+    BC3Unorm = 77,
+    BC3UnormSrgb = 78,
+    BC4Unorm = 80,
+    BC5Unorm = 83,
+    B5G5R5A1Unorm = 86,
+    B8G8R8A8Unorm = 87,
+    B8G8R8A8UnormSrgb = 91,
+    BC6HUf16 = 95,
+    BC6HSf16 = 96,
+    BC7Unorm = 98,
+    BC7UnormSrgb = 99,
+}
+
+impl DxgiFormat {
+    pub fn from_dxgi(format: u32) -> Option<Self> {
+        Some(match format {
+            10 => Self::R16G16B16A16Float,
+            28 => Self::R8G8B8A8Unorm,
+            29 => Self::R8G8B8A8UnormSrgb,
+            65 => Self::A8Unorm,
+            71 => Self::BC1Unorm,
+            72 => Self::BC1UnormSrgb,
+            74 => Self::BC2Unorm,
+            75 => Self::BC2UnormSrgb,
+            77 => Self::BC3Unorm,
+            78 => Self::BC3UnormSrgb,
+            80 => Self::BC4Unorm,
+            83 => Self::BC5Unorm,
+            86 => Self::B5G5R5A1Unorm,
+            87 => Self::B8G8R8A8Unorm,
+            91 => Self::B8G8R8A8UnormSrgb,
+            95 => Self::BC6HUf16,
+            96 => Self::BC6HSf16,
+            98 => Self::BC7Unorm,
+            99 => Self::BC7UnormSrgb,
+            _ => return None,
+        })
+    }
+
+    /// The DXGI format of a TPF texture format code.
+    pub fn from_tpf_format(format: u8) -> Option<Self> {
+        Some(match format {
+            0 | 1 | 24 | 25 | 108 | 109 => Self::BC1Unorm,
+            3 => Self::BC2Unorm,
+            5 | 23 | 33 | 110 => Self::BC3Unorm,
+            6 => Self::B5G5R5A1Unorm,
+            9 => Self::B8G8R8A8Unorm,
+            10 | 105 => Self::R8G8B8A8Unorm,
+            16 => Self::A8Unorm,
+            22 => Self::R16G16B16A16Float,
+            100 | 113 => Self::BC6HUf16,
+            102 | 106 | 107 => Self::BC7Unorm,
+            103 => Self::BC4Unorm,
+            104 => Self::BC5Unorm,
+            112 => Self::BC7UnormSrgb,
+            _ => return None,
+        })
+    }
+
+    /// The TPF format code for this format. sRGB and signed variants of formats share the code of
+    /// the linear unsigned format, except for BC7.
+    pub fn tpf_format(self) -> u8 {
+        match self {
+            Self::BC1Unorm | Self::BC1UnormSrgb => 0,
+            Self::BC2Unorm | Self::BC2UnormSrgb => 3,
+            Self::BC3Unorm | Self::BC3UnormSrgb => 5,
+            Self::B5G5R5A1Unorm => 6,
+            Self::B8G8R8A8Unorm | Self::B8G8R8A8UnormSrgb => 9,
+            Self::R8G8B8A8Unorm | Self::R8G8B8A8UnormSrgb => 10,
+            Self::A8Unorm => 16,
+            Self::R16G16B16A16Float => 22,
+            Self::BC6HUf16 | Self::BC6HSf16 => 100,
+            Self::BC7Unorm => 102,
+            Self::BC4Unorm => 103,
+            Self::BC5Unorm => 104,
+            Self::BC7UnormSrgb => 112,
+        }
+    }
+
+    pub fn is_block_compressed(self) -> bool {
+        matches!(
+            self,
+            Self::BC1Unorm
+                | Self::BC1UnormSrgb
+                | Self::BC2Unorm
+                | Self::BC2UnormSrgb
+                | Self::BC3Unorm
+                | Self::BC3UnormSrgb
+                | Self::BC4Unorm
+                | Self::BC5Unorm
+                | Self::BC6HUf16
+                | Self::BC6HSf16
+                | Self::BC7Unorm
+                | Self::BC7UnormSrgb
+        )
+    }
+
+    /// The size of a 4x4 block of a block compressed format, or of a single pixel otherwise.
+    pub fn block_bytes(self) -> usize {
+        match self {
+            Self::BC2Unorm
+            | Self::BC2UnormSrgb
+            | Self::BC3Unorm
+            | Self::BC3UnormSrgb
+            | Self::BC5Unorm
+            | Self::BC6HUf16
+            | Self::BC6HSf16
+            | Self::BC7Unorm
+            | Self::BC7UnormSrgb => 16,
+            Self::BC1Unorm | Self::BC1UnormSrgb | Self::BC4Unorm | Self::R16G16B16A16Float => 8,
+            Self::R8G8B8A8Unorm
+            | Self::R8G8B8A8UnormSrgb
+            | Self::B8G8R8A8Unorm
+            | Self::B8G8R8A8UnormSrgb => 4,
+            Self::B5G5R5A1Unorm => 2,
+            Self::A8Unorm => 1,
+        }
+    }
+
+    /// The `FourCC` that older DDS readers understand for this format, if any.
+    fn legacy_four_cc(self) -> Option<&'static [u8; 4]> {
+        match self {
+            Self::BC1Unorm => Some(b"DXT1"),
+            Self::BC2Unorm => Some(b"DXT3"),
+            Self::BC3Unorm => Some(b"DXT5"),
+            Self::BC4Unorm => Some(b"ATI1"),
+            Self::BC5Unorm => Some(b"ATI2"),
+            _ => None,
+        }
+    }
+}
+
+/// Everything a DDS header describes about a texture.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DdsDescription {
+    pub format: DxgiFormat,
+    pub width: u32,
+    pub height: u32,
+    pub mipmaps: u32,
+    pub cubemap: bool,
+}
+
+impl DdsDescription {
+    /// Reads the header of a DDS file, returning the description and the texture data following
+    /// it.
+    pub fn parse(dds: &[u8]) -> Result<(Self, &[u8]), DdsError> {
+        let header_bytes = dds.strip_prefix(DDS_MAGIC).ok_or(DdsError::InvalidHeader)?;
+        let (header, mut data) =
+            Ref::<_, DdsHeader>::new_from_prefix(header_bytes).ok_or(DdsError::InvalidHeader)?;
+        if header.size.get() != HEADER_SIZE {
+            return Err(DdsError::InvalidHeader);
+        }
+
+        let pixel_format = &header.pixel_format;
+        let mut cubemap = header.caps2.get() & DDSCAPS2_CUBEMAP != 0;
+        let format = if pixel_format.flags.get() & DDPF_FOURCC != 0 {
+            match &pixel_format.four_cc {
+                b"DXT1" => DxgiFormat::BC1Unorm,
+                b"DXT2" | b"DXT3" => DxgiFormat::BC2Unorm,
+                b"DXT4" | b"DXT5" => DxgiFormat::BC3Unorm,
+                b"ATI1" | b"BC4U" => DxgiFormat::BC4Unorm,
+                b"ATI2" | b"BC5U" => DxgiFormat::BC5Unorm,
+                b"DX10" => {
+                    let (dx10, next) = Ref::<_, Dx10Header>::new_from_prefix(data)
+                        .ok_or(DdsError::InvalidHeader)?;
+                    data = next;
+                    cubemap |= dx10.misc_flags.get() & D3D10_RESOURCE_MISC_TEXTURECUBE != 0;
+
+                    let format = dx10.format.get();
+                    DxgiFormat::from_dxgi(format).ok_or(DdsError::UnsupportedDxgiFormat(format))?
+                }
+                four_cc if u32::from_le_bytes(*four_cc) == D3DFMT_A16B16G16R16F => {
+                    DxgiFormat::R16G16B16A16Float
+                }
+                _ => return Err(DdsError::UnsupportedPixelFormat),
+            }
+        } else {
+            let masks = [
+                pixel_format.bit_count.get(),
+                pixel_format.red_mask.get(),
+                pixel_format.green_mask.get(),
+                pixel_format.blue_mask.get(),
+            ];
+            match (pixel_format.flags.get() & (DDPF_RGB | DDPF_ALPHA), masks) {
+                (DDPF_RGB, [32, 0xFF0000, 0xFF00, 0xFF]) => DxgiFormat::B8G8R8A8Unorm,
+                (DDPF_RGB, [32, 0xFF, 0xFF00, 0xFF0000]) => DxgiFormat::R8G8B8A8Unorm,
+                (DDPF_RGB, [16, 0x7C00, 0x3E0, 0x1F]) => DxgiFormat::B5G5R5A1Unorm,
+                (DDPF_ALPHA, [8, ..]) => DxgiFormat::A8Unorm,
+                _ => return Err(DdsError::UnsupportedPixelFormat),
+            }
+        };
+
+        let description = Self {
+            format,
+            width: header.width.get(),
+            height: header.height.get(),
+            mipmaps: header.mipmaps.get().max(1),
+            cubemap,
+        };
+
+        Ok((description, data))
+    }
+
+    /// The number of mipmaps in a full chain down to a 1x1 texture.
+    pub fn full_mip_count(width: u32, height: u32) -> u32 {
+        u32::BITS - width.max(height).max(1).leading_zeros()
+    }
+
+    /// The size of a single face of the given mip level.
+    pub fn mip_size(&self, level: u32) -> usize {
+        let width = (self.width >> level).max(1) as usize;
+        let height = (self.height >> level).max(1) as usize;
+
+        if self.format.is_block_compressed() {
+            width.div_ceil(4) * height.div_ceil(4) * self.format.block_bytes()
+        } else {
+            width * height * self.format.block_bytes()
+        }
+    }
+
+    /// The size of the texture data, with every face of a cubemap holding its own mip chain.
+    pub fn data_size(&self) -> usize {
+        let faces = if self.cubemap { 6 } else { 1 };
+        let chain: usize = (0..self.mipmaps.max(1))
+            .map(|level| self.mip_size(level))
+            .sum();
+
+        chain * faces
+    }
+
+    /// Writes the DDS magic and header, followed by a DX10 header when the format has no legacy
+    /// `FourCC`.
+    pub fn write_header(&self, w: &mut impl Write) -> io::Result<()> {
+        let mipmaps = self.mipmaps.max(1);
+        let compressed = self.format.is_block_compressed();
+
+        let mut flags = DDSD_CAPS | DDSD_HEIGHT | DDSD_WIDTH | DDSD_PIXELFORMAT;
+        flags |= if compressed {
+            DDSD_LINEARSIZE
+        } else {
+            DDSD_PITCH
+        };
+        if mipmaps > 1 {
+            flags |= DDSD_MIPMAPCOUNT;
+        }
+
+        let pitch_or_linear_size = if compressed {
+            self.mip_size(0)
+        } else {
+            self.width as usize * self.format.block_bytes()
+        };
+
+        let mut caps = DDSCAPS_TEXTURE;
+        if mipmaps > 1 {
+            caps |= DDSCAPS_COMPLEX | DDSCAPS_MIPMAP;
+        }
+        if self.cubemap {
+            caps |= DDSCAPS_COMPLEX;
+        }
+        let caps2 = if self.cubemap {
+            DDSCAPS2_CUBEMAP_ALL_FACES
+        } else {
+            0
+        };
+
+        w.write_all(DDS_MAGIC)?;
+        w.write_u32::<LE>(HEADER_SIZE)?;
+        w.write_u32::<LE>(flags)?;
+        w.write_u32::<LE>(self.height)?;
+        w.write_u32::<LE>(self.width)?;
+        w.write_u32::<LE>(pitch_or_linear_size as u32)?;
+        w.write_u32::<LE>(0)?;
+        w.write_u32::<LE>(mipmaps)?;
+        w.write_all(&[0; 11 * 4])?;
+
+        let four_cc = self.format.legacy_four_cc().unwrap_or(b"DX10");
+        w.write_u32::<LE>(PIXEL_FORMAT_SIZE)?;
+        w.write_u32::<LE>(DDPF_FOURCC)?;
+        w.write_all(four_cc)?;
+        w.write_all(&[0; 5 * 4])?;
+
+        w.write_u32::<LE>(caps)?;
+        w.write_u32::<LE>(caps2)?;
+        w.write_all(&[0; 3 * 4])?;
+
+        if four_cc == b"DX10" {
+            let misc_flags = if self.cubemap {
+                D3D10_RESOURCE_MISC_TEXTURECUBE
+            } else {
+                0
+            };
+
+            w.write_u32::<LE>(self.format as u32)?;
+            w.write_u32::<LE>(D3D10_RESOURCE_DIMENSION_TEXTURE2D)?;
+            w.write_u32::<LE>(misc_flags)?;
+            w.write_u32::<LE>(1)?;
+            w.write_u32::<LE>(0)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(FromZeroes, FromBytes)]
+#[repr(C)]
+#[allow(unused)]
+struct DdsHeader {
+    size: U32<LE>,
+    flags: U32<LE>,
+    height: U32<LE>,
+    width: U32<LE>,
+    pitch_or_linear_size: U32<LE>,
+    depth: U32<LE>,
+    mipmaps: U32<LE>,
+    reserved: [U32<LE>; 11],
+    pixel_format: PixelFormat,
+    caps: U32<LE>,
+    caps2: U32<LE>,
+    caps3: U32<LE>,
+    caps4: U32<LE>,
+    reserved2: U32<LE>,
+}
+
+#[derive(FromZeroes, FromBytes)]
+#[repr(C)]
+#[allow(unused)]
+struct PixelFormat {
+    size: U32<LE>,
+    flags: U32<LE>,
+    four_cc: [u8; 4],
+    bit_count: U32<LE>,
+    red_mask: U32<LE>,
+    green_mask: U32<LE>,
+    blue_mask: U32<LE>,
+    alpha_mask: U32<LE>,
+}
+
+#[derive(FromZeroes, FromBytes)]
+#[repr(C)]
+#[allow(unused)]
+struct Dx10Header {
+    format: U32<LE>,
+    resource_dimension: U32<LE>,
+    misc_flags: U32<LE>,
+    array_size: U32<LE>,
+    misc_flags2: U32<LE>,
+}
+
+pub fn has_dds_header(data: &[u8]) -> bool {
+    data.starts_with(DDS_MAGIC)
+}
+
+/// Builds a DDS file from texture data without a header. Trailing padding after the mip chain is
+/// dropped.
+pub fn build_dds(description: &DdsDescription, data: &[u8]) -> Result<Vec<u8>, DdsError> {
+    let expected = description.data_size();
+    if data.len() < expected {
+        return Err(DdsError::DataTooShort {
+            expected,
+            actual: data.len(),
+        });
+    }
+
+    let mut dds = Vec::with_capacity(expected + 148);
+    description.write_header(&mut dds)?;
+    dds.extend_from_slice(&data[..expected]);
+
+    Ok(dds)
+}
+
+#[cfg(test)]
+mod test {
+    use byteorder::{ByteOrder, LE};
+
+    use crate::tpf::dds::{build_dds, DdsDescription, DdsError, DxgiFormat};
+
+    #[test]
+    pub fn builds_dds_headers() {
+        let cubemap = DdsDescription {
+            format: DxgiFormat::BC1Unorm,
+            width: 4,
+            height: 4,
+            mipmaps: DdsDescription::full_mip_count(4, 4),
+            cubemap: true,
+        };
+        assert_eq!(cubemap.mipmaps, 3);
+        assert_eq!(cubemap.data_size(), 3 * 8 * 6);
+
+        let dds = build_dds(&cubemap, &[0xAB; 200]).expect("enough data");
+        assert_eq!(dds.len(), 4 + 124 + 144);
+        let (parsed, data) = DdsDescription::parse(&dds).expect("valid header");
+        assert_eq!(parsed, cubemap);
+        assert_eq!(data.len(), 144);
+        assert_eq!(&dds[0x54..0x58], b"DXT1");
+        assert_eq!(LE::read_u32(&dds[0x1C..]), 3);
+        assert_eq!(LE::read_u32(&dds[0x70..]), 0xFE00);
+
+        let bc7 = DdsDescription {
+            format: DxgiFormat::BC7UnormSrgb,
+            width: 8,
+            height: 2,
+            mipmaps: 1,
+            cubemap: false,
+        };
+        let dds = build_dds(&bc7, &[0; 32]).expect("enough data");
+        assert_eq!(dds.len(), 4 + 124 + 20 + 32);
+        assert_eq!(&dds[0x54..0x58], b"DX10");
+        assert_eq!(LE::read_u32(&dds[0x80..]), 99);
+        assert_eq!(DdsDescription::parse(&dds).expect("valid header").0, bc7);
+
+        assert!(matches!(
+            build_dds(&bc7, &[0; 16]),
+            Err(DdsError::DataTooShort {
+                expected: 32,
+                actual: 16
+            })
+        ));
+    }
+}
@@ -0,0 +1,241 @@
+//! Decoding of texture data to 8-bit RGBA, so textures can be previewed and compared without a
+//! GPU. Block compressed formats are decoded with `bcdec_rs`.
+
+use std::io::Write;
+
+use byteorder::{ByteOrder, LE};
+
+use crate::tpf::dds::{DdsDescription, DdsError, DxgiFormat};
+
+/// An image with 8-bit RGBA pixels, stored row by row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    pub fn write_png(&self, w: impl Write) -> Result<(), png::EncodingError> {
+        let mut encoder = png::Encoder::new(w, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        encoder.write_header()?.write_image_data(&self.pixels)
+    }
+}
+
+/// Decodes the top mip of a texture, or of the first face of a cubemap.
+///
+/// Floating point formats are clamped to the `[0, 1]` range, BC4 becomes greyscale, BC5 leaves the
+/// blue channel at zero and A8 is white with the stored alpha.
+pub fn decode_rgba(description: &DdsDescription, data: &[u8]) -> Result<RgbaImage, DdsError> {
+    if description.width == 0 || description.height == 0 {
+        return Err(DdsError::EmptyTexture);
+    }
+
+    let format = description.format;
+    let expected = description.mip_size(0);
+    if data.len() < expected {
+        return Err(DdsError::DataTooShort {
+            expected,
+            actual: data.len(),
+        });
+    }
+
+    let width = description.width as usize;
+    let height = description.height as usize;
+    let mut pixels = vec![0u8; width * height * 4];
+
+    if format.is_block_compressed() {
+        let blocks_wide = width.div_ceil(4);
+        let blocks = data[..expected].chunks_exact(format.block_bytes());
+
+        for (index, block) in blocks.enumerate() {
+            let decoded = decode_block(format, block);
+            let x = index % blocks_wide * 4;
+            let y = index / blocks_wide * 4;
+
+            // Blocks on the right and bottom edges may hang over the image
+            let row_length = (width - x).min(4) * 4;
+            for row in 0..(height - y).min(4) {
+                let start = ((y + row) * width + x) * 4;
+                pixels[start..start + row_length]
+                    .copy_from_slice(&decoded[row * 16..row * 16 + row_length]);
+            }
+        }
+    } else {
+        let sources = data[..expected].chunks_exact(format.block_bytes());
+        for (pixel, source) in pixels.chunks_exact_mut(4).zip(sources) {
+            pixel.copy_from_slice(&decode_pixel(format, source));
+        }
+    }
+
+    Ok(RgbaImage {
+        width: description.width,
+        height: description.height,
+        pixels,
+    })
+}
+
+/// Decodes a 4x4 block into 16 rows of 4 RGBA pixels.
+fn decode_block(format: DxgiFormat, block: &[u8]) -> [u8; 64] {
+    let mut rgba = [0u8; 64];
+
+    match format {
+        DxgiFormat::BC1Unorm | DxgiFormat::BC1UnormSrgb => bcdec_rs::bc1(block, &mut rgba, 16),
+        DxgiFormat::BC2Unorm | DxgiFormat::BC2UnormSrgb => bcdec_rs::bc2(block, &mut rgba, 16),
+        DxgiFormat::BC3Unorm | DxgiFormat::BC3UnormSrgb => bcdec_rs::bc3(block, &mut rgba, 16),
+        DxgiFormat::BC7Unorm | DxgiFormat::BC7UnormSrgb => bcdec_rs::bc7(block, &mut rgba, 16),
+        DxgiFormat::BC4Unorm => {
+            let mut red = [0u8; 16];
+            bcdec_rs::bc4(block, &mut red, 4, false);
+
+            for (pixel, red) in rgba.chunks_exact_mut(4).zip(red) {
+                pixel.copy_from_slice(&[red, red, red, u8::MAX]);
+            }
+        }
+        DxgiFormat::BC5Unorm => {
+            let mut red_green = [0u8; 32];
+            bcdec_rs::bc5(block, &mut red_green, 8, false);
+
+            for (pixel, red_green) in rgba.chunks_exact_mut(4).zip(red_green.chunks_exact(2)) {
+                pixel.copy_from_slice(&[red_green[0], red_green[1], 0, u8::MAX]);
+            }
+        }
+        DxgiFormat::BC6HUf16 | DxgiFormat::BC6HSf16 => {
+            let mut rgb = [0f32; 48];
+            let signed = format == DxgiFormat::BC6HSf16;
+            bcdec_rs::bc6h_float(block, &mut rgb, 12, signed);
+
+            for (pixel, rgb) in rgba.chunks_exact_mut(4).zip(rgb.chunks_exact(3)) {
+                pixel.copy_from_slice(&[unorm8(rgb[0]), unorm8(rgb[1]), unorm8(rgb[2]), u8::MAX]);
+            }
+        }
+        _ => unreachable!("{format:?} is not block compressed"),
+    }
+
+    rgba
+}
+
+fn decode_pixel(format: DxgiFormat, source: &[u8]) -> [u8; 4] {
+    match format {
+        DxgiFormat::R8G8B8A8Unorm | DxgiFormat::R8G8B8A8UnormSrgb => {
+            [source[0], source[1], source[2], source[3]]
+        }
+        DxgiFormat::B8G8R8A8Unorm | DxgiFormat::B8G8R8A8UnormSrgb => {
+            [source[2], source[1], source[0], source[3]]
+        }
+        DxgiFormat::B5G5R5A1Unorm => {
+            let value = LE::read_u16(source);
+            let channel = |shift: u16| {
+                let bits = (value >> shift & 0x1F) as u8;
+                bits << 3 | bits >> 2
+            };
+
+            let alpha = if value & 0x8000 != 0 { u8::MAX } else { 0 };
+            [channel(10), channel(5), channel(0), alpha]
+        }
+        DxgiFormat::A8Unorm => [u8::MAX, u8::MAX, u8::MAX, source[0]],
+        DxgiFormat::R16G16B16A16Float => {
+            let mut rgba = [0u8; 4];
+            for (channel, half) in rgba.iter_mut().zip(source.chunks_exact(2)) {
+                *channel = unorm8(f16_to_f32(LE::read_u16(half)));
+            }
+
+            rgba
+        }
+        _ => unreachable!("{format:?} is block compressed"),
+    }
+}
+
+fn unorm8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn f16_to_f32(half: u16) -> f32 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = (half >> 10 & 0x1F) as i32;
+    let mantissa = (half & 0x3FF) as f32;
+
+    match exponent {
+        0 => sign * mantissa * 2f32.powi(-24),
+        0x1F if mantissa == 0.0 => sign * f32::INFINITY,
+        0x1F => f32::NAN,
+        _ => sign * (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tpf::{
+        dds::{DdsDescription, DdsError, DxgiFormat},
+        decode::decode_rgba,
+    };
+
+    #[test]
+    pub fn decodes_textures_to_rgba() {
+        // A single BC1 block with pure red and blue endpoints, with the top row using the first
+        // endpoint and the rest the second
+        let bc1 = [0x00, 0xF8, 0x1F, 0x00, 0x00, 0x55, 0x55, 0x55];
+        let description = DdsDescription {
+            format: DxgiFormat::BC1Unorm,
+            width: 3,
+            height: 2,
+            mipmaps: 1,
+            cubemap: false,
+        };
+
+        let image = decode_rgba(&description, &bc1).expect("enough data");
+        assert_eq!(image.pixels.len(), 3 * 2 * 4);
+        assert_eq!(image.pixels[..4], [255, 0, 0, 255]);
+        assert_eq!(image.pixels[12..16], [0, 0, 255, 255]);
+
+        let half_floats = [0x00, 0x3C, 0x00, 0x38, 0x00, 0x00, 0x00, 0x40];
+        let description = DdsDescription {
+            format: DxgiFormat::R16G16B16A16Float,
+            width: 1,
+            height: 1,
+            mipmaps: 1,
+            cubemap: false,
+        };
+        let image = decode_rgba(&description, &half_floats).expect("enough data");
+        assert_eq!(image.pixels, [255, 128, 0, 255]);
+
+        let mut png = Vec::new();
+        image.write_png(&mut png).expect("valid image");
+        assert!(png.starts_with(b"\x89PNG"));
+
+        let empty = DdsDescription {
+            width: 0,
+            ..description
+        };
+        assert!(matches!(
+            decode_rgba(&empty, &half_floats),
+            Err(DdsError::EmptyTexture)
+        ));
+    }
+
+    #[test]
+    pub fn decodes_signed_bc6h() {
+        // A mode 11 block with every pixel at the first endpoint, whose red component is the
+        // most negative value when signed and a bright one when unsigned
+        let bc6h = [
+            0x03, 0xC0, 0xFF, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00,
+        ];
+        let description = |format| DdsDescription {
+            format,
+            width: 4,
+            height: 4,
+            mipmaps: 1,
+            cubemap: false,
+        };
+
+        let unsigned = decode_rgba(&description(DxgiFormat::BC6HUf16), &bc6h).expect("enough data");
+        assert_eq!(unsigned.pixels[..4], [255, 255, 0, 255]);
+        let signed = decode_rgba(&description(DxgiFormat::BC6HSf16), &bc6h).expect("enough data");
+        assert_eq!(signed.pixels[..4], [0, 255, 0, 255]);
+        assert_eq!(DxgiFormat::from_dxgi(96), Some(DxgiFormat::BC6HSf16));
+    }
+}
@@ -0,0 +1,107 @@
+//! Tiling of console texture data.
+//!
+//! PS4 textures store every mip of every face in tiles of 8x8 blocks, or pixels for uncompressed
+//! formats. Blocks within a tile are in Morton order, and mips are padded to whole tiles.
+
+use crate::tpf::dds::{DdsDescription, DdsError};
+
+const TILE_SIZE: usize = 8;
+
+/// Rearranges tiled PS4 texture data into the linear layout of a DDS file, dropping the padding
+/// of mips that aren't a whole number of tiles.
+pub fn deswizzle_ps4(description: &DdsDescription, data: &[u8]) -> Result<Vec<u8>, DdsError> {
+    let block_bytes = description.format.block_bytes();
+    let block_length = if description.format.is_block_compressed() {
+        4
+    } else {
+        1
+    };
+    let faces = if description.cubemap { 6 } else { 1 };
+
+    let mut linear = Vec::with_capacity(description.data_size());
+    let mut source = 0;
+
+    for _ in 0..faces {
+        for level in 0..description.mipmaps.max(1) {
+            let width = (description.width >> level).max(1) as usize;
+            let height = (description.height >> level).max(1) as usize;
+            let blocks_wide = width.div_ceil(block_length);
+            let blocks_high = height.div_ceil(block_length);
+            let tiles_wide = blocks_wide.div_ceil(TILE_SIZE);
+            let tiles_high = blocks_high.div_ceil(TILE_SIZE);
+
+            let tiled_size = tiles_wide * tiles_high * TILE_SIZE * TILE_SIZE * block_bytes;
+            let tiled = data
+                .get(source..source + tiled_size)
+                .ok_or(DdsError::DataTooShort {
+                    expected: source + tiled_size,
+                    actual: data.len(),
+                })?;
+            source += tiled_size;
+
+            let start = linear.len();
+            linear.resize(start + blocks_wide * blocks_high * block_bytes, 0);
+            let mip = &mut linear[start..];
+
+            let blocks = tiled.chunks_exact(block_bytes);
+            for (index, block) in blocks.enumerate() {
+                let tile = index / (TILE_SIZE * TILE_SIZE);
+                let (x, y) = morton_decode(index % (TILE_SIZE * TILE_SIZE));
+                let x = tile % tiles_wide * TILE_SIZE + x;
+                let y = tile / tiles_wide * TILE_SIZE + y;
+
+                if x < blocks_wide && y < blocks_high {
+                    let target = (y * blocks_wide + x) * block_bytes;
+                    mip[target..target + block_bytes].copy_from_slice(block);
+                }
+            }
+        }
+    }
+
+    Ok(linear)
+}
+
+/// Splits a Morton index into its x coordinate in the even bits and y in the odd bits.
+fn morton_decode(index: usize) -> (usize, usize) {
+    let compact = |mut bits: usize| {
+        let mut value = 0;
+        for shift in 0..usize::BITS / 2 {
+            value |= (bits & 1) << shift;
+            bits >>= 2;
+        }
+
+        value
+    };
+
+    (compact(index), compact(index >> 1))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tpf::{
+        dds::{DdsDescription, DxgiFormat},
+        swizzle::deswizzle_ps4,
+    };
+
+    #[test]
+    pub fn deswizzles_ps4_tiles() {
+        // A 12x4 pixel A8 texture is padded to two 8x8 tiles
+        let description = DdsDescription {
+            format: DxgiFormat::A8Unorm,
+            width: 12,
+            height: 4,
+            mipmaps: 1,
+            cubemap: false,
+        };
+        let tiled: Vec<u8> = (0..128).collect();
+
+        let linear = deswizzle_ps4(&description, &tiled).expect("enough data");
+        assert_eq!(linear.len(), 48);
+        assert_eq!(linear[..8], [0, 1, 4, 5, 16, 17, 20, 21]);
+        assert_eq!(linear[8..12], [64, 65, 68, 69]);
+        assert_eq!(linear[12..16], [2, 3, 6, 7]);
+        assert_eq!(linear[36..40], [10, 11, 14, 15]);
+
+        assert!(deswizzle_ps4(&description, &tiled[..100]).is_err());
+    }
+}
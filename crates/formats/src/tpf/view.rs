@@ -0,0 +1,197 @@
+use std::borrow::Cow;
+
+use byteorder::LE;
+use encoding_rs::SHIFT_JIS;
+use zerocopy::{FromBytes, FromZeroes, Ref, U16, U32};
+
+use crate::{
+    io_ext::read_wide_cstring,
+    tpf::{dds::DdsError, linear_texture_data, TPFError, TPFPlatform, Texture, TextureHeader},
+};
+
+/// A borrowed view of a little endian TPF, which returns texture data as slices of the file
+/// instead of copying it.
+pub struct Tpf<'a> {
+    bytes: &'a [u8],
+
+    header: &'a Header,
+
+    entries: TextureEntries<'a>,
+}
+
+impl<'a> Tpf<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, TPFError> {
+        let (header, next) =
+            Ref::<_, Header>::new_from_prefix(bytes).ok_or(TPFError::OutOfBounds)?;
+        if &header.magic != b"TPF\0" {
+            return Err(TPFError::InvalidMagic(header.magic));
+        }
+        if header.encoding > 2 {
+            return Err(TPFError::UnsupportedEncoding(header.encoding));
+        }
+
+        let count = header.texture_count.get() as usize;
+        let entries = match TPFPlatform::from_u8(header.platform) {
+            None => return Err(TPFError::UnknownPlatform(header.platform)),
+            Some(TPFPlatform::Pc) => TextureEntries::Pc(
+                PcTextureEntry::slice_from_prefix(next, count)
+                    .ok_or(TPFError::OutOfBounds)?
+                    .0,
+            ),
+            Some(TPFPlatform::Ps4 | TPFPlatform::XboxOne) => TextureEntries::Console(
+                ConsoleTextureEntry::slice_from_prefix(next, count)
+                    .ok_or(TPFError::OutOfBounds)?
+                    .0,
+            ),
+            Some(TPFPlatform::Xbox360 | TPFPlatform::Ps3) => {
+                return Err(TPFError::UnsupportedPlatform(header.platform))
+            }
+        };
+
+        Ok(Self {
+            bytes,
+            header: header.into_ref(),
+            entries,
+        })
+    }
+
+    pub fn platform(&self) -> Option<TPFPlatform> {
+        TPFPlatform::from_u8(self.header.platform)
+    }
+
+    pub fn unk0d(&self) -> u8 {
+        self.header.unk0d
+    }
+
+    /// Texture names are UTF-16 if this is 1 and Shift-JIS otherwise.
+    pub fn encoding(&self) -> u8 {
+        self.header.encoding
+    }
+
+    pub fn texture_count(&self) -> usize {
+        self.header.texture_count.get() as usize
+    }
+
+    /// Reads the metadata and name of every texture.
+    pub fn textures(&self) -> impl Iterator<Item = Result<Texture, TPFError>> + '_ {
+        (0..self.texture_count()).map(|index| self.texture(index))
+    }
+
+    pub fn texture(&self, index: usize) -> Result<Texture, TPFError> {
+        let (common, name_offset, unk10, header) = match self.entries {
+            TextureEntries::Pc(entries) => {
+                let entry = entries.get(index).ok_or(TPFError::OutOfBounds)?;
+                (&entry.common, entry.name_offset, entry.unk10, None)
+            }
+            TextureEntries::Console(entries) => {
+                let entry = entries.get(index).ok_or(TPFError::OutOfBounds)?;
+                let header = TextureHeader {
+                    width: entry.width.get(),
+                    height: entry.height.get(),
+                    array_size: entry.array_size.get(),
+                    unk1: 0,
+                    unk2: entry.unk2.get(),
+                    dxgi_format: entry.dxgi_format.get(),
+                };
+
+                (&entry.common, entry.name_offset, entry.unk10, Some(header))
+            }
+        };
+
+        Ok(Texture {
+            data_offset: common.data_offset.get(),
+            data_size: common.data_size.get(),
+            format: common.format,
+            cubemap: common.cubemap,
+            mipmaps: common.mipmaps,
+            unk0b: common.unk0b,
+            unk10: unk10.get(),
+            header,
+            name: self.name(name_offset.get() as usize)?.into_owned(),
+        })
+    }
+
+    /// The data of a texture, borrowed from the file.
+    pub fn texture_data(&self, texture: &Texture) -> Result<&'a [u8], TPFError> {
+        let start = texture.data_offset as usize;
+        let end = start
+            .checked_add(texture.data_size as usize)
+            .ok_or(TPFError::OutOfBounds)?;
+
+        self.bytes.get(start..end).ok_or(TPFError::OutOfBounds)
+    }
+
+    /// See [`TPF::linear_data`](crate::tpf::TPF::linear_data).
+    pub fn linear_data<'d>(
+        &self,
+        texture: &Texture,
+        data: &'d [u8],
+    ) -> Result<Cow<'d, [u8]>, DdsError> {
+        linear_texture_data(self.platform(), texture, data)
+    }
+
+    fn name(&self, offset: usize) -> Result<Cow<'a, str>, TPFError> {
+        let bytes = self.bytes.get(offset..).ok_or(TPFError::OutOfBounds)?;
+
+        if self.encoding() == 1 {
+            Ok(Cow::Owned(read_wide_cstring::<LE>(bytes)?.to_string()))
+        } else {
+            let end = bytes
+                .iter()
+                .position(|byte| *byte == 0)
+                .ok_or(TPFError::OutOfBounds)?;
+            let (name, _) = SHIFT_JIS.decode_without_bom_handling(&bytes[..end]);
+            Ok(name)
+        }
+    }
+}
+
+enum TextureEntries<'a> {
+    Pc(&'a [PcTextureEntry]),
+    Console(&'a [ConsoleTextureEntry]),
+}
+
+#[derive(FromZeroes, FromBytes)]
+#[repr(C)]
+#[allow(unused)]
+struct Header {
+    magic: [u8; 4],
+    data_size: U32<LE>,
+    texture_count: U32<LE>,
+    platform: u8,
+    unk0d: u8,
+    encoding: u8,
+    unk0f: u8,
+}
+
+#[derive(FromZeroes, FromBytes)]
+#[repr(C)]
+struct TextureEntryCommon {
+    data_offset: U32<LE>,
+    data_size: U32<LE>,
+    format: u8,
+    cubemap: u8,
+    mipmaps: u8,
+    unk0b: u8,
+}
+
+#[derive(FromZeroes, FromBytes)]
+#[repr(C)]
+struct PcTextureEntry {
+    common: TextureEntryCommon,
+    name_offset: U32<LE>,
+    unk10: U32<LE>,
+}
+
+#[derive(FromZeroes, FromBytes)]
+#[repr(C)]
+struct ConsoleTextureEntry {
+    common: TextureEntryCommon,
+    width: U16<LE>,
+    height: U16<LE>,
+    array_size: U32<LE>,
+    unk2: U32<LE>,
+    name_offset: U32<LE>,
+    unk10: U32<LE>,
+    dxgi_format: U32<LE>,
+}
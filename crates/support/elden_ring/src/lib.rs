@@ -3,16 +3,57 @@ use std::{
     path::Path,
 };
 
-use aes::cipher::{BlockDecryptMut, KeyIvInit};
+use aes::cipher::{generic_array::GenericArray, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
 use fstools::formats::{bnd4::BND4, dcx::DcxHeader};
 
-pub fn decrypt_regulation(reader: &mut impl Read) -> io::Result<Vec<u8>> {
-    const REGULATION_KEY: &[u8; 32] = &[
+pub use self::regulation::Regulation;
+
+pub mod nightreign;
+mod regulation;
+
+/// AES-256 key used to encrypt the `regulation.bin` file of a game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RegulationKey(pub [u8; 32]);
+
+impl RegulationKey {
+    pub const ELDEN_RING: RegulationKey = RegulationKey([
         0x99, 0xBF, 0xFC, 0x36, 0x6A, 0x6B, 0xC8, 0xC6, 0xF5, 0x82, 0x7D, 0x09, 0x36, 0x02, 0xD6,
         0x76, 0xC4, 0x28, 0x92, 0xA0, 0x1C, 0x20, 0x7F, 0xB0, 0x24, 0xD3, 0xAF, 0x4E, 0x49, 0x3F,
         0xEF, 0x99,
-    ];
+    ]);
+
+    /// Parses a key from 64 hex digits. Whitespace between digits is ignored.
+    pub fn from_hex(hex: &str) -> io::Result<Self> {
+        let digits: Vec<u8> = hex
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| c.to_digit(16).map(|d| d as u8))
+            .collect::<Option<_>>()
+            .ok_or_else(|| io::Error::other("Regulation key contains non-hex characters"))?;
+
+        if digits.len() != 64 {
+            return Err(io::Error::other("Regulation key must be 32 bytes long"));
+        }
 
+        let mut key = [0u8; 32];
+        for (byte, pair) in key.iter_mut().zip(digits.chunks_exact(2)) {
+            *byte = (pair[0] << 4) | pair[1];
+        }
+
+        Ok(Self(key))
+    }
+}
+
+/// Decrypts an Elden Ring `regulation.bin`. See [`decrypt_regulation_with_key`].
+pub fn decrypt_regulation(reader: &mut impl Read) -> io::Result<Vec<u8>> {
+    decrypt_regulation_with_key(reader, &RegulationKey::ELDEN_RING)
+}
+
+/// Decrypts a `regulation.bin` file, returning the DCX compressed archive it contains.
+pub fn decrypt_regulation_with_key(
+    reader: &mut impl Read,
+    key: &RegulationKey,
+) -> io::Result<Vec<u8>> {
     let mut iv = [0u8; 16];
     reader.read_exact(&mut iv)?;
 
@@ -20,7 +61,7 @@ pub fn decrypt_regulation(reader: &mut impl Read) -> io::Result<Vec<u8>> {
     reader.read_to_end(&mut out_buf)?;
 
     type Aes256Cbc = cbc::Decryptor<aes::Aes256>;
-    let mut cipher = Aes256Cbc::new_from_slices(REGULATION_KEY, &iv).unwrap();
+    let mut cipher = Aes256Cbc::new(&key.0.into(), &iv.into());
 
     // SAFETY: GenericArray<u8, _> is safe to transmute from an equiv. slice of u8s
     unsafe {
@@ -30,6 +71,33 @@ pub fn decrypt_regulation(reader: &mut impl Read) -> io::Result<Vec<u8>> {
     Ok(out_buf)
 }
 
+/// Encrypts an Elden Ring regulation. See [`encrypt_regulation_with_key`].
+pub fn encrypt_regulation(data: &[u8]) -> io::Result<Vec<u8>> {
+    encrypt_regulation_with_key(data, &RegulationKey::ELDEN_RING)
+}
+
+/// Encrypts a DCX compressed regulation with AES-256-CBC and a random IV, producing the
+/// contents of a `regulation.bin` file.
+///
+/// The data is padded with zeroes to a multiple of the block size.
+pub fn encrypt_regulation_with_key(data: &[u8], key: &RegulationKey) -> io::Result<Vec<u8>> {
+    let mut iv = [0u8; 16];
+    getrandom::getrandom(&mut iv).map_err(io::Error::other)?;
+
+    let mut out_buf = Vec::with_capacity(iv.len() + data.len() + 16);
+    out_buf.extend_from_slice(&iv);
+    out_buf.extend_from_slice(data);
+    out_buf.resize(iv.len() + data.len().next_multiple_of(16), 0);
+
+    type Aes256Cbc = cbc::Encryptor<aes::Aes256>;
+    let mut cipher = Aes256Cbc::new(&key.0.into(), &iv.into());
+    for block in out_buf[iv.len()..].chunks_exact_mut(16) {
+        cipher.encrypt_block_mut(GenericArray::from_mut_slice(block));
+    }
+
+    Ok(out_buf)
+}
+
 pub fn load_regulation(game_path: impl AsRef<Path>) -> io::Result<BND4> {
     let regulation_bytes = std::fs::read(game_path.as_ref().join("regulation.bin"))?;
     let dcx_bytes = decrypt_regulation(&mut regulation_bytes.as_slice())?;
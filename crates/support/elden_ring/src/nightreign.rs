@@ -0,0 +1,342 @@
+//! Nightreign regulation support.
+//!
+//! Nightreign regulations use the same BND4 and param layout as Elden Ring and are handled by
+//! [`Regulation`]. They are compressed with [`DCX_ALGORITHM`] and contain params of the types in
+//! [`PARAM_TYPES`].
+//!
+//! The AES key is not bundled with this crate, so it has to be passed in by the caller, e.g. by
+//! parsing it with [`RegulationKey::from_hex`].
+
+use std::{io, path::Path};
+
+use crate::{Regulation, RegulationKey};
+
+/// DCX compression algorithm of Nightreign regulations, see [`Regulation::dcx_algorithm`].
+pub const DCX_ALGORITHM: [u8; 4] = *b"ZSTD";
+
+/// Paramdef types of the params in Nightreign regulations, sorted alphabetically.
+pub const PARAM_TYPES: &[&str] = &[
+    "ACROSS_DAY_CORRECT_PARAM_ST",
+    "ACTIONBUTTON_PARAM_ST",
+    "AI_SOUND_PARAM_ST",
+    "ANTIQUE_STAND_PARAM_ST",
+    "ASSET_GEOMETORY_PARAM_ST",
+    "ASSET_MATERIAL_SFX_PARAM_ST",
+    "ASSET_MODEL_SFX_PARAM_ST",
+    "ATK_PARAM_ST",
+    "ATTACHEFFECT_FILTER_CATEGORY_PARAM_ST",
+    "ATTACHEFFECT_FILTER_PARAM_ST",
+    "ATTACHEFFECT_PARAM_ST",
+    "ATTACHEFFECT_TABLE_PARAM_ST",
+    "ATTACK_ELEMENT_CORRECT_PARAM_ST",
+    "AUTO_CREATE_ENV_SOUND_PARAM_ST",
+    "BEHAVIOR_PARAM_ST",
+    "BONFIRE_WARP_PARAM_ST",
+    "BUDDY_PARAM_ST",
+    "BUDDY_STONE_PARAM_ST",
+    "BUDGET_PARAM_ST",
+    "BULLET_CREATE_LIMIT_PARAM_ST",
+    "BULLET_PARAM_ST",
+    "CACL_CORRECT_GRAPH_ST",
+    "CAMERA_FADE_PARAM_ST",
+    "CEREMONY_PARAM_ST",
+    "CHARACTER_INIT_PARAM",
+    "CHARMAKEMENUTOP_PARAM_ST",
+    "CHARMAKEMENU_LISTITEM_PARAM_ST",
+    "CHR_MODEL_PARAM_ST",
+    "CHR_PHYSICS_VELOCITY_CHANGE_ST",
+    "CLEAR_COUNT_CORRECT_PARAM_ST",
+    "COMMON_SYSTEM_PARAM_ST",
+    "COOL_TIME_PARAM_ST",
+    "CS_AA_QUALITY_DETAIL",
+    "CS_DECAL_QUALITY_DETAIL",
+    "CS_DOF_QUALITY_DETAIL",
+    "CS_EFFECT_QUALITY_DETAIL",
+    "CS_GRAPHICS_CONFIG_PARAM_ST",
+    "CS_KEY_ASSIGN_MENUITEM_PARAM",
+    "CS_LIGHTING_QUALITY_DETAIL",
+    "CS_MOTION_BLUR_QUALITY_DETAIL",
+    "CS_RAYTRACING_QUALITY_DETAIL",
+    "CS_REFLECTION_QUALITY_DETAIL",
+    "CS_SHADER_QUALITY_DETAIL",
+    "CS_SHADOW_QUALITY_DETAIL",
+    "CS_SSAO_QUALITY_DETAIL",
+    "CS_TEXTURE_FILTER_QUALITY_DETAIL",
+    "CS_VOLUMETRIC_EFFECT_QUALITY_DETAIL",
+    "CS_WATER_QUALITY_DETAIL",
+    "CUTSCENE_GPARAM_TIME_PARAM_ST",
+    "CUTSCENE_GPARAM_WEATHER_PARAM_ST",
+    "CUTSCENE_MAP_ID_PARAM_ST",
+    "CUTSCENE_TEXTURE_LOAD_PARAM_ST",
+    "CUTSCENE_TIMEZONE_CONVERT_PARAM_ST",
+    "CUTSCENE_WEATHER_OVERRIDE_GPARAM_ID_CONVERT_PARAM_ST",
+    "DECAL_PARAM_ST",
+    "DEFAULT_KEY_ASSIGN",
+    "DEFEAT_BOSS_SOUL_PARAM_ST",
+    "DIRECTION_CAMERA_PARAM_ST",
+    "ENEMY_COMMON_PARAM_ST",
+    "ENV_OBJ_LOT_PARAM_ST",
+    "EQUIP_MTRL_SET_PARAM_ST",
+    "EQUIP_PARAM_ACCESSORY_ST",
+    "EQUIP_PARAM_ANTIQUE_ST",
+    "EQUIP_PARAM_CUSTOM_WEAPON_ST",
+    "EQUIP_PARAM_GOODS_ST",
+    "EQUIP_PARAM_PROTECTOR_ST",
+    "EQUIP_PARAM_WEAPON_ST",
+    "ESTUS_FLASK_RECOVERY_PARAM_ST",
+    "EVENT_BGM_BOSSCHRID_CONVERT_PARAM_ST",
+    "EVENT_FLAG_USAGE_PARAM_ST",
+    "FACE_PARAM_ST",
+    "FACE_RANGE_PARAM_ST",
+    "FALL_CONTROL_PARAM_ST",
+    "FE_TEXT_EFFECT_PARAM_ST",
+    "FOOT_SFX_PARAM_ST",
+    "GAME_SYSTEM_COMMON_PARAM_ST",
+    "GESTURE_PARAM_ST",
+    "GPARAM_REF_SETTINGS_PARAM_ST",
+    "GRAPHICS_COMMON_PARAM_ST",
+    "GRASS_LOD_RANGE_PARAM_ST",
+    "GRASS_TYPE_PARAM_ST",
+    "HERO_MENU_CAMERA_PARAM_ST",
+    "HERO_MENU_PARAM_ST",
+    "HERO_OPERATION_EXPLANATION_PARAM_ST",
+    "HERO_PARAM_ST",
+    "HERO_STATUS_PARAM",
+    "HIT_EFFECT_SFX_CONCEPT_PARAM_ST",
+    "HIT_EFFECT_SFX_PARAM_ST",
+    "HIT_MTRL_PARAM_ST",
+    "ITEMLOT_PARAM_ST",
+    "ITEMTABLE_PARAM_ST",
+    "KEY_ASSIGN_PARAM_ST",
+    "KNOCKBACK_PARAM_ST",
+    "KNOWLEDGE_LOADSCREEN_ITEM_PARAM_ST",
+    "LEGACY_DISTANT_VIEW_PARTS_REPLACE_PARAM",
+    "LIMITED_DAY_ASSET_PARAM_ST",
+    "LOAD_BALANCER_DRAW_DIST_SCALE_PARAM_ST",
+    "LOAD_BALANCER_NEW_DRAW_DIST_SCALE_PARAM_ST",
+    "LOAD_BALANCER_PARAM_ST",
+    "LOBBY_MENU_NPC_PARAM_ST",
+    "LOBBY_MENU_PARAM_ST",
+    "LOCK_CAM_PARAM_ST",
+    "LOT_BASE_MAP_PATTERN_FLAG_ST",
+    "LOT_BASE_SMALBASE_AND_SPOT_ST",
+    "LOT_RESULT_MAP_PATTERN_FLAG_ST",
+    "LOT_RESULT_PLAYAREA_PARAM_ST",
+    "LOT_RESULT_SMALLBASE_AND_SPOT_ST",
+    "MAGIC_PARAM_ST",
+    "MAGIC_TABLE_PARAM_ST",
+    "MAIN_SCENARIO_MENU_PARAM_ST",
+    "MANUAL_MAP_PATTERN_INFO_ST",
+    "MAP_DEFAULT_INFO_PARAM_ST",
+    "MAP_GD_REGION_DRAW_PARAM",
+    "MAP_GD_REGION_ID_PARAM_ST",
+    "MAP_GRID_CREATE_HEIGHT_LIMIT_INFO_PARAM_ST",
+    "MAP_MIMICRY_ESTABLISHMENT_PARAM_ST",
+    "MAP_NAME_TEX_PARAM_ST",
+    "MAP_PATTERN_COMMON_ST",
+    "MAP_PATTERN_MAP_LOT_CONDITION_ST",
+    "MAP_PATTERN_SET_PARAM_ST",
+    "MAP_PIECE_TEX_PARAM_ST",
+    "MATERIAL_EX_PARAM_ST",
+    "MENUPROPERTY_LAYOUT",
+    "MENUPROPERTY_SPEC",
+    "MENU_COMMON_PARAM_ST",
+    "MENU_OFFSCR_REND_PARAM_ST",
+    "MENU_PARAM_COLOR_TABLE_ST",
+    "MENU_VALUE_TABLE_SPEC",
+    "MENU_WINDOW_PARAM_ST",
+    "MESSAGEBOX_PARAM_ST",
+    "MIMICRY_ESTABLISHMENT_TEX_PARAM_ST",
+    "MISSION_MANAGEMENT_PARAM_ST",
+    "MIXCRAFT_MAGIC_PARAM_ST",
+    "MOVE_PARAM_ST",
+    "MULTI_ESTUS_FLASK_BONUS_PARAM_ST",
+    "MULTI_PLAY_CORRECTION_PARAM_ST",
+    "MULTI_SOUL_BONUS_RATE_PARAM_ST",
+    "NETWORK_MSG_PARAM_ST",
+    "NETWORK_PARAM_ST",
+    "NIGHT_BOSS_MENU_PARAM_ST",
+    "NPCBOT_TABLE_PARAM_ST",
+    "NPC_AI_ACTION_PARAM_ST",
+    "NPC_AI_BEHAVIOR_PROBABILITY_PARAM_ST",
+    "NPC_PARAM_ST",
+    "NPC_THINK_PARAM_ST",
+    "OBJ_ACT_PARAM_ST",
+    "OPERATION_GUIDE_PARAM_ST",
+    "OVERLAY_MATERIAL_PARAM_ST",
+    "PARTS_DRAW_PARAM_ST",
+    "PERMANENT_BUFF_PARAM_ST",
+    "PERSONAL_SCENARIO_PARAM_ST",
+    "PHANTOM_PARAM_ST",
+    "PIN_TYPE_PARAM_ST",
+    "PLAYAREA_GIANT_ASSET_CTRL_PARAM_ST",
+    "PLAYER_COMMON_PARAM_ST",
+    "PLAY_AREA_CREATE_COMMON_PARAM_ST",
+    "PLAY_AREA_CREATE_DEFAULT_PARAM_ST",
+    "PLAY_AREA_CREATE_PARAM_ST",
+    "PLAY_REGION_PARAM_ST",
+    "POSTURE_CONTROL_PARAM_PRO_ST",
+    "POSTURE_CONTROL_PARAM_WEP_LEFT_ST",
+    "POSTURE_CONTROL_PARAM_WEP_RIGHT_ST",
+    "RANDOM_APPEAR_PARAM_ST",
+    "RARE_MAP_INFO_MENU_PARAM_ST",
+    "REINFORCE_PARAM_PROTECTOR_ST",
+    "REINFORCE_PARAM_WEAPON_ST",
+    "RESIST_CORRECT_PARAM_ST",
+    "RESULT_MENU_PLAY_SPEED_PARAM_ST",
+    "REVERB_AUX_SEND_BUS_PARAM_ST",
+    "RIDE_PARAM_ST",
+    "ROLLING_OBJ_LOT_PARAM_ST",
+    "RUNTIME_BONE_CONTROL_PARAM_ST",
+    "SCENARIO_PLACEMENT_PARAM_ST",
+    "SCRATCHED_PARTS_PARAM_ST",
+    "SESSION_RANDOM_ASSET_TEX_REPLACE_PARAM_ST",
+    "SE_ACTIVATION_RANGE_PARAM_ST",
+    "SE_MATERIAL_CONVERT_PARAM_ST",
+    "SFX_BLOCK_RES_SHARE_PARAM",
+    "SHOP_LINEUP_PARAM",
+    "SIGN_PUDDLE_PARAM_ST",
+    "SMALBASE_SFX_LOD_EXCLUSION_LIST_PARAM_ST",
+    "SMALLBASE_AND_SPOT_ATTACH_POINT_ST",
+    "SMALLBASE_AND_SPOT_DEFINE_ST",
+    "SMALLBASE_INVATION_NPC_PARAM_ST",
+    "SMALLBASE_LOT_MAP_COMBINATION_PARAM_ST",
+    "SMALLBASE_MAP_VARIATION_PARAM_ST",
+    "SORTIE_STARTING_POINT_ST",
+    "SOUND_ASSET_SOUND_OBJ_ENABLE_DIST_PARAM_ST",
+    "SOUND_AUTO_ENV_SOUND_GROUP_PARAM_ST",
+    "SOUND_AUTO_REVERB_EVALUATION_DIST_PARAM_ST",
+    "SOUND_AUTO_REVERB_SELECT_PARAM_ST",
+    "SOUND_CHR_PHYSICS_SE_PARAM_ST",
+    "SOUND_COMMON_INGAME_PARAM_ST",
+    "SOUND_COMMON_SYSTEM_PARAM_ST",
+    "SOUND_CUTSCENE_PARAM_ST",
+    "SPEEDTREE_MODEL_PARAM_ST",
+    "SP_EFFECT_PARAM_ST",
+    "SP_EFFECT_SET_PARAM_ST",
+    "SP_EFFECT_VFX_PARAM_ST",
+    "SUBSTORY_LINK_PARAM_ST",
+    "SWORDARTS_TABLE_PARAM_ST",
+    "SWORD_ARTS_PARAM_ST",
+    "TALK_PARAM_ST",
+    "TEXT_EMBED_IMAGE_PARAM_ST",
+    "THROW_COUNTER_PARAM_ST",
+    "THROW_DIRECTION_SFX_PARAM_ST",
+    "THROW_PARAM_ST",
+    "TOUGHNESS_PARAM_ST",
+    "TUTORIAL_CATEGORY_PARAM_ST",
+    "TUTORIAL_PARAM_ST",
+    "USER_DISP_LOG_PARAM_ST",
+    "WAYPOINT_PARAM_ST",
+    "WEATHER_ASSET_CREATE_PARAM_ST",
+    "WEATHER_ASSET_REPLACE_PARAM_ST",
+    "WEATHER_LOT_PARAM_ST",
+    "WEATHER_LOT_TEX_PARAM_ST",
+    "WEATHER_PARAM_ST",
+    "WEP_ABSORP_POS_PARAM_ST",
+    "WET_ASPECT_PARAM_ST",
+    "WHITE_SIGN_COOL_TIME_PARAM_ST",
+    "WORLD_MAP_LEGACY_CONV_PARAM_ST",
+    "WORLD_MAP_PIECE_PARAM_ST",
+    "WORLD_MAP_PLACE_NAME_PARAM_ST",
+    "WORLD_MAP_POINT_ICON_PARAM_ST",
+    "WORLD_MAP_POINT_PARAM_ST",
+    "WWISE_VALUE_TO_STR_CONVERT_PARAM_ST",
+];
+
+/// Reads the `regulation.bin` file in the given Nightreign game directory.
+pub fn load_regulation(game_path: impl AsRef<Path>, key: &RegulationKey) -> io::Result<Regulation> {
+    Regulation::load_with_key(game_path, key)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use fstools::formats::{
+        bnd4::{BND4File, BND4},
+        dcx::DcxHeader,
+        param::{ParamBuilder, ParamRow},
+    };
+
+    use super::{DCX_ALGORITHM, PARAM_TYPES};
+    use crate::{encrypt_regulation_with_key, Regulation, RegulationKey};
+
+    /// A DCX header as found in Nightreign's `regulation.bin`.
+    fn dcx_header() -> DcxHeader {
+        let mut header = b"DCX\0".to_vec();
+        for value in [0x11000u32, 0x18, 0x24, 0x44, 0x4C] {
+            header.extend_from_slice(&value.to_be_bytes());
+        }
+        header.extend_from_slice(b"DCS\0");
+        header.extend_from_slice(&[0; 8]);
+        header.extend_from_slice(b"DCP\0");
+        header.extend_from_slice(&DCX_ALGORITHM);
+        header.extend_from_slice(&0x20u32.to_be_bytes());
+        header.extend_from_slice(&[0; 20]);
+        header.extend_from_slice(b"DCA\0");
+        header.extend_from_slice(&8u32.to_be_bytes());
+
+        DcxHeader::read(Cursor::new(header))
+            .expect("valid header")
+            .0
+    }
+
+    #[test]
+    pub fn reads_nightreign_regulations() {
+        assert!(PARAM_TYPES.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let mut param = ParamBuilder::new("EQUIP_PARAM_WEAPON_ST", false, true, true);
+        param.add_row(ParamRow {
+            id: 1,
+            name: None,
+            data: vec![1; 4],
+        });
+        let files = [BND4File {
+            flags: 0x40,
+            unk4: -1,
+            id: 0,
+            path: "N:\\CL\\data\\Param\\param\\GameParam\\EquipParamWeapon.param".to_string(),
+            data: param.to_bytes().expect("writable param"),
+        }];
+        let archive = BND4 {
+            unk04: 0,
+            unk05: 1,
+            unk0a: 0,
+            file_count: 0,
+            file_headers_offset: 0x40,
+            version: 0,
+            file_header_size: 0x24,
+            file_headers_end: 0,
+            unicode: true,
+            raw_format: 0x74,
+            extended: 4,
+            buckets_offset: 0,
+            files: Vec::new(),
+            data: Vec::new(),
+        };
+        let mut bnd4_bytes = Vec::new();
+        archive.write(&mut bnd4_bytes, &files).expect("written");
+
+        let key = RegulationKey([0x5A; 32]);
+        let dcx_bytes = dcx_header().compress(&bnd4_bytes).expect("compressed");
+        let encrypted = encrypt_regulation_with_key(&dcx_bytes, &key).expect("encrypted");
+
+        let regulation = Regulation::from_encrypted_with_key(&encrypted, &key).expect("valid");
+        assert_eq!(regulation.dcx_algorithm(), &DCX_ALGORITHM);
+        for name in regulation.param_names() {
+            let param = regulation.param(name).expect("valid param");
+            assert!(PARAM_TYPES.binary_search(&param.param_type()).is_ok());
+        }
+
+        let rewritten = regulation.to_encrypted_bytes().expect("encrypted");
+        let regulation = Regulation::from_encrypted_with_key(&rewritten, &key).expect("valid");
+        assert_eq!(
+            regulation
+                .param("EquipParamWeapon")
+                .expect("found")
+                .row_count(),
+            1
+        );
+    }
+}
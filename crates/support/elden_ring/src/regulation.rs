@@ -0,0 +1,150 @@
+use std::{
+    io::{self, Read, Write},
+    path::Path,
+};
+
+use fstools::formats::{
+    bnd4::{BND4File, BND4},
+    dcx::DcxHeader,
+    param::{self, ParamBuilder, ParamCommon},
+};
+
+use crate::{decrypt_regulation_with_key, encrypt_regulation_with_key, RegulationKey};
+
+/// A decrypted and decompressed `regulation.bin`, holding the param files of the game.
+///
+/// Params are addressed by the stem of their path in the archive, e.g. `EquipParamWeapon`.
+///
+/// The regulation is written back with the key it was read with, and with the DCX compression
+/// algorithm of the original file.
+pub struct Regulation {
+    key: RegulationKey,
+    dcx_header: DcxHeader,
+    bnd4: BND4,
+    files: Vec<BND4File>,
+}
+
+impl Regulation {
+    /// Reads the `regulation.bin` file in the given Elden Ring game directory.
+    pub fn load(game_path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::load_with_key(game_path, &RegulationKey::ELDEN_RING)
+    }
+
+    /// Reads the `regulation.bin` file in the given game directory.
+    pub fn load_with_key(game_path: impl AsRef<Path>, key: &RegulationKey) -> io::Result<Self> {
+        let regulation_bytes = std::fs::read(game_path.as_ref().join("regulation.bin"))?;
+        Self::from_encrypted_with_key(&regulation_bytes, key)
+    }
+
+    /// Decrypts, decompresses and unpacks the contents of an Elden Ring `regulation.bin` file.
+    pub fn from_encrypted(bytes: &[u8]) -> io::Result<Self> {
+        Self::from_encrypted_with_key(bytes, &RegulationKey::ELDEN_RING)
+    }
+
+    /// Decrypts, decompresses and unpacks the contents of a `regulation.bin` file.
+    pub fn from_encrypted_with_key(bytes: &[u8], key: &RegulationKey) -> io::Result<Self> {
+        let dcx_bytes = decrypt_regulation_with_key(&mut &bytes[..], key)?;
+
+        let (dcx_header, mut dcx_decoder) = DcxHeader::read(io::Cursor::new(dcx_bytes))
+            .map_err(|_| io::Error::other("DCX header reading failed"))?;
+
+        let mut bnd4_bytes = Vec::with_capacity(dcx_decoder.hint_size());
+        dcx_decoder.read_to_end(&mut bnd4_bytes)?;
+
+        let bnd4 = BND4::from_reader(io::Cursor::new(bnd4_bytes))
+            .map_err(|_| io::Error::other("Failed to read regulation BND4"))?;
+        let files = bnd4.owned_files();
+
+        Ok(Self {
+            key: *key,
+            dcx_header,
+            bnd4,
+            files,
+        })
+    }
+
+    /// The key this regulation is encrypted with.
+    pub fn key(&self) -> &RegulationKey {
+        &self.key
+    }
+
+    /// Magic of the DCX compression algorithm this regulation is compressed with, e.g. `ZSTD`.
+    pub fn dcx_algorithm(&self) -> &[u8; 4] {
+        self.dcx_header.compression_parameters().algorithm()
+    }
+
+    /// Iterates over the names of the params in this regulation.
+    pub fn param_names(&self) -> impl Iterator<Item = &str> {
+        self.files.iter().map(|file| param_name(&file.path))
+    }
+
+    /// Returns the raw bytes of the param with the given name.
+    pub fn param_bytes(&self, name: &str) -> Option<&[u8]> {
+        self.file(name).map(|file| file.data.as_slice())
+    }
+
+    /// Parses the param with the given name.
+    pub fn param(&self, name: &str) -> io::Result<Box<dyn ParamCommon<'_> + '_>> {
+        let bytes = self.param_bytes(name).ok_or_else(|| not_found(name))?;
+        param::parse_dyn(bytes).map_err(io::Error::other)
+    }
+
+    /// Parses the param with the given name into an owned, modifiable [`ParamBuilder`].
+    pub fn param_builder(&self, name: &str) -> io::Result<ParamBuilder> {
+        self.param(name)?.to_builder().map_err(io::Error::other)
+    }
+
+    /// Replaces the contents of the param with the given name.
+    pub fn set_param_bytes(&mut self, name: &str, bytes: Vec<u8>) -> io::Result<()> {
+        let file = self
+            .files
+            .iter_mut()
+            .find(|file| param_name(&file.path).eq_ignore_ascii_case(name))
+            .ok_or_else(|| not_found(name))?;
+
+        file.data = bytes;
+        Ok(())
+    }
+
+    /// Replaces the param with the given name with the serialized contents of `param`.
+    pub fn set_param(&mut self, name: &str, param: &ParamBuilder) -> io::Result<()> {
+        self.set_param_bytes(name, param.to_bytes()?)
+    }
+
+    /// Repacks, compresses and encrypts this regulation, producing the contents of a
+    /// `regulation.bin` file.
+    pub fn to_encrypted_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut bnd4_bytes = Vec::new();
+        self.bnd4.write(&mut bnd4_bytes, &self.files)?;
+
+        let dcx_bytes = self
+            .dcx_header
+            .compress(&bnd4_bytes)
+            .map_err(io::Error::other)?;
+
+        encrypt_regulation_with_key(&dcx_bytes, &self.key)
+    }
+
+    /// Writes this regulation as an encrypted `regulation.bin` file.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(&self.to_encrypted_bytes()?)
+    }
+
+    fn file(&self, name: &str) -> Option<&BND4File> {
+        self.files
+            .iter()
+            .find(|file| param_name(&file.path).eq_ignore_ascii_case(name))
+    }
+}
+
+/// Gets the param name from a path such as `N:\GR\data\Param\param\GameParam\ActionButtonParam.param`.
+fn param_name(path: &str) -> &str {
+    let file_name = path.rsplit(['\\', '/']).next().unwrap_or(path);
+    file_name
+        .rsplit_once('.')
+        .map_or(file_name, |(stem, _)| stem)
+}
+
+fn not_found(name: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("No param named {name}"))
+}
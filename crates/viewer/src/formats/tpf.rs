@@ -12,7 +12,7 @@ use bevy::{
     },
     utils::BoxedFuture,
 };
-use fstools_formats::tpf::TPF;
+use fstools_formats::tpf::{TPFError, TPF};
 use thiserror::Error;
 
 use crate::formats::TpfPlugin;
@@ -25,6 +25,9 @@ pub enum TPFAssetLoaderError {
     #[error("Could not load tpf: {0}")]
     Io(#[from] std::io::Error),
 
+    #[error("Could not parse tpf: {0}")]
+    Tpf(#[from] TPFError),
+
     #[error("Could not load tpf texture: {0}")]
     TextureParse(#[from] TextureError),
 }
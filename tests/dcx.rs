@@ -9,7 +9,7 @@ use std::{
 
 use fstools::{dvdbnd, formats::dcx::DcxHeader, prelude::*};
 use fstools_dvdbnd::GameType::EldenRing;
-use fstools_elden_ring_support::decrypt_regulation;
+use fstools_elden_ring_support::{decrypt_regulation, nightreign, RegulationKey};
 use fstools_formats::dcx::DcxError;
 use insta::assert_snapshot;
 use libtest_mimic::{Arguments, Failed, Trial};
@@ -48,6 +48,16 @@ fn main() -> Result<(), Box<dyn Error>> {
         check_regulation(&reg_path)
     }));
 
+    // Nightreign regulations are only checked when a game directory and key are given
+    if let (Ok(nr_path), Ok(nr_key)) =
+        (std::env::var("NR_PATH"), std::env::var("NR_REGULATION_KEY"))
+    {
+        let key = RegulationKey::from_hex(&nr_key)?;
+        tests.push(Trial::test("nightreign/regulation.bin", move || {
+            check_nightreign_regulation(Path::new(&nr_path), &key)
+        }));
+    }
+
     libtest_mimic::run(&args, tests).exit();
 }
 
@@ -57,6 +67,25 @@ pub fn check_regulation(path: &Path) -> Result<(), Failed> {
     check_dcx(io::Cursor::new(dcx_bytes))
 }
 
+pub fn check_nightreign_regulation(path: &Path, key: &RegulationKey) -> Result<(), Failed> {
+    let regulation = nightreign::load_regulation(path, key)?;
+    if regulation.dcx_algorithm() != &nightreign::DCX_ALGORITHM {
+        return Err("unexpected regulation DCX algorithm".into());
+    }
+
+    for name in regulation.param_names() {
+        let param_type = regulation.param(name)?.param_type().to_string();
+        if nightreign::PARAM_TYPES
+            .binary_search(&param_type.as_str())
+            .is_err()
+        {
+            return Err(format!("unknown param type {param_type} of {name}").into());
+        }
+    }
+
+    Ok(())
+}
+
 pub fn check_file(vfs: Arc<DvdBnd>, file: &Path) -> Result<(), Failed> {
     let file = match vfs.open(file.to_string_lossy().as_ref()) {
         Ok(file) => file,
@@ -0,0 +1,88 @@
+use std::{
+    error::Error,
+    ffi::OsStr,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use fstools::{formats::dcx::DcxHeader, prelude::*};
+use fstools_dvdbnd::GameType::{self, EldenRing, Nightreign};
+use fstools_formats::{bnd4::BND4, flver::owned::Flver};
+use libtest_mimic::{Arguments, Failed, Trial};
+
+/// Rewrites every FLVER in the character and parts archives of the game and checks that the
+/// output matches the original byte-for-byte.
+///
+/// Nightreign models are only checked if `NR_PATH` and `NR_KEYS_PATH` are set.
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Arguments::from_args();
+    let mut tests = vec![];
+
+    let er_path = PathBuf::from(std::env::var("ER_PATH").expect("er_path"));
+    let er_keys_path = PathBuf::from(std::env::var("ER_KEYS_PATH").expect("er_keys_path"));
+    tests.extend(game_tests(EldenRing, er_path, er_keys_path)?);
+
+    if let (Ok(nr_path), Ok(nr_keys_path)) =
+        (std::env::var("NR_PATH"), std::env::var("NR_KEYS_PATH"))
+    {
+        tests.extend(game_tests(Nightreign, nr_path.into(), nr_keys_path.into())?);
+    }
+
+    libtest_mimic::run(&args, tests).exit();
+}
+
+fn game_tests(
+    game_type: GameType,
+    game_path: PathBuf,
+    keys_path: PathBuf,
+) -> Result<Vec<Trial>, Box<dyn Error>> {
+    let vfs = Arc::new(DvdBnd::create_from_game(
+        game_type,
+        game_path,
+        FileKeyProvider::new(keys_path),
+    )?);
+
+    let tests = DvdBnd::dictionary_from_game(game_type)
+        .filter(|line| line.extension() == Some(OsStr::new("dcx")))
+        .filter(|line| {
+            line.to_string_lossy().ends_with(".chrbnd.dcx")
+                || line.to_string_lossy().ends_with(".partsbnd.dcx")
+        })
+        .map(|line| {
+            let vfs = vfs.clone();
+            Trial::test(line.to_string_lossy().to_string(), move || {
+                check_archive(&vfs, &line)
+            })
+            .with_kind("flver")
+        })
+        .collect();
+
+    Ok(tests)
+}
+
+pub fn check_archive(vfs: &DvdBnd, file: &Path) -> Result<(), Failed> {
+    let Ok(reader) = vfs.open(file.to_string_lossy().as_ref()) else {
+        return Ok(());
+    };
+
+    let (_, mut decoder) =
+        DcxHeader::read(reader).map_err(|_| Failed::from("failed to parse DCX header"))?;
+    let mut bnd4_bytes = Vec::new();
+    decoder.read_to_end(&mut bnd4_bytes)?;
+    let bnd4 = BND4::from_reader(io::Cursor::new(bnd4_bytes))?;
+
+    for entry in bnd4
+        .files
+        .iter()
+        .filter(|entry| entry.path.ends_with(".flver"))
+    {
+        let bytes = bnd4.file_bytes(entry);
+        let flver = Flver::parse(bytes)?;
+        if flver.to_bytes()? != bytes {
+            return Err(format!("{} was not rewritten byte-for-byte", entry.path).into());
+        }
+    }
+
+    Ok(())
+}